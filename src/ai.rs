@@ -0,0 +1,274 @@
+// Opponent for the adversarial mode: after every player move one cross steps
+// to a neighbouring free cell.
+//
+// How it picks the step depends on `Strength`:
+// - Easy takes the step that brings the crosses closest to a losing line,
+//   and never completes a line while it has any other step, so a duel is lost
+//   by being cornered rather than by a sudden jump.
+// - Normal and Hard search a few turns ahead with minimax and alpha-beta
+//   pruning, assuming the player answers every cross step with their best
+//   move. They do complete lines whenever that wins.
+//
+// Positions are the same (row, col) vectors the solver and game use; leaves
+// are scored by how far the player still is from winning (the solver's lower
+// bound) against how far the crosses are from a line.
+//
+// "How far from a line" is greedy rather than exact: for every line the board
+// allows, each of its cells takes the nearest cross not used yet, and the
+// distances are summed. That keeps a step cheap even with many crosses.
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::heuristics::LowerBound;
+use crate::movement::legal_moves;
+use crate::rules::{check_lose_flat, is_win_flat};
+
+const DIRS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+/// One cross move: index of the cross and the cell it steps to.
+type Step = (usize, (usize, usize));
+
+// Scores of decided positions, far beyond anything `evaluate` returns
+const WIN: i64 = 1_000_000;
+// Stand-in for the player's distance to a win when none is reachable
+const UNREACHABLE: i64 = 100;
+
+/// How hard the crosses play in the adversarial mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strength {
+    #[default]
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Strength {
+    /// Plies searched by minimax (cross steps and player moves each count
+    /// one); Easy does not search.
+    fn depth(self) -> usize {
+        match self {
+            Strength::Easy => 0,
+            Strength::Normal => 2,
+            Strength::Hard => 4,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Strength::Easy => "easy",
+            Strength::Normal => "normal",
+            Strength::Hard => "hard",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Some(Strength::Easy),
+            "normal" => Some(Strength::Normal),
+            "hard" => Some(Strength::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// Greedy distance of the crosses from the nearest losing line.
+fn line_distance(
+    crosses: &[(usize, usize)],
+    lines: &[Vec<(usize, usize)>],
+    board: &Board,
+) -> usize {
+    // crosses that fell into a hole can't help any more, nor can blocks
+    let crosses: Vec<(usize, usize)> = board
+        .line_crosses(crosses)
+        .iter()
+        .copied()
+        .filter(|&(r, c)| board.has_cell(r as isize, c as isize))
+        .collect();
+    let mut best = usize::MAX;
+    for line in lines {
+        let mut used = vec![false; crosses.len()];
+        let mut total = 0usize;
+        for &cell in line {
+            let nearest = (0..crosses.len())
+                .filter(|&i| !used[i])
+                .min_by_key(|&i| board.distance(crosses[i], cell));
+            let Some(i) = nearest else {
+                return usize::MAX;
+            };
+            used[i] = true;
+            total += board.distance(crosses[i], cell);
+        }
+        best = best.min(total);
+    }
+    best
+}
+
+fn flat(v: &[(usize, usize)], board: &Board) -> Vec<usize> {
+    v.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+}
+
+/// Every single-cell cross step onto a free cell; heavy crosses and neutral
+/// blocks stay put.
+fn cross_steps(circles: &[(usize, usize)], crosses: &[(usize, usize)], board: &Board) -> Vec<Step> {
+    let mut steps = Vec::new();
+    for (i, &(r, c)) in crosses.iter().enumerate() {
+        if !board.has_cell(r as isize, c as isize)
+            || board.is_heavy(r, c)
+            || board.is_block(i, crosses.len())
+        {
+            continue;
+        }
+        for (dr, dc) in DIRS {
+            let (nr, nc) = board.wrap(r as isize + dr, c as isize + dc);
+            if !board.has_cell(nr, nc) {
+                continue;
+            }
+            let to = (nr as usize, nc as usize);
+            if !circles.contains(&to) && !crosses.contains(&to) {
+                steps.push((i, to));
+            }
+        }
+    }
+    steps
+}
+
+/// Shared state of one minimax search.
+struct Search<'a> {
+    board: &'a Board,
+    player_idx: usize,
+    lines: Vec<Vec<(usize, usize)>>,
+    bound: LowerBound<'a>,
+}
+
+impl Search<'_> {
+    /// Score of an undecided position from the crosses' point of view.
+    fn evaluate(&self, circles: &[(usize, usize)], crosses: &[(usize, usize)]) -> i64 {
+        let to_win = self
+            .bound
+            .estimate(circles, crosses, self.player_idx)
+            .map_or(UNREACHABLE, |d| d as i64);
+        let to_line =
+            line_distance(crosses, &self.lines, self.board).min(UNREACHABLE as usize) as i64;
+        4 * to_win - to_line
+    }
+
+    /// Minimax value with alpha-beta pruning. `crosses_turn` says who moves
+    /// next; a side without a legal move passes.
+    fn value(
+        &self,
+        circles: &[(usize, usize)],
+        crosses: &[(usize, usize)],
+        depth: usize,
+        crosses_turn: bool,
+        mut alpha: i64,
+        mut beta: i64,
+    ) -> i64 {
+        // quicker results count for more, so both sides hurry to a win
+        if is_win_flat(&flat(circles, self.board), self.board) {
+            return -WIN - depth as i64;
+        }
+        if check_lose_flat(&flat(crosses, self.board), self.board) {
+            return WIN + depth as i64;
+        }
+        if depth == 0 {
+            return self.evaluate(circles, crosses);
+        }
+
+        if crosses_turn {
+            let steps = cross_steps(circles, crosses, self.board);
+            if steps.is_empty() {
+                return self.value(circles, crosses, depth - 1, false, alpha, beta);
+            }
+            let mut best = i64::MIN;
+            for (i, to) in steps {
+                let mut next = crosses.to_vec();
+                next[i] = to;
+                best = best.max(self.value(circles, &next, depth - 1, false, alpha, beta));
+                alpha = alpha.max(best);
+                if alpha >= beta {
+                    break;
+                }
+            }
+            best
+        } else {
+            let mut best = i64::MAX;
+            let moves = legal_moves(circles, crosses, self.player_idx, 0, self.board);
+            for (_, m) in &moves {
+                best = best.min(self.value(&m.circles, &m.crosses, depth - 1, true, alpha, beta));
+                beta = beta.min(best);
+                if alpha >= beta {
+                    return best;
+                }
+            }
+            if moves.is_empty() {
+                return self.value(circles, crosses, depth - 1, true, alpha, beta);
+            }
+            best
+        }
+    }
+}
+
+/// Easy strength: the step that brings the crosses closest to a line,
+/// completing one only when every step would.
+fn greedy_step(
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    board: &Board,
+) -> Option<Step> {
+    let lines = board.rules.rule_set().losing_lines(board);
+    // (completes a line, distance afterwards, step)
+    let mut best: Option<(bool, usize, Step)> = None;
+    for (i, to) in cross_steps(circles, crosses, board) {
+        let mut moved = crosses.to_vec();
+        moved[i] = to;
+        let candidate = (
+            check_lose_flat(&flat(&moved, board), board),
+            line_distance(&moved, &lines, board),
+            (i, to),
+        );
+        if best.is_none_or(|b| (candidate.0, candidate.1) < (b.0, b.1)) {
+            best = Some(candidate);
+        }
+    }
+    best.map(|(_, _, step)| step)
+}
+
+/// Let the crosses answer the player's last move by stepping one of them.
+/// Nothing happens once the game is decided or when no cross can move.
+pub fn respond(
+    circles: &[(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    player_idx: usize,
+    board: &Board,
+) {
+    if is_win_flat(&flat(circles, board), board) || check_lose_flat(&flat(crosses, board), board) {
+        return;
+    }
+
+    let depth = board.rules.ai_strength.depth();
+    let step = if depth == 0 {
+        greedy_step(circles, crosses, board)
+    } else {
+        let search = Search {
+            board,
+            player_idx,
+            lines: board.rules.rule_set().losing_lines(board),
+            bound: LowerBound::new(board),
+        };
+        let mut best: Option<(i64, Step)> = None;
+        for (i, to) in cross_steps(circles, crosses, board) {
+            let mut next = crosses.to_vec();
+            next[i] = to;
+            let alpha = best.map_or(i64::MIN, |b| b.0);
+            let v = search.value(circles, &next, depth - 1, false, alpha, i64::MAX);
+            if best.is_none_or(|b| v > b.0) {
+                best = Some((v, (i, to)));
+            }
+        }
+        best.map(|(_, step)| step)
+    };
+    if let Some((i, to)) = step {
+        crosses[i] = to;
+    }
+}
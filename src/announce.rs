@@ -0,0 +1,123 @@
+// Plain-text descriptions of moves for the screen-reader mode.
+//
+// With `screen_reader` set in the config, every move is followed by one line
+// such as "player moved up, pushed cross to row 3 col 5; warning: two crosses
+// aligned in column 2", which the game shows on the top row and `--plain`
+// prints under the board. Rows and columns are counted from 1, the way they
+// are read out, and the line only mentions what changed.
+use crate::board::Board;
+
+/// Positions of the circles (player included) and crosses.
+pub type Pieces<'a> = (&'a [(usize, usize)], &'a [(usize, usize)]);
+
+pub fn direction_name(dr: isize, dc: isize) -> &'static str {
+    match (dr.signum(), dc.signum()) {
+        (-1, 0) => "up",
+        (1, 0) => "down",
+        (0, -1) => "left",
+        _ => "right",
+    }
+}
+
+fn cell_name((r, c): (usize, usize)) -> String {
+    format!("row {} col {}", r + 1, c + 1)
+}
+
+/// The player's move from `before` to `after`: where the player went and
+/// every piece it pushed or pulled along.
+pub fn describe_move(
+    player_idx: usize,
+    (dr, dc): (isize, isize),
+    pull: bool,
+    before: Pieces,
+    after: Pieces,
+) -> String {
+    let mut parts = vec![format!(
+        "player {} {} to {}",
+        if pull { "pulled" } else { "moved" },
+        direction_name(dr, dc),
+        cell_name(after.0[player_idx])
+    )];
+    let verb = if pull { "pulled" } else { "pushed" };
+    for (i, (a, b)) in after.0.iter().zip(before.0).enumerate() {
+        if i != player_idx && a != b {
+            parts.push(format!("{} circle to {}", verb, cell_name(*a)));
+        }
+    }
+    if after.1.len() < before.1.len() {
+        // a bomb went off and the lists no longer line up
+        parts.push(describe_blast(before.1.len() - after.1.len()));
+    } else {
+        for (a, b) in after.1.iter().zip(before.1) {
+            if a != b {
+                parts.push(format!("{} cross to {}", verb, cell_name(*a)));
+            }
+        }
+    }
+    parts.join(", ")
+}
+
+/// A bomb that cleared `count` crosses.
+pub fn describe_blast(count: usize) -> String {
+    format!(
+        "a bomb cleared {} cross{}",
+        count,
+        if count == 1 { "" } else { "es" }
+    )
+}
+
+/// A power-up used on the cross at `cell`.
+pub fn describe_removal(cell: (usize, usize)) -> String {
+    format!("player used a power-up on the cross at {}", cell_name(cell))
+}
+
+/// The crosses' reply under the adversarial rule, if one of them moved.
+pub fn describe_reply(before: &[(usize, usize)], after: &[(usize, usize)]) -> Option<String> {
+    let (a, b) = after.iter().zip(before).find(|(a, b)| a != b)?;
+    Some(format!(
+        "a cross moved from {} to {}",
+        cell_name(*b),
+        cell_name(*a)
+    ))
+}
+
+/// Losing lines holding all crosses but one, with the last cell still
+/// empty; one description per line of the board.
+pub fn warnings(board: &Board, (circles, crosses): Pieces) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for line in board.rules.rule_set().losing_lines(board) {
+        let on_cross = line
+            .iter()
+            .filter(|p| board.line_crosses(crosses).contains(p))
+            .count();
+        let empty = line
+            .iter()
+            .any(|p| !crosses.contains(p) && !circles.contains(p));
+        if on_cross + 1 != line.len() || !empty {
+            continue;
+        }
+        let count = if on_cross == 2 { "two" } else { "three" };
+        let ((r0, c0), (r1, c1)) = (line[0], line[1]);
+        let text = if r0 == r1 {
+            format!("{} crosses aligned in row {}", count, r0 + 1)
+        } else if c0 == c1 {
+            format!("{} crosses aligned in column {}", count, c0 + 1)
+        } else {
+            format!("{} crosses aligned on a diagonal", count)
+        };
+        if !found.contains(&text) {
+            found.push(text);
+        }
+    }
+    found
+}
+
+/// `text` followed by the warnings for `pieces`, as a single line.
+pub fn with_warnings(mut text: String, board: &Board, pieces: Pieces) -> String {
+    let warnings = warnings(board, pieces);
+    if !warnings.is_empty() {
+        text.push_str("; warning: ");
+        text.push_str(&warnings.join(", "));
+    }
+    text
+}
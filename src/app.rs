@@ -0,0 +1,151 @@
+// Screens and the loop that drives them.
+//
+// A screen is its state plus two functions: `update`, a reducer that applies
+// one `AppEvent` to the state and says whether the screen changed or is
+// finished, and `draw`, which renders the state. `run` owns everything in
+// between: the frame clock, turning terminal input into events, asking the
+// screen for results of its background work, and redrawing only when
+// something changed (see `tick`). A new screen implements `Screen` and is
+// started with `run`; screens that lead to other screens return a choice and
+// let their caller run the next one.
+//
+// Screens never touch the terminal themselves. Input arrives as `AppEvent`s
+// from a `Frontend` and drawing goes through whichever ratatui backend it
+// brings, so another frontend (e.g. a browser canvas fed by xterm.js key
+// events) can drive the same screens. The terminal is the only one so far.
+use std::error::Error;
+use std::io::Stdout;
+
+use crossterm::event::{Event, KeyEvent};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::{Frame, Terminal};
+
+use crate::generator::GeneratedPuzzle;
+use crate::leaderboard::TopTimes;
+use crate::term::Tui;
+use crate::tick::{Ticker, draw_if_dirty};
+
+pub enum AppEvent {
+    KeyPressed(KeyEvent),
+    /// Sent once per frame, after that frame's input
+    Tick,
+    /// A puzzle the screen was waiting for (see `Screen::poll`)
+    GenerationDone(Box<GeneratedPuzzle>),
+    /// The day's top times after a daily puzzle result was sent
+    TopTimes(TopTimes),
+    /// The terminal changed size; every screen lays itself out afresh
+    Resize,
+    /// The terminal window gained (`true`) or lost focus, on terminals that
+    /// report it
+    Focus(bool),
+}
+
+impl AppEvent {
+    /// The event for a terminal event, if the screens care about it.
+    fn from_terminal(event: Event) -> Option<Self> {
+        match event {
+            Event::Key(key) => Some(AppEvent::KeyPressed(key)),
+            Event::Resize(..) => Some(AppEvent::Resize),
+            Event::FocusGained => Some(AppEvent::Focus(true)),
+            Event::FocusLost => Some(AppEvent::Focus(false)),
+            _ => None,
+        }
+    }
+}
+
+/// What a screen's reducer made of an event.
+pub enum Step<T> {
+    /// Nothing on screen changed
+    Idle,
+    Redraw,
+    /// The screen is finished and hands back its result
+    Done(T),
+}
+
+pub trait Screen {
+    type Output;
+
+    /// Apply one event to the state.
+    fn update(&mut self, event: AppEvent) -> Step<Self::Output>;
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>);
+
+    /// Result of the screen's background work, checked once per frame before
+    /// the input is handled.
+    fn poll(&mut self) -> Option<AppEvent> {
+        None
+    }
+}
+
+/// Where screens get their input from and draw to.
+pub trait Frontend {
+    type Backend: Backend;
+
+    fn terminal(&mut self) -> &mut Terminal<Self::Backend>;
+
+    /// Wait for the next frame and return the input that arrived until then.
+    fn events(&mut self) -> Result<Vec<AppEvent>, Box<dyn Error>>;
+}
+
+/// The terminal: crossterm input on the frame clock (see `tick`).
+pub struct TermFrontend<'a> {
+    terminal: &'a mut Tui,
+    ticker: Ticker,
+}
+
+impl<'a> TermFrontend<'a> {
+    pub fn new(terminal: &'a mut Tui) -> Self {
+        TermFrontend {
+            terminal,
+            ticker: Ticker::default(),
+        }
+    }
+}
+
+impl Frontend for TermFrontend<'_> {
+    type Backend = CrosstermBackend<Stdout>;
+
+    fn terminal(&mut self) -> &mut Tui {
+        self.terminal
+    }
+
+    fn events(&mut self) -> Result<Vec<AppEvent>, Box<dyn Error>> {
+        let events = self.ticker.events()?;
+        Ok(events
+            .into_iter()
+            .filter_map(AppEvent::from_terminal)
+            .collect())
+    }
+}
+
+/// Run `screen` in the terminal; see `run_on`.
+pub fn run<S: Screen>(terminal: &mut Tui, screen: &mut S) -> Result<S::Output, Box<dyn Error>> {
+    run_on(&mut TermFrontend::new(terminal), screen)
+}
+
+/// Run `screen` until its reducer returns `Step::Done`. The screen keeps its
+/// state afterwards, so a menu can be shown again where the player left it.
+pub fn run_on<F: Frontend, S: Screen>(
+    frontend: &mut F,
+    screen: &mut S,
+) -> Result<S::Output, Box<dyn Error>> {
+    let mut dirty = true;
+    loop {
+        draw_if_dirty(frontend.terminal(), &mut dirty, |f| screen.draw(f))?;
+
+        let mut events: Vec<AppEvent> = screen.poll().into_iter().collect();
+        events.extend(frontend.events()?);
+        events.push(AppEvent::Tick);
+        for event in events {
+            // input always redraws; ticks only when the screen says so
+            if matches!(event, AppEvent::KeyPressed(_) | AppEvent::Resize) {
+                dirty = true;
+            }
+            match screen.update(event) {
+                Step::Idle => {}
+                Step::Redraw => dirty = true,
+                Step::Done(output) => return Ok(output),
+            }
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// The generated puzzle in progress, kept on disk so it can be resumed.
+//
+// The game writes `tic-tac-go-autosave.json` in the working directory after
+// every move, so quitting and closing the terminal alike leave the latest
+// position behind. On the next launch the player is asked whether to resume
+// it. Finishing the puzzle, or leaving it for a new one, deletes the file.
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Position;
+use crate::generator::{Setup, Undos};
+use crate::puzzle::PuzzleFile;
+
+pub const AUTOSAVE_FILE: &str = "tic-tac-go-autosave.json";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Autosave {
+    pub setup: Setup,
+    pub seed: u64,
+    pub par: Option<usize>,
+    /// The day, for a daily puzzle
+    pub daily: Option<u64>,
+    /// Board, rules and pieces as the puzzle started
+    pub puzzle: PuzzleFile,
+    /// The position when saved, and the positions before each move
+    pub position: Position,
+    pub history: Vec<Position>,
+    /// Whether each move spent a power-up
+    #[serde(default)]
+    pub removals: Vec<bool>,
+    pub moves: usize,
+    pub undos: Undos,
+    pub undos_used: usize,
+    /// Time spent on the puzzle so far
+    pub seconds: f64,
+}
+
+impl Autosave {
+    /// The saved puzzle, if there is one. An unreadable file is logged and
+    /// otherwise treated as missing.
+    pub fn load() -> Option<Self> {
+        let contents = fs::read_to_string(AUTOSAVE_FILE).ok()?;
+        serde_json::from_str(&contents)
+            .map_err(|e| log::warn!("{}: {}; ignoring it", AUTOSAVE_FILE, e))
+            .ok()
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(Path::new(AUTOSAVE_FILE), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Forget the saved puzzle.
+pub fn delete() {
+    match fs::remove_file(AUTOSAVE_FILE) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => {
+            log::warn!("could not delete {}: {}", AUTOSAVE_FILE, e)
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,112 @@
+// `tic-tac-go bench`: rough timings for board creation, puzzle generation and
+// solving, so performance regressions in the search code are easy to spot.
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::generator::{self, Difficulty};
+
+const DEFAULT_ITERATIONS: usize = 5;
+const SOLVER_MAX_NODES: usize = 500_000;
+const SIZES: [(usize, usize); 3] = [(4, 5), (6, 7), (8, 10)];
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+fn print_usage() {
+    eprintln!("usage: tic-tac-go bench [--iterations N]");
+    eprintln!();
+    eprintln!("Times Board::random, puzzle generation and the solver for each");
+    eprintln!("difficulty and a few board sizes, averaged over N runs.");
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--iterations" | "-n" => {
+                iterations = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("--iterations expects a positive number")?;
+            }
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(());
+            }
+            other => {
+                print_usage();
+                return Err(format!("unknown option {}", other).into());
+            }
+        }
+    }
+
+    println!(
+        "{:<7} {:<10} {:>11} {:>14} {:>11} {:>10} {:>8}",
+        "size", "difficulty", "board (ms)", "generate (ms)", "solve (ms)", "avg moves", "solved"
+    );
+    for &(rows, cols) in &SIZES {
+        for &difficulty in &DIFFICULTIES {
+            let mut board_time = Duration::ZERO;
+            let mut gen_time = Duration::ZERO;
+            let mut solve_time = Duration::ZERO;
+            let mut solved = 0usize;
+            let mut total_moves = 0usize;
+
+            for _ in 0..iterations {
+                let start = Instant::now();
+                let board = Board::random_sized(rows, cols);
+                board_time += start.elapsed();
+
+                let start = Instant::now();
+                let (circles_flat, crosses_flat, player_idx) =
+                    generator::generate_puzzle_constructive(&board, difficulty);
+                gen_time += start.elapsed();
+
+                if circles_flat.is_empty() {
+                    continue;
+                }
+                let circles: Vec<(usize, usize)> =
+                    circles_flat.iter().map(|&f| board.from_flat(f)).collect();
+                let crosses: Vec<(usize, usize)> =
+                    crosses_flat.iter().map(|&f| board.from_flat(f)).collect();
+
+                let start = Instant::now();
+                let result = generator::solve_min_moves(
+                    &board,
+                    &circles,
+                    &crosses,
+                    player_idx,
+                    SOLVER_MAX_NODES,
+                );
+                solve_time += start.elapsed();
+                if let Some(moves) = result {
+                    solved += 1;
+                    total_moves += moves;
+                }
+            }
+
+            let n = iterations as f64;
+            let avg_moves = if solved > 0 {
+                format!("{:.1}", total_moves as f64 / solved as f64)
+            } else {
+                "-".to_string()
+            };
+            println!(
+                "{:<7} {:<10} {:>11.2} {:>14.2} {:>11.2} {:>10} {:>8}",
+                format!("{}x{}", rows, cols),
+                format!("{:?}", difficulty),
+                millis(board_time) / n,
+                millis(gen_time) / n,
+                millis(solve_time) / n,
+                avg_moves,
+                format!("{}/{}", solved, iterations)
+            );
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,71 @@
 // Board utilities for tic-tac-go
-use rand::{Rng, thread_rng};
 use rand::seq::SliceRandom;
+use rand::{Rng, thread_rng};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
+use std::fmt;
+
+use crate::rules::{MIN_CIRCLES, Rules};
+
+/// The two cells of a portal pair.
+pub type PortalPair = ((usize, usize), (usize, usize));
 
+/// How random boards are carved out of the full rectangle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Carving {
+    /// Share of the cells turned into holes, in percent; each board draws a
+    /// value from this range
+    pub hole_percent: (usize, usize),
+    /// Number of random-walk blobs the holes grow from, also drawn per board
+    pub blobs: (usize, usize),
+    /// Reconnect cells cut off by holes with straight corridors; otherwise
+    /// they become holes too, leaving only the largest region
+    pub corridors: bool,
+}
+
+impl Default for Carving {
+    fn default() -> Self {
+        Carving {
+            hole_percent: (6, 16),
+            blobs: (1, 3),
+            corridors: true,
+        }
+    }
+}
+
+/// Why `Board::validate` rejected a board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardError {
+    /// Row widths, offsets and the per-cell masks disagree
+    Layout(String),
+    /// Fewer present cells than the circles plus one free cell
+    TooFewCells(usize),
+    /// Holes and walls split the board into this many regions
+    Split(usize),
+    /// No three present cells in a line, so no puzzle can be won
+    NoLine,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::Layout(msg) => write!(f, "inconsistent board layout: {}", msg),
+            BoardError::TooFewCells(n) => write!(
+                f,
+                "board has {} cells, at least {} are needed",
+                n,
+                MIN_CIRCLES + 1
+            ),
+            BoardError::Split(n) => write!(f, "board is split into {} separate regions", n),
+            BoardError::NoLine => write!(f, "board has no room for three in a row"),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+#[derive(Clone, Debug)]
 pub struct Board {
     pub rows: usize,
     pub cols: usize,
@@ -10,17 +74,68 @@ pub struct Board {
     pub total_cells: usize,
     // per-cell existence mask: true == cell exists / is playable
     pub cells: Vec<bool>,
-    pub default_grid_w: u16,
-    pub default_grid_h: u16,
+    // per-cell ice mask: pushed pieces keep sliding across ice cells
+    pub ice: Vec<bool>,
+    // per-cell wall mask: walls are missing cells too, but drawn as solid blocks
+    pub walls: Vec<bool>,
+    // per-cell portal link: flat index of the paired portal cell, if any
+    pub portals: Vec<Option<usize>>,
+    // per-cell goal mask: cells the circles must cover under the goal-cell rules
+    pub goals: Vec<bool>,
+    // per-cell heavy mask: cells holding a heavy cross, which is never moved
+    pub heavy: Vec<bool>,
+    // per-cell power-up mask: the player picks one up by stepping on the cell
+    pub powerups: Vec<bool>,
+    // per-cell bomb mask: a piece pushed onto the cell sets it off
+    pub bombs: Vec<bool>,
+    // how many of the crosses, counted from the end of the list, are neutral
+    // blocks: pushed like crosses, but never part of a line
+    pub blocks: usize,
+    pub rules: Rules,
 }
 
 impl Board {
-    pub fn random() -> Self {
-        let mut rng = thread_rng();
+    pub fn random_from(carving: &Carving, rng: &mut impl Rng) -> Self {
         let rows: usize = rng.gen_range(3..=8);
         let min_cols = 20_usize.div_ceil(rows);
         let max_cols = min_cols + 8;
         let cols: usize = rng.gen_range(min_cols..=max_cols);
+        Self::random_sized_from(rows, cols, carving, rng)
+    }
+
+    /// Random board with fixed dimensions; holes are carved the same way as in
+    /// `Board::random`.
+    pub fn random_sized(rows: usize, cols: usize) -> Self {
+        Self::random_sized_from(rows, cols, &Carving::default(), &mut thread_rng())
+    }
+
+    pub fn random_sized_from(
+        rows: usize,
+        cols: usize,
+        carving: &Carving,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut board = Self::random_with_holes_from(rows, cols, carving, rng);
+        // now and then a few interior walls
+        if rng.gen_bool(0.35) {
+            board.place_walls_from(rng.gen_range(1..=3), rng);
+        }
+        board
+    }
+
+    /// Random board with fixed dimensions and holes carved as `carving` says.
+    pub fn random_with_holes_from(
+        rows: usize,
+        cols: usize,
+        carving: &Carving,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let (lo, hi) = carving.hole_percent;
+        let hole_frac = if lo < hi {
+            rng.gen_range(lo as f64 / 100.0..hi as f64 / 100.0)
+        } else {
+            lo as f64 / 100.0
+        };
 
         let row_widths = vec![cols; rows];
 
@@ -30,25 +145,24 @@ impl Board {
         }
         let total_cells = row_offsets[rows - 1] + row_widths[rows - 1];
 
-        let default_grid_w: u16 = (4 * cols + 1) as u16;
-        let default_grid_h: u16 = (2 * rows + 1) as u16;
-
         // start with all cells present
         let mut cells = vec![true; total_cells];
 
         // Decide roughly how many holes to carve out (as fraction of total cells)
-        let hole_frac: f64 = rng.gen_range(0.06..0.16); // 6%..16% holes
         let mut target_holes = ((total_cells as f64) * hole_frac).round() as usize;
-        if target_holes == 0 && total_cells > 8 {
+        if target_holes == 0 && hole_frac > 0.0 && total_cells > 8 {
             target_holes = 1;
         }
         target_holes = std::cmp::min(target_holes, total_cells.saturating_sub(6));
 
         if target_holes > 0 {
-            // seeded random-walk blobs
-            let seeds = rng.gen_range(1..=3);
+            // seeded random-walk blobs; drawn as u32, which keeps the boards
+            // of existing seeds
+            let lo = carving.blobs.0.max(1);
+            let hi = carving.blobs.1.max(lo);
+            let seeds = rng.gen_range(lo as u32..=hi as u32);
 
-            let to_rc = |mut idx: usize| -> (usize, usize) {
+            let to_rc = |idx: usize| -> (usize, usize) {
                 let mut r = 0usize;
                 while r < rows {
                     let start = row_offsets[r];
@@ -111,7 +225,7 @@ impl Board {
                     if neighbors.is_empty() {
                         break;
                     }
-                    neighbors.shuffle(&mut rng);
+                    neighbors.shuffle(rng);
                     cur = *neighbors.first().unwrap();
                 }
 
@@ -120,97 +234,519 @@ impl Board {
                     seeds_pos[seed_idx] = rng.gen_range(0..total_cells);
                 }
             }
+        }
 
-            // Ensure the remaining cells are globally connected; if not, carve simple corridors
-            // Build components of present cells
-            let mut seen = vec![false; total_cells];
-            let mut components: Vec<Vec<usize>> = Vec::new();
-            for i in 0..total_cells {
-                if !cells[i] || seen[i] {
-                    continue;
+        let mut board = Board {
+            rows,
+            cols,
+            row_widths,
+            row_offsets,
+            total_cells,
+            cells,
+            ice: vec![false; total_cells],
+            walls: vec![false; total_cells],
+            portals: vec![None; total_cells],
+            goals: vec![false; total_cells],
+            heavy: vec![false; total_cells],
+            powerups: vec![false; total_cells],
+            bombs: vec![false; total_cells],
+            blocks: 0,
+            rules: Rules::default(),
+        };
+        if target_holes > 0 {
+            board.join_regions(carving.corridors);
+        }
+        board
+    }
+
+    /// Join every region cut off by holes to the largest one with a simple
+    /// Manhattan corridor, or with `corridors` off turn them into holes.
+    fn join_regions(&mut self, corridors: bool) {
+        let components = self.components();
+        if components.len() < 2 {
+            return;
+        }
+        let mut largest_idx = 0usize;
+        for (i, comp) in components.iter().enumerate() {
+            if comp.len() > components[largest_idx].len() {
+                largest_idx = i;
+            }
+        }
+        for (i, comp) in components.iter().enumerate() {
+            if i == largest_idx {
+                continue;
+            }
+            if !corridors {
+                for &(r, c) in comp {
+                    let idx = self.to_flat(r, c);
+                    self.cells[idx] = false;
                 }
-                // BFS
-                let mut q = vec![i];
-                seen[i] = true;
-                let mut qi = 0usize;
-                while qi < q.len() {
-                    let cur = q[qi];
-                    qi += 1;
-                    let (r, c) = to_rc(cur);
-                    if c > 0 {
-                        let n = rc_to_idx(r, c - 1);
-                        if cells[n] && !seen[n] { seen[n] = true; q.push(n); }
+                continue;
+            }
+            // find the nearest cell in the largest region
+            let (sr, sc) = comp[0];
+            let mut best = None;
+            let mut best_dist = usize::MAX;
+            for &(tr, tc) in &components[largest_idx] {
+                let dist = sr.abs_diff(tr) + sc.abs_diff(tc);
+                if dist < best_dist {
+                    best_dist = dist;
+                    best = Some((tr, tc));
+                }
+            }
+            if let Some((tr, tc)) = best {
+                let (mut r, mut c) = (sr, sc);
+                while r != tr {
+                    if r < tr {
+                        r += 1;
+                    } else {
+                        r -= 1;
                     }
-                    if c + 1 < row_widths[r] {
-                        let n = rc_to_idx(r, c + 1);
-                        if cells[n] && !seen[n] { seen[n] = true; q.push(n); }
+                    if c >= self.row_widths[r] {
+                        c = self.row_widths[r].saturating_sub(1);
                     }
-                    if r > 0 && c < row_widths[r - 1] {
-                        let n = rc_to_idx(r - 1, c);
-                        if cells[n] && !seen[n] { seen[n] = true; q.push(n); }
+                    let idx = self.to_flat(r, c);
+                    self.cells[idx] = true;
+                }
+                while c != tc {
+                    if c < tc {
+                        c += 1;
+                    } else {
+                        c -= 1;
                     }
-                    if r + 1 < rows && c < row_widths[r + 1] {
-                        let n = rc_to_idx(r + 1, c);
-                        if cells[n] && !seen[n] { seen[n] = true; q.push(n); }
+                    if c >= self.row_widths[r] {
+                        break;
                     }
+                    let idx = self.to_flat(r, c);
+                    self.cells[idx] = true;
                 }
-                components.push(q);
             }
+        }
+    }
 
-            if components.len() > 1 {
-                // connect smaller components to the largest with simple manhattan corridors
-                let mut largest_idx = 0usize;
-                for (i, comp) in components.iter().enumerate() {
-                    if comp.len() > components[largest_idx].len() { largest_idx = i; }
-                }
-                for (i, comp) in components.iter().enumerate() {
-                    if i == largest_idx { continue; }
-                    let src = comp[0];
-                    // find nearest cell in largest
-                    let mut best = None;
-                    let mut best_dist = usize::MAX;
-                    for &t in &components[largest_idx] {
-                        let (sr, sc) = to_rc(src);
-                        let (tr, tc) = to_rc(t);
-                        let dist = sr.abs_diff(tr) + sc.abs_diff(tc);
-                        if dist < best_dist { best_dist = dist; best = Some(t); }
-                    }
-                    if let Some(dest) = best {
-                        let (mut r, mut c) = to_rc(src);
-                        let (tr, tc) = to_rc(dest);
-                        while r != tr {
-                            if r < tr { r += 1; } else { r -= 1; }
-                            if c >= row_widths[r] { c = row_widths[r].saturating_sub(1); }
-                            let idx = rc_to_idx(r, c);
-                            if !cells[idx] { cells[idx] = true; }
-                        }
-                        while c != tc {
-                            if c < tc { c += 1; } else { c -= 1; }
-                            if c >= row_widths[r] { break; }
-                            let idx = rc_to_idx(r, c);
-                            if !cells[idx] { cells[idx] = true; }
-                        }
-                    }
+    /// Build a rectangular board of `rows` x `cols` with the given cells removed.
+    /// Used when loading puzzle files, which only store dimensions and holes.
+    pub fn from_dims(
+        rows: usize,
+        cols: usize,
+        removed: &[(usize, usize)],
+    ) -> Result<Self, Box<dyn Error>> {
+        if rows == 0 || cols == 0 {
+            return Err("Invalid rows or cols".into());
+        }
+        let row_widths = vec![cols; rows];
+        let mut row_offsets = vec![0usize; rows];
+        for i in 1..rows {
+            row_offsets[i] = row_offsets[i - 1] + row_widths[i - 1];
+        }
+        let total_cells = row_offsets[rows - 1] + row_widths[rows - 1];
+        let mut cells = vec![true; total_cells];
+        for &(r, c) in removed.iter() {
+            if r < rows && c < cols {
+                let idx = row_offsets[r] + c;
+                if idx < total_cells {
+                    cells[idx] = false;
                 }
             }
         }
-
-        Board {
+        Ok(Board {
             rows,
             cols,
             row_widths,
             row_offsets,
             total_cells,
             cells,
-            default_grid_w,
-            default_grid_h,
+            ice: vec![false; total_cells],
+            walls: vec![false; total_cells],
+            portals: vec![None; total_cells],
+            goals: vec![false; total_cells],
+            heavy: vec![false; total_cells],
+            powerups: vec![false; total_cells],
+            bombs: vec![false; total_cells],
+            blocks: 0,
+            rules: Rules::default(),
+        })
+    }
+
+    /// Turn up to `count` interior cells into walls, skipping any whose loss
+    /// would split the board in two.
+    pub fn place_walls_from(&mut self, count: usize, rng: &mut impl Rng) {
+        let mut candidates: Vec<usize> = (0..self.total_cells)
+            .filter(|&i| {
+                let (r, c) = self.from_flat(i);
+                self.cells[i] && r > 0 && c > 0 && r + 1 < self.rows && c + 1 < self.row_widths[r]
+            })
+            .collect();
+        candidates.shuffle(rng);
+        let mut placed = 0;
+        for idx in candidates {
+            if placed == count {
+                break;
+            }
+            self.cells[idx] = false;
+            if self.is_connected() {
+                self.walls[idx] = true;
+                self.ice[idx] = false;
+                placed += 1;
+            } else {
+                self.cells[idx] = true;
+            }
+        }
+    }
+
+    /// Present cells grouped into 4-connected regions, joined across the
+    /// seam on toroidal boards. Each region lists its cells in breadth-first
+    /// order from its first cell in row-major order.
+    pub fn components(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut seen = vec![false; self.total_cells];
+        let mut components = Vec::new();
+        for start in 0..self.total_cells {
+            if !self.cells[start] || seen[start] {
+                continue;
+            }
+            seen[start] = true;
+            let mut region = vec![self.from_flat(start)];
+            let mut next = 0;
+            while next < region.len() {
+                let (r, c) = region[next];
+                next += 1;
+                for (dr, dc) in [(0isize, -1isize), (0, 1), (-1, 0), (1, 0)] {
+                    let (nr, nc) = self.wrap(r as isize + dr, c as isize + dc);
+                    if !self.has_cell(nr, nc) {
+                        continue;
+                    }
+                    let n = self.to_flat(nr as usize, nc as usize);
+                    if !seen[n] {
+                        seen[n] = true;
+                        region.push((nr as usize, nc as usize));
+                    }
+                }
+            }
+            components.push(region);
+        }
+        components
+    }
+
+    /// Whether all present cells form one region (see `components`).
+    pub fn is_connected(&self) -> bool {
+        self.components().len() <= 1
+    }
+
+    /// Check the invariants the rest of the game relies on: a consistent
+    /// layout, enough cells for the pieces, a single region, and at least one
+    /// line of three a win could be made on.
+    pub fn validate(&self) -> Result<(), BoardError> {
+        if self.row_widths.len() != self.rows || self.row_offsets.len() != self.rows {
+            return Err(BoardError::Layout(format!(
+                "{} rows but {} widths and {} offsets",
+                self.rows,
+                self.row_widths.len(),
+                self.row_offsets.len()
+            )));
+        }
+        let mut offset = 0;
+        for r in 0..self.rows {
+            if self.row_offsets[r] != offset {
+                return Err(BoardError::Layout(format!(
+                    "row {} starts at {} instead of {}",
+                    r, self.row_offsets[r], offset
+                )));
+            }
+            if self.row_widths[r] > self.cols {
+                return Err(BoardError::Layout(format!(
+                    "row {} is {} wide on a {}-column board",
+                    r, self.row_widths[r], self.cols
+                )));
+            }
+            offset += self.row_widths[r];
+        }
+        let masks = [
+            self.cells.len(),
+            self.ice.len(),
+            self.walls.len(),
+            self.portals.len(),
+            self.goals.len(),
+            self.heavy.len(),
+            self.powerups.len(),
+            self.bombs.len(),
+        ];
+        if self.total_cells != offset || masks.iter().any(|&len| len != offset) {
+            return Err(BoardError::Layout(format!(
+                "{} cells in the rows but {} in total",
+                offset, self.total_cells
+            )));
+        }
+        let present = self.cells.iter().filter(|&&c| c).count();
+        if present < MIN_CIRCLES + 1 {
+            return Err(BoardError::TooFewCells(present));
         }
+        let regions = self.components().len();
+        if regions > 1 {
+            return Err(BoardError::Split(regions));
+        }
+        if crate::rules::lines(self, 3).is_empty() {
+            return Err(BoardError::NoLine);
+        }
+        Ok(())
+    }
+
+    /// Turn the given cells into walls; cells outside the board are ignored.
+    pub fn set_walls(&mut self, cells: &[(usize, usize)]) {
+        for &(r, c) in cells {
+            if r < self.rows && c < self.row_widths[r] {
+                let idx = self.to_flat(r, c);
+                self.cells[idx] = false;
+                self.walls[idx] = true;
+            }
+        }
+    }
+
+    /// Positions of all wall cells, row by row.
+    pub fn wall_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.total_cells)
+            .filter(|&i| self.walls[i])
+            .map(|i| self.from_flat(i))
+            .collect()
+    }
+
+    /// Whether (r, c) is a hole: inside its row but not a cell, and not a wall.
+    pub fn is_hole(&self, r: isize, c: isize) -> bool {
+        r >= 0
+            && c >= 0
+            && (r as usize) < self.rows
+            && (c as usize) < self.row_widths[r as usize]
+            && !self.has_cell(r, c)
+            && !self.is_wall(r as usize, c as usize)
+    }
+
+    pub fn is_wall(&self, r: usize, c: usize) -> bool {
+        r < self.rows && c < self.row_widths[r] && self.walls[self.to_flat(r, c)]
+    }
+
+    /// Turn roughly `ice_frac` of the present cells into ice, laid out as short
+    /// straight strips so that pushed pieces have somewhere to slide.
+    pub fn scatter_ice_from(&mut self, ice_frac: f64, rng: &mut impl Rng) {
+        let present = self.cells.iter().filter(|&&c| c).count();
+        let target = ((present as f64) * ice_frac).round() as usize;
+        let mut placed = 0usize;
+        let mut attempts = 0usize;
+        while placed < target && attempts < target * 20 {
+            attempts += 1;
+            let (mut r, mut c) = self.from_flat(rng.gen_range(0..self.total_cells));
+            let (dr, dc) = *[(0isize, 1isize), (1, 0)].choose(rng).unwrap();
+            for _ in 0..rng.gen_range(2..=4) {
+                if !self.is_cell_present(r, c) {
+                    break;
+                }
+                let idx = self.to_flat(r, c);
+                if !self.ice[idx] {
+                    self.ice[idx] = true;
+                    placed += 1;
+                    if placed >= target {
+                        break;
+                    }
+                }
+                let (nr, nc) = (r as isize + dr, c as isize + dc);
+                if !self.has_cell(nr, nc) {
+                    break;
+                }
+                (r, c) = (nr as usize, nc as usize);
+            }
+        }
+    }
+
+    /// Mark the given cells as ice; cells outside the board or removed are
+    /// ignored.
+    pub fn set_ice(&mut self, cells: &[(usize, usize)]) {
+        for &(r, c) in cells {
+            if self.has_cell(r as isize, c as isize) {
+                let idx = self.to_flat(r, c);
+                self.ice[idx] = true;
+            }
+        }
+    }
+
+    /// Positions of all ice cells, row by row.
+    pub fn ice_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.total_cells)
+            .filter(|&i| self.ice[i])
+            .map(|i| self.from_flat(i))
+            .collect()
+    }
+
+    pub fn has_ice(&self) -> bool {
+        self.ice.iter().any(|&i| i)
+    }
+
+    /// Whether (r, c) is an ice cell; false for anything off the board.
+    pub fn is_ice(&self, r: isize, c: isize) -> bool {
+        self.has_cell(r, c) && self.ice[self.to_flat(r as usize, c as usize)]
+    }
+
+    /// Link each pair of cells as portals. Pairs with an end that is off the
+    /// board, removed, or already a portal are ignored.
+    pub fn set_portals(&mut self, pairs: &[PortalPair]) {
+        for &(a, b) in pairs {
+            if a == b
+                || !self.has_cell(a.0 as isize, a.1 as isize)
+                || !self.has_cell(b.0 as isize, b.1 as isize)
+            {
+                continue;
+            }
+            let (ia, ib) = (self.to_flat(a.0, a.1), self.to_flat(b.0, b.1));
+            if self.portals[ia].is_none() && self.portals[ib].is_none() {
+                self.portals[ia] = Some(ib);
+                self.portals[ib] = Some(ia);
+            }
+        }
+    }
+
+    /// Every portal pair once, ordered by its first cell, row by row.
+    pub fn portal_pairs(&self) -> Vec<PortalPair> {
+        (0..self.total_cells)
+            .filter_map(|i| match self.portals[i] {
+                Some(j) if i < j => Some((self.from_flat(i), self.from_flat(j))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn has_portals(&self) -> bool {
+        self.portals.iter().any(|p| p.is_some())
+    }
+
+    /// Mark the given cells as goals; cells outside the board or removed are
+    /// ignored.
+    pub fn set_goals(&mut self, cells: &[(usize, usize)]) {
+        for &(r, c) in cells {
+            if self.has_cell(r as isize, c as isize) {
+                let idx = self.to_flat(r, c);
+                self.goals[idx] = true;
+            }
+        }
+    }
+
+    /// Positions of all goal cells, row by row.
+    pub fn goal_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.total_cells)
+            .filter(|&i| self.goals[i])
+            .map(|i| self.from_flat(i))
+            .collect()
+    }
+
+    pub fn is_goal(&self, r: usize, c: usize) -> bool {
+        r < self.rows && c < self.row_widths[r] && self.goals[self.to_flat(r, c)]
+    }
+
+    /// Mark the given cells as holding heavy crosses; cells outside the board
+    /// or removed are ignored.
+    pub fn set_heavy(&mut self, cells: &[(usize, usize)]) {
+        for &(r, c) in cells {
+            if self.has_cell(r as isize, c as isize) {
+                let idx = self.to_flat(r, c);
+                self.heavy[idx] = true;
+            }
+        }
+    }
+
+    /// Positions of all heavy crosses, row by row.
+    pub fn heavy_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.total_cells)
+            .filter(|&i| self.heavy[i])
+            .map(|i| self.from_flat(i))
+            .collect()
+    }
+
+    /// Whether (r, c) holds a heavy cross; false for anything off the board.
+    pub fn is_heavy(&self, r: usize, c: usize) -> bool {
+        r < self.rows && c < self.row_widths[r] && self.heavy[self.to_flat(r, c)]
+    }
+
+    /// Mark the given cells as power-up tiles; cells outside the board or
+    /// removed are ignored.
+    pub fn set_powerups(&mut self, cells: &[(usize, usize)]) {
+        for &(r, c) in cells {
+            if self.has_cell(r as isize, c as isize) {
+                let idx = self.to_flat(r, c);
+                self.powerups[idx] = true;
+            }
+        }
+    }
+
+    /// Positions of all power-up tiles, row by row.
+    pub fn powerup_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.total_cells)
+            .filter(|&i| self.powerups[i])
+            .map(|i| self.from_flat(i))
+            .collect()
+    }
+
+    /// Mark the given cells as bombs; cells outside the board or removed are
+    /// ignored.
+    pub fn set_bombs(&mut self, cells: &[(usize, usize)]) {
+        for &(r, c) in cells {
+            if self.has_cell(r as isize, c as isize) {
+                let idx = self.to_flat(r, c);
+                self.bombs[idx] = true;
+            }
+        }
+    }
+
+    /// Positions of all bombs, row by row.
+    pub fn bomb_cells(&self) -> Vec<(usize, usize)> {
+        (0..self.total_cells)
+            .filter(|&i| self.bombs[i])
+            .map(|i| self.from_flat(i))
+            .collect()
+    }
+
+    pub fn is_bomb(&self, r: usize, c: usize) -> bool {
+        r < self.rows && c < self.row_widths[r] && self.bombs[self.to_flat(r, c)]
+    }
+
+    /// The cells a bomb at (r, c) clears when it goes off: its neighbours
+    /// along both axes, wrapping on a toroidal board.
+    pub fn blast_cells(&self, r: usize, c: usize) -> Vec<(usize, usize)> {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .map(|&(dr, dc)| self.wrap(r as isize + dr, c as isize + dc))
+            .filter(|&(r, c)| self.has_cell(r, c))
+            .map(|(r, c)| (r as usize, c as usize))
+            .collect()
+    }
+
+    /// Whether a bomb sits next to (r, c), so a cross there may yet be blown
+    /// away.
+    pub fn beside_bomb(&self, r: usize, c: usize) -> bool {
+        self.blast_cells(r, c)
+            .iter()
+            .any(|&(r, c)| self.is_bomb(r, c))
+    }
+
+    /// The crosses of a list, in any form, that count for lines: all but the
+    /// neutral blocks at its end.
+    pub fn line_crosses<'a, T>(&self, crosses: &'a [T]) -> &'a [T] {
+        &crosses[..crosses.len().saturating_sub(self.blocks)]
+    }
+
+    /// Whether the cross at `idx` of a list of `len` crosses is a neutral block.
+    pub fn is_block(&self, idx: usize, len: usize) -> bool {
+        idx + self.blocks >= len
+    }
+
+    /// The cell a piece pushed onto the portal at (r, c) comes out of.
+    pub fn portal_exit(&self, r: usize, c: usize) -> Option<(usize, usize)> {
+        if r >= self.rows || c >= self.row_widths[r] {
+            return None;
+        }
+        self.portals[self.to_flat(r, c)].map(|j| self.from_flat(j))
     }
 
     pub fn to_flat(&self, r: usize, c: usize) -> usize {
         self.row_offsets[r] + c
     }
 
+    #[allow(clippy::wrong_self_convention)]
     pub fn from_flat(&self, idx: usize) -> (usize, usize) {
         let mut r = 0usize;
         while r < self.rows {
@@ -224,8 +760,143 @@ impl Board {
         panic!("invalid flat index {}", idx);
     }
 
+    /// Like `is_cell_present`, but for signed coordinates that may lie outside
+    /// the board (e.g. a neighbour of an edge cell).
+    pub fn has_cell(&self, r: isize, c: isize) -> bool {
+        r >= 0
+            && c >= 0
+            && (r as usize) < self.rows
+            && (c as usize) < self.row_widths[r as usize]
+            && self.is_cell_present(r as usize, c as usize)
+    }
+
+    /// Where (r, c) lands once rows and columns wrap around the edges, which
+    /// they do only on toroidal boards (`rules.wrap`); elsewhere (r, c) itself.
+    /// Neighbours are computed as `wrap(r + dr, c + dc)`.
+    pub fn wrap(&self, r: isize, c: isize) -> (isize, isize) {
+        if !self.rules.wrap {
+            return (r, c);
+        }
+        (
+            r.rem_euclid(self.rows as isize),
+            c.rem_euclid(self.cols as isize),
+        )
+    }
+
+    /// Manhattan distance between two cells, the short way round on toroidal
+    /// boards.
+    pub fn distance(&self, a: (usize, usize), b: (usize, usize)) -> usize {
+        let (dr, dc) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+        if self.rules.wrap {
+            dr.min(self.rows - dr) + dc.min(self.cols - dc)
+        } else {
+            dr + dc
+        }
+    }
+
     pub fn is_cell_present(&self, r: usize, c: usize) -> bool {
         let idx = self.to_flat(r, c);
         self.cells[idx]
     }
 }
+
+/// Board layout as stored on disk and sent over the network: dimensions, the
+/// cells missing from the rectangle, and the special cells. Field names and
+/// defaults are those of puzzle files, which embed it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BoardShape {
+    pub rows: usize,
+    pub cols: usize,
+    #[serde(default)]
+    pub removed: Vec<(usize, usize)>,
+    /// Cells of `removed` that are drawn as solid walls rather than holes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub walls: Vec<(usize, usize)>,
+    /// Ice cells; pieces pushed onto them keep sliding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ice: Vec<(usize, usize)>,
+    /// Linked portal cells; a piece pushed onto one comes out of the other
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub portals: Vec<PortalPair>,
+    /// Goal cells for the circles under the goal-cell rules
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub goals: Vec<(usize, usize)>,
+    /// Crosses that can't be pushed or pulled; each is also in the crosses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub heavy: Vec<(usize, usize)>,
+    /// Power-up tiles; the player picks one up by stepping on it and can
+    /// then remove a cross next to it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub powerups: Vec<(usize, usize)>,
+    /// Bomb cells; a piece pushed onto one clears the crosses next to it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bombs: Vec<(usize, usize)>,
+    /// How many of the puzzle's crosses, the last ones listed, are neutral
+    /// blocks
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub blocks: usize,
+    #[serde(default, skip_serializing_if = "Rules::is_classic")]
+    pub rules: Rules,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl BoardShape {
+    pub fn build(&self) -> Result<Board, Box<dyn Error>> {
+        let mut board = Board::from_dims(self.rows, self.cols, &self.removed)?;
+        board.set_walls(&self.walls);
+        board.set_ice(&self.ice);
+        board.set_portals(&self.portals);
+        board.set_goals(&self.goals);
+        board.set_heavy(&self.heavy);
+        board.set_powerups(&self.powerups);
+        board.set_bombs(&self.bombs);
+        board.blocks = self.blocks;
+        board.rules = self.rules;
+        Ok(board)
+    }
+}
+
+impl From<&Board> for BoardShape {
+    /// Short rows are padded to the full width with removed cells.
+    fn from(board: &Board) -> Self {
+        let mut removed = Vec::new();
+        for r in 0..board.rows {
+            for c in 0..board.cols {
+                if c >= board.row_widths[r] || !board.is_cell_present(r, c) {
+                    removed.push((r, c));
+                }
+            }
+        }
+        BoardShape {
+            rows: board.rows,
+            cols: board.cols,
+            removed,
+            walls: board.wall_cells(),
+            ice: board.ice_cells(),
+            portals: board.portal_pairs(),
+            goals: board.goal_cells(),
+            heavy: board.heavy_cells(),
+            powerups: board.powerup_cells(),
+            bombs: board.bomb_cells(),
+            blocks: board.blocks,
+            rules: board.rules,
+        }
+    }
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardShape::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BoardShape::deserialize(deserializer)?
+            .build()
+            .map_err(serde::de::Error::custom)
+    }
+}
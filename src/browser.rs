@@ -1,18 +1,19 @@
 use std::{
+    collections::HashSet,
     error::Error,
     fs,
     io::Stdout,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::mpsc,
     thread,
     time::{Duration, SystemTime},
 };
 
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{Event, KeyCode};
 use ratatui::{
     Terminal,
     backend::CrosstermBackend,
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
@@ -21,14 +22,43 @@ use ratatui::{
 use crate::{
     board::Board,
     database::{download, list_puzzles, upload},
+    game::{self, GameExit},
+    generator::verify_puzzle,
+    input, journal,
+    pack::{self, Manifest},
+    puzzle::PuzzleFile,
 };
 
+const PUZZLE_DIR: &str = "puzzles";
+
 struct PuzzleItem {
     path: PathBuf,
     file_name: String,
     rows: usize,
     cols: usize,
     created_at: Option<u64>,
+    /// Title and required files from the pack manifest
+    title: Option<String>,
+    requires: Vec<String>,
+    /// Canonical hash, for progress; `None` for files that don't parse
+    hash: Option<u64>,
+}
+
+/// Required files of `item` not solved yet; unknown files count as unsolved.
+fn missing<'a>(
+    item: &'a PuzzleItem,
+    puzzles: &[PuzzleItem],
+    solved: &HashSet<u64>,
+) -> Vec<&'a str> {
+    item.requires
+        .iter()
+        .filter(|file| {
+            !puzzles
+                .iter()
+                .any(|p| &p.file_name == *file && p.hash.is_some_and(|h| solved.contains(&h)))
+        })
+        .map(String::as_str)
+        .collect()
 }
 
 fn parse_number(s: &str, key: &str) -> Option<u64> {
@@ -136,43 +166,6 @@ fn parse_pair_single(s: &str, key: &str) -> Option<(usize, usize)> {
     None
 }
 
-fn board_from_dims(
-    rows: usize,
-    cols: usize,
-    removed: &[(usize, usize)],
-) -> Result<Board, Box<dyn std::error::Error>> {
-    if rows == 0 || cols == 0 {
-        return Err("Invalid rows or cols".into());
-    }
-    let row_widths = vec![cols; rows];
-    let mut row_offsets = vec![0usize; rows];
-    for i in 1..rows {
-        row_offsets[i] = row_offsets[i - 1] + row_widths[i - 1];
-    }
-    let total_cells = row_offsets[rows - 1] + row_widths[rows - 1];
-    let default_grid_w: u16 = (4 * cols + 1) as u16;
-    let default_grid_h: u16 = (2 * rows + 1) as u16;
-    let mut cells = vec![true; total_cells];
-    for &(r, c) in removed.iter() {
-        if r < rows && c < cols {
-            let idx = row_offsets[r] + c;
-            if idx < total_cells {
-                cells[idx] = false;
-            }
-        }
-    }
-    Ok(Board {
-        rows,
-        cols,
-        row_widths,
-        row_offsets,
-        total_cells,
-        cells,
-        default_grid_w,
-        default_grid_h,
-    })
-}
-
 fn load_puzzle_board(
     path: &PathBuf,
 ) -> Result<
@@ -194,13 +187,71 @@ fn load_puzzle_board(
     let crosses = parse_pairs(&contents, "\"crosses\":");
     let removed = parse_pairs(&contents, "\"removed\":");
     let player = parse_pair_single(&contents, "\"player\":");
-    let board = board_from_dims(rows, cols, &removed)?;
+    let mut board = Board::from_dims(rows, cols, &removed)?;
+    board.set_walls(&parse_pairs(&contents, "\"walls\":"));
+    board.set_ice(&parse_pairs(&contents, "\"ice\":"));
+    board.set_heavy(&parse_pairs(&contents, "\"heavy\":"));
+    board.set_powerups(&parse_pairs(&contents, "\"powerups\":"));
+    board.set_bombs(&parse_pairs(&contents, "\"bombs\":"));
+    board.blocks = parse_number(&contents, "\"blocks\":").unwrap_or(0) as usize;
+    let portal_ends = parse_pairs(&contents, "\"portals\":");
+    let portals: Vec<_> = portal_ends.chunks_exact(2).map(|p| (p[0], p[1])).collect();
+    board.set_portals(&portals);
+    // rule variants are optional; older files simply use the classic rules
+    board.rules = PuzzleFile::parse(&contents)
+        .map(|p| p.shape.rules)
+        .unwrap_or_default();
+    board.validate()?;
     Ok((board, circles, crosses, removed, player, created_at))
 }
 
+/// Side panel for a puzzle file: a miniature of the board, one character
+/// per cell, over its size, pieces, rules and creation date.
+fn preview_lines(path: &Path) -> Vec<Spans<'static>> {
+    let loaded = PuzzleFile::load(path).and_then(|p| Ok((p.board()?, p)));
+    let (board, puzzle) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            return vec![Spans::from(Span::styled(
+                format!("Unreadable: {}", e),
+                Style::default().fg(Color::Red),
+            ))];
+        }
+    };
+    let mut lines = game::minimap_lines(
+        &board,
+        &puzzle.circles,
+        &puzzle.crosses,
+        puzzle.player_idx(),
+    );
+    lines.push(Spans::from(Span::raw("")));
+    lines.push(Spans::from(Span::raw(format!(
+        "{}x{} board",
+        board.rows, board.cols
+    ))));
+    lines.push(Spans::from(Span::raw(format!(
+        "{} circles, {} crosses",
+        puzzle.circles.len(),
+        puzzle.crosses.len()
+    ))));
+    let rules = board.rules.names();
+    lines.push(Spans::from(Span::raw(if rules.is_empty() {
+        "Classic rules".to_string()
+    } else {
+        format!("Rules: {}", rules.join(", "))
+    })));
+    if let Some(ts) = puzzle.created_at {
+        lines.push(Spans::from(Span::raw(format!(
+            "Created {}",
+            journal::date(ts)
+        ))));
+    }
+    lines
+}
+
 fn read_puzzles() -> Vec<PuzzleItem> {
     let mut puzzles = Vec::new();
-    if let Ok(entries) = fs::read_dir("puzzles") {
+    if let Ok(entries) = fs::read_dir(PUZZLE_DIR) {
         for entry in entries.flatten() {
             let path = entry.path();
             if !path.is_file() {
@@ -227,17 +278,42 @@ fn read_puzzles() -> Vec<PuzzleItem> {
                         created_at = Some(ts);
                     }
                 }
+                let hash = PuzzleFile::load(&path).ok().map(|p| p.canonical_hash());
                 puzzles.push(PuzzleItem {
                     path,
                     file_name,
                     rows,
                     cols,
                     created_at,
+                    title: None,
+                    requires: Vec::new(),
+                    hash,
                 });
             }
         }
     }
     puzzles.sort_by_key(|p| p.file_name.clone());
+    // a pack manifest puts its puzzles first, in its order, under its titles
+    let manifest = pack::load(Path::new(PUZZLE_DIR)).unwrap_or_else(|e| {
+        log::warn!("{}/{}: {}", PUZZLE_DIR, pack::MANIFEST_FILE, e);
+        None
+    });
+    if let Some(manifest) = manifest {
+        for p in &mut puzzles {
+            if let Some(entry) = manifest.entry(&p.file_name) {
+                p.title = entry.title.clone();
+                p.requires = entry.requires.clone();
+            }
+        }
+        let rank = |p: &PuzzleItem| {
+            manifest
+                .puzzles
+                .iter()
+                .position(|e| e.file == p.file_name)
+                .unwrap_or(manifest.puzzles.len())
+        };
+        puzzles.sort_by_key(rank);
+    }
     puzzles
 }
 
@@ -248,6 +324,14 @@ pub fn show_browser(
     let mut selected: usize = 0;
     let mut status_msg: Option<String> = None;
     let mut error_popup: Option<String> = None;
+    let mut solved = pack::solved();
+    let manifest: Option<Manifest> = match pack::load(Path::new(PUZZLE_DIR)) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            error_popup = Some(format!("{}: {}", pack::MANIFEST_FILE, e));
+            None
+        }
+    };
 
     // Background channels for remote list and downloads
     let (list_tx, list_rx) = mpsc::channel::<Result<Vec<(String, Option<u64>)>, String>>();
@@ -266,6 +350,9 @@ pub fn show_browser(
     let mut rename_target_idx: Option<usize> = None;
     let mut rename_confirm_prompt: Option<(PathBuf, String)> = None;
 
+    // Preview of the selected local puzzle, and the file it shows
+    let mut preview: Option<(PathBuf, Vec<Spans<'static>>)> = None;
+
     loop {
         // Poll background results without blocking the UI
         if let Ok(res) = list_rx.try_recv() {
@@ -294,6 +381,15 @@ pub fn show_browser(
             }
         }
 
+        let wanted = if remote_mode {
+            None
+        } else {
+            puzzles.get(selected).map(|p| &p.path)
+        };
+        if wanted != preview.as_ref().map(|(path, _)| path) {
+            preview = wanted.map(|path| (path.clone(), preview_lines(path)));
+        }
+
         terminal.draw(|f| {
             let size = f.size();
 
@@ -332,6 +428,9 @@ pub fn show_browser(
                 header,
                 Style::default().add_modifier(Modifier::BOLD),
             )));
+            if !remote_mode && let Some(title) = manifest.as_ref().and_then(|m| m.title.as_ref()) {
+                lines.push(Spans::from(Span::raw(format!(" Pack: {}", title))));
+            }
             lines.push(Spans::from(Span::raw("")));
 
             if let Some(ref msg) = status_msg {
@@ -351,12 +450,20 @@ pub fn show_browser(
                     )));
                 } else {
                     for (i, p) in puzzles.iter().enumerate() {
-                        let label = match p.created_at {
-                            Some(ts) => {
-                                format!("{}  —  {}x{}  —  {}", p.file_name, p.rows, p.cols, ts)
-                            }
-                            None => format!("{}  —  {}x{}", p.file_name, p.rows, p.cols),
+                        let name = match &p.title {
+                            Some(title) => format!("{} ({})", title, p.file_name),
+                            None => p.file_name.clone(),
                         };
+                        let mut label = match p.created_at {
+                            Some(ts) => format!("{}  —  {}x{}  —  {}", name, p.rows, p.cols, ts),
+                            None => format!("{}  —  {}x{}", name, p.rows, p.cols),
+                        };
+                        let missing = missing(p, &puzzles, &solved);
+                        if !missing.is_empty() {
+                            label.push_str(&format!("  —  locked: solve {}", missing.join(", ")));
+                        } else if p.hash.is_some_and(|h| solved.contains(&h)) {
+                            label.push_str("  —  solved");
+                        }
                         if i == selected {
                             lines.push(Spans::from(Span::styled(
                                 label,
@@ -400,8 +507,30 @@ pub fn show_browser(
                 area.width.saturating_sub(2),
                 area.height.saturating_sub(2),
             );
+            // the preview goes on the right when there is room for it and
+            // a list of reasonable width
+            let mut list_area = inner;
+            if let Some((_, preview_lines)) = &preview {
+                let width = preview_lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 4;
+                if inner.width >= width + 40 {
+                    let columns = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Min(0), Constraint::Length(width)])
+                        .split(inner);
+                    list_area = columns[0];
+                    let height = (preview_lines.len() as u16 + 2).min(columns[1].height);
+                    let panel_area = Rect {
+                        height,
+                        ..columns[1]
+                    };
+                    let panel = Paragraph::new(preview_lines.clone())
+                        .block(Block::default().borders(Borders::ALL).title("Preview"));
+                    f.render_widget(Clear, panel_area);
+                    f.render_widget(panel, panel_area);
+                }
+            }
             let para = Paragraph::new(lines).alignment(Alignment::Left);
-            f.render_widget(para, inner);
+            f.render_widget(para, list_area);
 
             // show rename modal if active
             if rename_mode {
@@ -495,9 +624,10 @@ pub fn show_browser(
             }
         })?;
 
-        if event::poll(Duration::from_millis(150))?
-            && let Event::Key(key) = event::read()?
-        {
+        for event in input::drain(Duration::from_millis(150))? {
+            let Event::Key(key) = event else {
+                continue;
+            };
             // Prioritize rename confirm -> rename input -> error popup -> normal handlers
             if rename_confirm_prompt.is_some() {
                 match key.code {
@@ -817,6 +947,13 @@ pub fn show_browser(
                         }
                     }
                     KeyCode::Enter if !remote_mode => {
+                        if let Some(p) = puzzles.get(selected) {
+                            let missing = missing(p, &puzzles, &solved);
+                            if !missing.is_empty() {
+                                status_msg = Some(format!("Solve {} first", missing.join(", ")));
+                                continue;
+                            }
+                        }
                         if !puzzles.is_empty() {
                             if let Some(p) = puzzles.get(selected) {
                                 match load_puzzle_board(&p.path) {
@@ -837,10 +974,31 @@ pub fn show_browser(
                                         } else {
                                             if !circles.is_empty() { 0usize } else { 0usize }
                                         };
-                                        if let Err(e) = crate::game::run_puzzle(
-                                            terminal, board, circles, crosses, player_idx,
+                                        let report =
+                                            verify_puzzle(&board, &circles, &crosses, player_idx);
+                                        if let Some(problem) = report.problem() {
+                                            error_popup = Some(format!(
+                                                "{} can't be played: {}",
+                                                p.file_name, problem
+                                            ));
+                                            continue;
+                                        }
+                                        match crate::game::run_puzzle(
+                                            terminal, board, circles, crosses, player_idx, None,
                                         ) {
-                                            eprintln!("Failed to run puzzle: {}", e);
+                                            Ok((exit, won)) => {
+                                                if let (true, Some(hash)) = (won, p.hash) {
+                                                    pack::mark_solved(hash);
+                                                    solved.insert(hash);
+                                                    status_msg =
+                                                        Some(format!("Solved {}", p.file_name));
+                                                }
+                                                // "menu" on the end-of-game overlay: back to the list
+                                                if let GameExit::Menu = exit {
+                                                    continue;
+                                                }
+                                            }
+                                            Err(e) => eprintln!("Failed to run puzzle: {}", e),
                                         }
                                     }
                                     Err(e) => {
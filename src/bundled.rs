@@ -0,0 +1,217 @@
+// Pre-generated puzzles shipped inside the binary, so normal play never waits
+// for the generator.
+//
+// `data/puzzles.jsonl.gz` holds one JSON line per puzzle: its difficulty, the
+// seed it was generated from, the rating of its solution (see `rating`), and
+// the puzzle itself. Every line came out of the generator with the built-in
+// settings and the standard rules, was solved by the solver, and is unique up
+// to mirroring and rotation. Easy, Medium and Hard games with exactly those
+// settings are served from it; anything else is generated as before.
+//
+// `tic-tac-go bundle` rebuilds the file, which is needed after changes to the
+// generator for the seeds to keep matching their puzzles.
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::generator::{Difficulty, GeneratedPuzzle, Setup, generate_from_seed};
+use crate::puzzle::PuzzleFile;
+use crate::rating::Rating;
+use crate::rules::Rules;
+
+const DATABASE: &[u8] = include_bytes!("../data/puzzles.jsonl.gz");
+const DEFAULT_OUT: &str = "data/puzzles.jsonl.gz";
+const DEFAULT_COUNT: usize = 300;
+// Seeds tried per wanted puzzle before a difficulty is given up on
+const ATTEMPTS_PER_PUZZLE: usize = 5;
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+static RECORDS: OnceLock<Vec<Record>> = OnceLock::new();
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Record {
+    difficulty: Difficulty,
+    seed: u64,
+    rating: Option<Rating>,
+    puzzle: PuzzleFile,
+}
+
+fn decode() -> Result<Vec<Record>, Box<dyn Error>> {
+    let mut text = String::new();
+    GzDecoder::new(DATABASE).read_to_string(&mut text)?;
+    let mut records = Vec::new();
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+        records.push(serde_json::from_str(line)?);
+    }
+    Ok(records)
+}
+
+fn records() -> &'static [Record] {
+    RECORDS.get_or_init(|| {
+        decode().unwrap_or_else(|e| {
+            log::warn!("bundled puzzles unusable: {}", e);
+            Vec::new()
+        })
+    })
+}
+
+/// Whether `difficulty` with `rules` plays exactly as the bundled puzzles
+/// were generated.
+fn serves(difficulty: Difficulty, rules: Rules) -> bool {
+    let config = config::get();
+    !config.live_generation
+        && rules == Rules::default()
+        && config.board == Default::default()
+        && difficulty.params() == difficulty.default_params()
+}
+
+/// A bundled puzzle for `setup`, picked by `seed`, or `None` when the setup
+/// or rules differ from those the bundle was made with. The puzzle carries
+/// the seed it was generated from, so the one after it (`seed + 1`) is the
+/// next of the bundle.
+pub fn lookup(seed: u64, setup: Setup, rules: Rules) -> Option<GeneratedPuzzle> {
+    let Setup::Tier(difficulty) = setup else {
+        return None;
+    };
+    if !serves(difficulty, rules) {
+        return None;
+    }
+    let matching: Vec<&Record> = records()
+        .iter()
+        .filter(|r| r.difficulty == difficulty)
+        .collect();
+    if matching.is_empty() {
+        return None;
+    }
+    let record = matching[(seed % matching.len() as u64) as usize];
+    let mut board = record.puzzle.board().ok()?;
+    board.rules = rules;
+    let flat = |pieces: &[(usize, usize)]| -> Vec<usize> {
+        pieces.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+    };
+    let (circles, crosses) = (flat(&record.puzzle.circles), flat(&record.puzzle.crosses));
+    log::info!("seed {}: bundled puzzle", record.seed);
+    Some(GeneratedPuzzle {
+        seed: record.seed,
+        board,
+        circles,
+        crosses,
+        player_idx: record.puzzle.player_idx(),
+        par: record.rating.map(|r| r.moves),
+        relaxed: None,
+        board_seed: None,
+        rating: record.rating,
+    })
+}
+
+fn print_usage() {
+    eprintln!("usage: tic-tac-go bundle [--count N] [--seed S] [--out FILE]");
+    eprintln!();
+    eprintln!(
+        "Generates N puzzles per difficulty (default {}) from consecutive seeds",
+        DEFAULT_COUNT
+    );
+    eprintln!("starting at S, with the built-in settings and standard rules, and");
+    eprintln!(
+        "writes them compressed to FILE (default {}), the database built",
+        DEFAULT_OUT
+    );
+    eprintln!("into the game. The config file is ignored.");
+}
+
+/// `tic-tac-go bundle`
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut count = DEFAULT_COUNT;
+    let mut seed = 0u64;
+    let mut out = PathBuf::from(DEFAULT_OUT);
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--count" | "-n" => {
+                count = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--count expects a number")?;
+            }
+            "--seed" => {
+                seed = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--seed expects a number")?;
+            }
+            "--out" | "-o" => out = PathBuf::from(it.next().ok_or("--out expects a file")?),
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(());
+            }
+            other => {
+                print_usage();
+                return Err(format!("unknown option {}", other).into());
+            }
+        }
+    }
+    config::init(config::Config::default());
+
+    let mut seen: HashSet<u64> = HashSet::new();
+    let mut records = Vec::new();
+    for difficulty in DIFFICULTIES {
+        let mut sizes: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        let mut found = 0;
+        for s in seed..seed + (count * ATTEMPTS_PER_PUZZLE) as u64 {
+            if found == count {
+                break;
+            }
+            let puzzle = generate_from_seed(s, difficulty, Rules::default());
+            if puzzle.circles.is_empty() {
+                continue;
+            }
+            let board = &puzzle.board;
+            let rc = |pieces: &[usize]| -> Vec<(usize, usize)> {
+                pieces.iter().map(|&f| board.from_flat(f)).collect()
+            };
+            let file = PuzzleFile::from_parts(
+                board,
+                &rc(&puzzle.circles),
+                &rc(&puzzle.crosses),
+                puzzle.player_idx,
+                None,
+            );
+            if !seen.insert(file.canonical_hash()) {
+                continue;
+            }
+            *sizes.entry((board.rows, board.cols)).or_default() += 1;
+            records.push(Record {
+                difficulty,
+                seed: s,
+                rating: puzzle.rating,
+                puzzle: file,
+            });
+            found += 1;
+        }
+        let sizes: Vec<String> = sizes
+            .iter()
+            .map(|((rows, cols), n)| format!("{}x{}: {}", rows, cols, n))
+            .collect();
+        println!("{:?}: {} puzzles ({})", difficulty, found, sizes.join(", "));
+    }
+
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    let mut gz = GzEncoder::new(BufWriter::new(File::create(&out)?), Compression::best());
+    for record in &records {
+        writeln!(gz, "{}", serde_json::to_string(record)?)?;
+    }
+    gz.finish()?.flush()?;
+    println!("wrote {}", out.display());
+    Ok(())
+}
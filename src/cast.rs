@@ -0,0 +1,90 @@
+// Finished games from the journal as asciinema v2 casts, for embedding a
+// solution on a web page with the asciinema player or sharing the file.
+//
+// A cast is a JSON header line followed by one `[time, "o", text]` line per
+// chunk of terminal output. Each frame here clears the screen and draws the
+// board in the ASCII format of `sim`, coloured, with a line saying how far
+// the game is: one frame for the start and one per move.
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::journal::{self, Entry};
+use crate::sim;
+use crate::state::GameState;
+
+// Seconds each move stays on screen
+const FRAME_SECONDS: f64 = 0.8;
+// Rows besides the board: title, blank lines and the progress line
+const EXTRA_ROWS: usize = 4;
+
+fn colour(ch: char) -> String {
+    let code = match ch {
+        '@' => "1;33",
+        'o' => "32",
+        'x' => "31",
+        '#' => "90",
+        '~' => "34",
+        'A'..='H' => "35",
+        _ => return ch.to_string(),
+    };
+    format!("\x1b[{}m{}\x1b[0m", code, ch)
+}
+
+/// The board with a space between cells, so it comes out roughly square.
+fn board_lines(state: &GameState) -> Vec<String> {
+    sim::to_ascii(state)
+        .lines()
+        .map(|line| line.chars().map(colour).collect::<Vec<_>>().join(" "))
+        .collect()
+}
+
+/// The cast of `entry`'s game.
+pub fn render(entry: &Entry) -> Result<String, Box<dyn Error>> {
+    let board = entry.puzzle.board()?;
+    let title = format!("tic-tac-go  {}  seed {}", entry.label(), entry.seed);
+    let last = entry.positions.len().saturating_sub(1);
+    let width = title.len().max(board.cols * 2).max(24);
+    let height = board.rows + EXTRA_ROWS;
+    let header = serde_json::json!({
+        "version": 2,
+        "width": width,
+        "height": height,
+        "timestamp": entry.finished_at,
+        "title": title,
+    });
+    let mut out = format!("{}\n", header);
+    for (i, (circles, crosses)) in entry.positions.iter().enumerate() {
+        let state = GameState {
+            board: board.clone(),
+            circles: circles.clone(),
+            crosses: crosses.clone(),
+            player_idx: entry.puzzle.player_idx(),
+            moves: i,
+            powerups: Default::default(),
+        };
+        let progress = match (i == last, entry.won) {
+            (true, true) => format!("won in {} moves", i),
+            (true, false) => format!("lost after {} moves", i),
+            (false, _) => format!("move {} of {}", i, last),
+        };
+        let mut frame = format!("\x1b[2J\x1b[H{}\r\n\r\n", title);
+        frame.push_str(&board_lines(&state).join("\r\n"));
+        frame.push_str(&format!("\r\n\r\n{}", progress));
+        let event = (i as f64 * FRAME_SECONDS, "o", frame);
+        out.push_str(&serde_json::to_string(&event)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Write the cast of `entry` to the working directory and return its path.
+pub fn export(entry: &Entry) -> Result<PathBuf, Box<dyn Error>> {
+    let path = PathBuf::from(format!(
+        "tic-tac-go-{}-{}.cast",
+        journal::date(entry.finished_at),
+        entry.seed
+    ));
+    fs::write(&path, render(entry)?)?;
+    Ok(path)
+}
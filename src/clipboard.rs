@@ -0,0 +1,60 @@
+// Copying text for the player to paste elsewhere, e.g. a puzzle's seed.
+//
+// The system clipboard is reached through `arboard`. Over SSH that would be
+// the remote machine's clipboard, so there (and whenever no clipboard is
+// reachable, such as without a display server) the text goes to the terminal
+// instead as an OSC 52 escape sequence, which most terminal emulators turn
+// into a clipboard write on the player's side.
+use std::error::Error;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use arboard::Clipboard;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+// Kept open for the rest of the run: on X11 the copied text is served by the
+// clipboard's owner and disappears with it
+static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+/// Where copied text ended up.
+pub enum Copied {
+    Clipboard,
+    /// Handed to the terminal with OSC 52; whether it arrived is up to the
+    /// terminal
+    Terminal,
+}
+
+fn over_ssh() -> bool {
+    std::env::var_os("SSH_CONNECTION").is_some() || std::env::var_os("SSH_TTY").is_some()
+}
+
+fn system_copy(text: &str) -> Result<(), Box<dyn Error>> {
+    let mut clipboard = CLIPBOARD.lock().map_err(|_| "clipboard lock poisoned")?;
+    if clipboard.is_none() {
+        *clipboard = Some(Clipboard::new()?);
+    }
+    if let Some(c) = clipboard.as_mut() {
+        c.set_text(text)?;
+    }
+    Ok(())
+}
+
+fn osc52_copy(text: &str) -> Result<(), Box<dyn Error>> {
+    let mut out = io::stdout();
+    write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Put `text` on the clipboard.
+pub fn copy(text: &str) -> Result<Copied, Box<dyn Error>> {
+    if !over_ssh() {
+        match system_copy(text) {
+            Ok(()) => return Ok(Copied::Clipboard),
+            Err(e) => log::info!("no system clipboard ({}); using OSC 52", e),
+        }
+    }
+    osc52_copy(text)?;
+    Ok(Copied::Terminal)
+}
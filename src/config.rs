@@ -0,0 +1,338 @@
+// Optional user configuration, read once at startup.
+//
+// The file is JSON and every field is optional, e.g. to make Hard puzzles use
+// more crosses and a stricter score, to play with diagonal lines and pulls,
+// and to hear sound cues:
+//
+//   { "difficulty": { "hard": { "crosses": [7, 12], "min_score": 26 } },
+//     "rules": { "diagonals": true, "pull": true }, "sound": true }
+//
+// It is looked up at `--config PATH`, then $TIC_TAC_GO_CONFIG, then
+// `tic-tac-go.json` in the working directory. `tic-tac-go config` prints the
+// effective values. The Settings screen changes some of them while the game
+// runs and writes them back to the same file (see `set`).
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::board::Carving;
+use crate::game::{GridStyle, PlayerStyle};
+use crate::generator::{Difficulty, DifficultyParams, Undos};
+use crate::input::KeyRepeat;
+use crate::leaderboard::OnlineScores;
+use crate::rules::{MAX_CIRCLES, MIN_CIRCLES, Rules};
+
+const DEFAULT_FILE: &str = "tic-tac-go.json";
+const PATH_VAR: &str = "TIC_TAC_GO_CONFIG";
+
+// Each change from the Settings screen leaks the config it replaces, so
+// references from `get` stay valid; a few per session at most
+static CONFIG: OnceLock<RwLock<&'static Config>> = OnceLock::new();
+// The file `load` read, or would have read; `set` writes there
+static FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Per-field overrides of a difficulty's built-in parameters.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DifficultyOverrides {
+    pub crosses: Option<(usize, usize)>,
+    pub circles: Option<usize>,
+    pub scramble_budget: Option<usize>,
+    pub attempts: Option<usize>,
+    pub min_score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub min_moves: Option<usize>,
+    pub max_moves: Option<usize>,
+    pub hardest_of: Option<usize>,
+    pub ice_percent: Option<usize>,
+    pub heavy_crosses: Option<usize>,
+    pub powerups: Option<usize>,
+    pub undos: Option<Undos>,
+}
+
+impl DifficultyOverrides {
+    /// Take every field that is set in `other`.
+    pub fn merge(&mut self, other: &DifficultyOverrides) {
+        self.crosses = other.crosses.or(self.crosses);
+        self.circles = other.circles.or(self.circles);
+        self.scramble_budget = other.scramble_budget.or(self.scramble_budget);
+        self.attempts = other.attempts.or(self.attempts);
+        self.min_score = other.min_score.or(self.min_score);
+        self.max_score = other.max_score.or(self.max_score);
+        self.min_moves = other.min_moves.or(self.min_moves);
+        self.max_moves = other.max_moves.or(self.max_moves);
+        self.hardest_of = other.hardest_of.or(self.hardest_of);
+        self.ice_percent = other.ice_percent.or(self.ice_percent);
+        self.heavy_crosses = other.heavy_crosses.or(self.heavy_crosses);
+        self.powerups = other.powerups.or(self.powerups);
+        self.undos = other.undos.or(self.undos);
+    }
+
+    fn apply(&self, params: &mut DifficultyParams) {
+        if let Some(v) = self.crosses {
+            params.crosses = v;
+        }
+        if let Some(v) = self.circles {
+            params.circles = v.clamp(MIN_CIRCLES, MAX_CIRCLES);
+        }
+        if let Some(v) = self.scramble_budget {
+            params.scramble_budget = v;
+        }
+        if let Some(v) = self.attempts {
+            params.attempts = v;
+        }
+        if let Some(v) = self.min_score {
+            params.min_score = v;
+        }
+        if let Some(v) = self.max_score {
+            params.max_score = Some(v);
+        }
+        if let Some(v) = self.min_moves {
+            params.min_moves = v;
+        }
+        if let Some(v) = self.max_moves {
+            params.max_moves = Some(v);
+        }
+        if let Some(v) = self.hardest_of {
+            params.hardest_of = v.max(1);
+        }
+        if let Some(v) = self.ice_percent {
+            params.ice_percent = v.min(100);
+        }
+        if let Some(v) = self.heavy_crosses {
+            params.heavy_crosses = v;
+        }
+        if let Some(v) = self.powerups {
+            params.powerups = v;
+        }
+        if let Some(v) = self.undos {
+            params.undos = v;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DifficultyConfig {
+    pub easy: DifficultyOverrides,
+    pub medium: DifficultyOverrides,
+    pub hard: DifficultyOverrides,
+    pub expert: DifficultyOverrides,
+    pub insane: DifficultyOverrides,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub difficulty: DifficultyConfig,
+    /// Rule variants new games start with
+    pub rules: Rules,
+    /// Holes of the randomly shaped boards of Easy, Medium and Hard
+    pub board: Carving,
+    /// Ring the terminal bell on pushes, blocked moves, wins and losses
+    pub sound: bool,
+    /// Skip the victory and defeat animations
+    pub reduce_motion: bool,
+    /// Describe every move in a line of text on the top row
+    pub screen_reader: bool,
+    /// How fast a held arrow key moves, on terminals that report releases
+    pub key_repeat: KeyRepeat,
+    /// Ask for a second press before a move that lines up three crosses
+    pub warn_losing_moves: bool,
+    /// Refuse such moves on Easy altogether
+    pub assist: bool,
+    /// Show the optimal number of moves left, worked out after every move
+    pub optimal_distance: bool,
+    /// Say so once the puzzle can no longer be won, offering a restart
+    pub soft_lock_warning: bool,
+    /// How the player's circle is told from the others
+    pub player_style: PlayerStyle,
+    /// Grid lines only between cells, or a box around each
+    pub grid_style: GridStyle,
+    /// Let the Custom form make boards of up to 30x30 cells; they scroll
+    /// with the player when they don't fit the terminal
+    pub large_boards: bool,
+    /// Share daily puzzle results with a score server (off unless enabled)
+    pub online: OnlineScores,
+    /// Where the weekly challenge puzzle file is downloaded from; the main
+    /// menu only offers the challenge when this is set
+    pub weekly_url: String,
+    /// Generate every puzzle on the spot instead of serving Easy, Medium and
+    /// Hard from the puzzles built into the game
+    pub live_generation: bool,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    pub fn overrides_mut(&mut self, difficulty: Difficulty) -> &mut DifficultyOverrides {
+        match difficulty {
+            Difficulty::Easy => &mut self.difficulty.easy,
+            Difficulty::Medium => &mut self.difficulty.medium,
+            Difficulty::Hard => &mut self.difficulty.hard,
+            Difficulty::Expert => &mut self.difficulty.expert,
+            Difficulty::Insane => &mut self.difficulty.insane,
+        }
+    }
+
+    pub fn overrides(&self, difficulty: Difficulty) -> &DifficultyOverrides {
+        match difficulty {
+            Difficulty::Easy => &self.difficulty.easy,
+            Difficulty::Medium => &self.difficulty.medium,
+            Difficulty::Hard => &self.difficulty.hard,
+            Difficulty::Expert => &self.difficulty.expert,
+            Difficulty::Insane => &self.difficulty.insane,
+        }
+    }
+
+    pub fn difficulty_params(&self, difficulty: Difficulty) -> DifficultyParams {
+        let mut params = difficulty.default_params();
+        self.overrides(difficulty).apply(&mut params);
+        params
+    }
+}
+
+/// Load the config from `explicit`, $TIC_TAC_GO_CONFIG or the default file.
+/// A missing default file is not an error; a missing explicit one is.
+pub fn load(explicit: Option<&Path>) -> Result<Config, Box<dyn Error>> {
+    let path = explicit
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::var_os(PATH_VAR).map(PathBuf::from));
+    let _ = FILE.set(path.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_FILE)));
+    match path {
+        Some(p) => Config::load(&p),
+        None if Path::new(DEFAULT_FILE).exists() => Config::load(Path::new(DEFAULT_FILE)),
+        None => Ok(Config::default()),
+    }
+}
+
+/// Install the config used by the rest of the program. Only the first call
+/// has any effect.
+pub fn init(config: Config) {
+    let _ = CONFIG.set(RwLock::new(Box::leak(Box::new(config))));
+}
+
+fn current() -> &'static RwLock<&'static Config> {
+    CONFIG.get_or_init(|| RwLock::new(Box::leak(Box::default())))
+}
+
+pub fn get() -> &'static Config {
+    *current().read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Copy what differs between `old` and `new` into `file`, field by field, so
+/// the file's other fields stay as they were. A field back at its default
+/// (`null`) is left out.
+fn merge(file: &mut Value, old: &Value, new: &Value) {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        *file = new.clone();
+        return;
+    };
+    if !file.is_object() {
+        *file = Value::Object(Map::new());
+    }
+    let Value::Object(fields) = file else {
+        return;
+    };
+    for (key, value) in new {
+        let before = old.get(key).unwrap_or(&Value::Null);
+        if before == value {
+            continue;
+        }
+        if value.is_null() {
+            fields.remove(key);
+        } else {
+            merge(
+                fields.entry(key.clone()).or_insert(Value::Null),
+                before,
+                value,
+            );
+        }
+    }
+}
+
+/// Change the config for the rest of the session with `edit`, and write the
+/// change to the config file.
+pub fn set(edit: impl FnOnce(&mut Config)) -> Result<(), Box<dyn Error>> {
+    let old = get();
+    let mut new = old.clone();
+    edit(&mut new);
+    let path = FILE.get_or_init(|| PathBuf::from(DEFAULT_FILE));
+    let mut file = match fs::read_to_string(path) {
+        Ok(text) => {
+            serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Value::Object(Map::new()),
+        Err(e) => return Err(e.into()),
+    };
+    merge(
+        &mut file,
+        &serde_json::to_value(old)?,
+        &serde_json::to_value(&new)?,
+    );
+    fs::write(path, serde_json::to_string_pretty(&file)? + "\n")?;
+    *current().write().unwrap_or_else(|e| e.into_inner()) = Box::leak(Box::new(new));
+    Ok(())
+}
+
+/// `tic-tac-go config`: print the effective difficulty parameters and rules.
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if let Some(arg) = args.first() {
+        eprintln!("usage: tic-tac-go [--config PATH] config");
+        return Err(format!("unexpected argument {}", arg).into());
+    }
+    let config = get();
+    let params = |d| {
+        let p: DifficultyParams = config.difficulty_params(d);
+        DifficultyOverrides {
+            crosses: Some(p.crosses),
+            circles: Some(p.circles),
+            scramble_budget: Some(p.scramble_budget),
+            attempts: Some(p.attempts),
+            min_score: Some(p.min_score),
+            max_score: p.max_score,
+            min_moves: Some(p.min_moves),
+            max_moves: p.max_moves,
+            hardest_of: Some(p.hardest_of),
+            ice_percent: Some(p.ice_percent),
+            heavy_crosses: Some(p.heavy_crosses),
+            powerups: Some(p.powerups),
+            undos: Some(p.undos),
+        }
+    };
+    let effective = Config {
+        difficulty: DifficultyConfig {
+            easy: params(Difficulty::Easy),
+            medium: params(Difficulty::Medium),
+            hard: params(Difficulty::Hard),
+            expert: params(Difficulty::Expert),
+            insane: params(Difficulty::Insane),
+        },
+        rules: config.rules,
+        board: config.board,
+        sound: config.sound,
+        reduce_motion: config.reduce_motion,
+        screen_reader: config.screen_reader,
+        key_repeat: config.key_repeat.clone(),
+        warn_losing_moves: config.warn_losing_moves,
+        assist: config.assist,
+        optimal_distance: config.optimal_distance,
+        soft_lock_warning: config.soft_lock_warning,
+        player_style: config.player_style,
+        grid_style: config.grid_style,
+        large_boards: config.large_boards,
+        online: config.online.clone(),
+        weekly_url: config.weekly_url.clone(),
+        live_generation: config.live_generation,
+    };
+    println!("{}", serde_json::to_string_pretty(&effective)?);
+    Ok(())
+}
@@ -1,948 +1,3699 @@
 use std::error::Error;
 use std::io::Stdout;
-use std::time::Duration;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::Terminal;
-use ratatui::backend::CrosstermBackend;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use rand::{Rng, thread_rng};
+use ratatui::backend::{Backend, CrosstermBackend};
 use ratatui::layout::{Alignment, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Span, Spans};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
-
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::{self, Strength};
+use crate::announce;
+use crate::app::{self, AppEvent, Screen, Step};
+use crate::autosave::{self, Autosave};
 use crate::board::Board;
-use crate::generator;
-use crate::movement;
-use crate::rules::{check_lose_flat, is_win_flat};
+use crate::browser;
+use crate::bundled;
+use crate::clipboard;
+use crate::generate::parse_difficulty;
+use crate::generator::{self, Undos};
+use crate::heuristics::LowerBound;
+use crate::history;
+use crate::journal::{self, Entry};
+use crate::layout;
+use crate::leaderboard::{self, Submission, TopTimes};
+use crate::logging;
+use crate::movement::{self, PowerUps};
+use crate::prefs::Prefs;
+use crate::puzzle::PuzzleFile;
+use crate::puzzle_editor;
+use crate::race;
+use crate::rules::{MAX_CIRCLES, MIN_CIRCLES, Rules, check_lose_flat, is_win_flat, move_budget};
+use crate::score::{self, Stats};
+use crate::settings;
+use crate::solver::{self, Outcome};
+use crate::sound::{self, Cue};
+use crate::title;
+use crate::weekly;
+
+/// A puzzle, or a run of puzzles, to play.
+enum Game {
+    /// Newly generated puzzles of a setup
+    Generated(generator::Setup, Rules),
+    Daily,
+    Weekly,
+    /// The unfinished puzzle from last time
+    Saved(Box<Autosave>),
+    /// A recent puzzle again
+    Retry(Box<Entry>),
+}
+
+/// The screens `run_screens` goes between, each saying which one comes next.
+/// Generating a puzzle and the results at its end are part of playing it.
+enum AppScreen {
+    Title,
+    /// Offer to pick up the unfinished puzzle from last time
+    Resume(Box<Autosave>),
+    Menu,
+    Difficulty,
+    Playing(Game),
+    /// Watch how a recent puzzle went
+    Replay(Box<Entry>),
+    Editor,
+    Browser,
+    History,
+    Sandbox,
+    Settings,
+    Quit,
+}
 
-pub enum StartupMode {
-    Play(generator::Difficulty),
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Play,
+    Daily,
+    Weekly,
     Create,
     Browse,
+    History,
+    Sandbox,
+    Settings,
 }
 
-pub fn select_mode(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-) -> Result<StartupMode, Box<dyn Error>> {
-    let mut selection: usize = 0; // 0: Play, 1: Create
-    let options = [
-        "Play generated puzzle (WIP)",
-        "Create puzzle",
-        "Browse created puzzles",
-    ];
+const MODE_OPTIONS: [(Mode, &str); 8] = [
+    (Mode::Play, "Play generated puzzle (WIP)"),
+    (Mode::Daily, "Today's daily puzzle"),
+    (Mode::Weekly, "Weekly challenge"),
+    (Mode::Create, "Create puzzle"),
+    (Mode::Browse, "Browse created puzzles"),
+    (Mode::History, "History"),
+    (Mode::Sandbox, "Sandbox"),
+    (Mode::Settings, "Settings"),
+];
+
+// Finished puzzles listed under the modes, newest first
+const RECENT_ENTRIES: usize = 10;
+
+enum MenuChoice {
+    Mode(Mode),
+    Retry(usize),
+    Replay(usize),
+}
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
-            let overlay_w = std::cmp::min(60, size.width.saturating_sub(4));
-            let overlay_h = 10u16;
-            let ox = (size.width.saturating_sub(overlay_w)) / 2;
-            let oy = (size.height.saturating_sub(overlay_h)) / 2;
-            let area = Rect::new(ox, oy, overlay_w, overlay_h);
-
-            let mut lines: Vec<Spans> = Vec::new();
+/// The main menu: the modes, then the most recently finished puzzles.
+/// Finishes with the chosen entry, or `None` when the player quits.
+struct MainMenu {
+    /// The entries of `MODE_OPTIONS` on offer; the weekly challenge only
+    /// when configured
+    modes: Vec<(Mode, &'static str)>,
+    /// Index into `modes`, then into `recent`
+    selection: usize,
+    recent: Vec<Entry>,
+}
+
+impl Screen for MainMenu {
+    type Output = Option<MenuChoice>;
+
+    fn update(&mut self, event: AppEvent) -> Step<Option<MenuChoice>> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        let recent = self.selection.checked_sub(self.modes.len());
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Step::Done(None),
+            KeyCode::Up | KeyCode::Char('w') => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('s') => {
+                self.selection = (self.selection + 1).min(self.modes.len() + self.recent.len() - 1)
+            }
+            KeyCode::Char(c @ '1'..='9') if (c as usize - '1' as usize) < self.modes.len() => {
+                self.selection = c as usize - '1' as usize
+            }
+            KeyCode::Enter | KeyCode::Char('r') if recent.is_some() => {
+                return Step::Done(recent.map(MenuChoice::Retry));
+            }
+            KeyCode::Char('p') if recent.is_some() => {
+                return Step::Done(recent.map(MenuChoice::Replay));
+            }
+            KeyCode::Enter => {
+                return Step::Done(Some(MenuChoice::Mode(self.modes[self.selection].0)));
+            }
+            _ => {}
+        }
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let (width, recent_rows) = match self.recent.len() {
+            0 => (60, 0),
+            n => (76, n as u16 + 2),
+        };
+        let overlay_w = std::cmp::min(width, size.width.saturating_sub(4));
+        let overlay_h = self.modes.len() as u16 + recent_rows + 6;
+        let area = layout::centered(size, overlay_w, overlay_h);
+
+        let mut lines: Vec<Spans> = Vec::new();
+        lines.push(Spans::from(Span::styled(
+            " Main Menu ",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Spans::from(Span::raw("")));
+
+        for (i, (_, option)) in self.modes.iter().enumerate() {
+            if i == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {}", option),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}", option))));
+            }
+        }
+        if !self.recent.is_empty() {
+            lines.push(Spans::from(Span::raw("")));
             lines.push(Spans::from(Span::styled(
-                " Main Menu ",
+                "Recent",
                 Style::default().add_modifier(Modifier::BOLD),
             )));
-            lines.push(Spans::from(Span::raw("")));
+        }
+        for (i, entry) in self.recent.iter().enumerate() {
+            let line = history::entry_line(entry);
+            if i + self.modes.len() == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {}", line),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}", line))));
+            }
+        }
+
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw(
+            if self.selection >= self.modes.len() {
+                "↑/↓ move, Enter/r: retry, p: replay, q to quit."
+            } else {
+                "Use ↑/↓ or w/s to move, Enter to select, q to quit."
+            },
+        )));
+
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// Show the main menu; the screen for the entry picked comes next.
+fn main_menu(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+) -> Result<AppScreen, Box<dyn Error>> {
+    let mut recent = journal::load();
+    recent.reverse();
+    recent.truncate(RECENT_ENTRIES);
+    let mut menu = MainMenu {
+        modes: MODE_OPTIONS
+            .into_iter()
+            .filter(|&(mode, _)| mode != Mode::Weekly || weekly::enabled())
+            .collect(),
+        selection: 0,
+        recent,
+    };
+    Ok(match app::run(terminal, &mut menu)? {
+        Some(MenuChoice::Mode(mode)) => match mode {
+            Mode::Play => AppScreen::Difficulty,
+            Mode::Daily => AppScreen::Playing(Game::Daily),
+            Mode::Weekly => AppScreen::Playing(Game::Weekly),
+            Mode::Create => AppScreen::Editor,
+            Mode::Browse => AppScreen::Browser,
+            Mode::History => AppScreen::History,
+            Mode::Sandbox => AppScreen::Sandbox,
+            Mode::Settings => AppScreen::Settings,
+        },
+        Some(MenuChoice::Retry(i)) => {
+            AppScreen::Playing(Game::Retry(Box::new(menu.recent.swap_remove(i))))
+        }
+        Some(MenuChoice::Replay(i)) => AppScreen::Replay(Box::new(menu.recent.swap_remove(i))),
+        None => AppScreen::Quit,
+    })
+}
+
+/// Where a game or screen left with `exit` goes on to.
+fn after(exit: GameExit) -> AppScreen {
+    match exit {
+        GameExit::Menu => AppScreen::Menu,
+        GameExit::Quit => AppScreen::Quit,
+    }
+}
+
+/// Show one screen after the other until the player quits: the title, the
+/// offer to pick up an unfinished puzzle from last time, then the main menu
+/// and whatever it leads to. A `quick` puzzle skips straight to playing it.
+pub fn run_screens(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    quick: Option<(generator::Setup, Rules)>,
+) -> Result<(), Box<dyn Error>> {
+    let mut screen = match quick {
+        Some((setup, rules)) => AppScreen::Playing(Game::Generated(setup, rules)),
+        None => AppScreen::Title,
+    };
+    loop {
+        screen = match screen {
+            AppScreen::Title => {
+                app::run(terminal, &mut title::TitleScreen::new())?;
+                match Autosave::load() {
+                    Some(save) => AppScreen::Resume(Box::new(save)),
+                    None => AppScreen::Menu,
+                }
+            }
+            AppScreen::Resume(save) => {
+                if resume_prompt(terminal, &save)? {
+                    AppScreen::Playing(Game::Saved(save))
+                } else {
+                    autosave::delete();
+                    AppScreen::Menu
+                }
+            }
+            AppScreen::Menu => main_menu(terminal)?,
+            AppScreen::Difficulty => match select_difficulty(terminal)? {
+                Some((setup, rules)) => AppScreen::Playing(Game::Generated(setup, rules)),
+                None => AppScreen::Menu,
+            },
+            AppScreen::Playing(game) => after(match game {
+                Game::Generated(setup, rules) => run_app(terminal, setup, rules)?,
+                Game::Daily => run_daily(terminal)?,
+                Game::Weekly => weekly::play(terminal)?,
+                Game::Saved(save) => resume(terminal, *save)?,
+                Game::Retry(entry) => retry_entry(terminal, &entry)?,
+            }),
+            AppScreen::Replay(entry) => {
+                replay(terminal, &entry)?;
+                AppScreen::Menu
+            }
+            AppScreen::Editor => {
+                puzzle_editor::show_create_placeholder(terminal)?;
+                AppScreen::Quit
+            }
+            AppScreen::Browser => {
+                browser::show_browser(terminal)?;
+                AppScreen::Quit
+            }
+            AppScreen::History => after(history::show_history(terminal)?),
+            AppScreen::Sandbox => after(run_sandbox(terminal)?),
+            AppScreen::Settings => after(settings::show_settings(terminal)?),
+            AppScreen::Quit => return Ok(()),
+        };
+    }
+}
+
+// Entries of the difficulty menu: the tiers in `Difficulty::ALL` order, then
+// the Custom form and the preset arenas
+const DIFFICULTY_ITEMS: [&str; 7] = [
+    "Easy",
+    "Medium",
+    "Hard",
+    "Expert",
+    "Insane",
+    "Custom...",
+    "Preset arena...",
+];
+
+/// Difficulty and rule variants. Finishes with the index of the chosen entry
+/// of `DIFFICULTY_ITEMS`, or `None` on quit.
+struct DifficultyMenu {
+    selection: usize,
+    rules: Rules,
+}
+
+impl Screen for DifficultyMenu {
+    type Output = Option<usize>;
+
+    fn update(&mut self, event: AppEvent) -> Step<Option<usize>> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        let rules = &mut self.rules;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Step::Done(None),
+            KeyCode::Char('v') => rules.variant = rules.variant.next(),
+            KeyCode::Char('g') => rules.diagonals = !rules.diagonals,
+            KeyCode::Char('c') => rules.chain_push = !rules.chain_push,
+            KeyCode::Char('p') => rules.pull = !rules.pull,
+            // off, then each strength in turn, then off again
+            KeyCode::Char('a') => {
+                (rules.adversarial, rules.ai_strength) =
+                    match (rules.adversarial, rules.ai_strength) {
+                        (false, _) => (true, Strength::Easy),
+                        (true, Strength::Easy) => (true, Strength::Normal),
+                        (true, Strength::Normal) => (true, Strength::Hard),
+                        (true, Strength::Hard) => (false, Strength::Easy),
+                    };
+            }
+            KeyCode::Char('m') => rules.move_limit = !rules.move_limit,
+            KeyCode::Char('t') => rules.wrap = !rules.wrap,
+            KeyCode::Char('h') => rules.sinkholes = !rules.sinkholes,
+            KeyCode::Char('u') => rules.unison = !rules.unison,
+            KeyCode::Char('b') => sound::set_enabled(!sound::enabled()),
+            KeyCode::Up | KeyCode::Char('w') => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('s') => {
+                self.selection = (self.selection + 1).min(DIFFICULTY_ITEMS.len() - 1)
+            }
+            KeyCode::Char(c @ '1'..='7') => self.selection = c as usize - '1' as usize,
+            KeyCode::Enter => return Step::Done(Some(self.selection)),
+            _ => {}
+        }
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let rules = &self.rules;
+        let size = f.size();
+        let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
+        let overlay_h = 24u16;
+        let area = layout::centered(size, overlay_w, overlay_h);
+
+        let mut lines: Vec<Spans> = Vec::new();
+        lines.push(Spans::from(Span::styled(
+            " Select difficulty ",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Spans::from(Span::raw("")));
+
+        for (i, label) in DIFFICULTY_ITEMS.iter().enumerate() {
+            if i == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {}", label),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}", label))));
+            }
+        }
+
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw(format!(
+            "Rules: {}  (v)",
+            rules.variant.label()
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Diagonal lines: {}  (g)",
+            if rules.diagonals { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Chain push: {}  (c)",
+            if rules.chain_push { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Pulls: {}  (p)",
+            if rules.pull { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Crosses fight back: {}  (a)",
+            if rules.adversarial {
+                rules.ai_strength.label()
+            } else {
+                "off"
+            }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Move limit: {}  (m)",
+            if rules.move_limit { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Wrap-around edges: {}  (t)",
+            if rules.wrap { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Crosses fall into holes: {}  (h)",
+            if rules.sinkholes { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Circles move together: {}  (u)",
+            if rules.unison { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw(format!(
+            "Sound: {}  (b)",
+            if sound::enabled() { "on" } else { "off" }
+        ))));
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw(
+            "Use ↑/↓ or w/s to move, Enter to select, q to quit.",
+        )));
+
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// The setup and rules picked in the difficulty menu, or `None` when the
+/// player went back.
+fn select_difficulty(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+) -> Result<Option<(generator::Setup, Rules)>, Box<dyn Error>> {
+    let mut prefs = Prefs::load();
+    let mut menu = DifficultyMenu {
+        selection: prefs.selection.unwrap_or(1).min(DIFFICULTY_ITEMS.len() - 1),
+        rules: prefs.rules.unwrap_or(crate::config::get().rules),
+    };
+    loop {
+        let Some(selection) = app::run(terminal, &mut menu)? else {
+            return Ok(None);
+        };
+        let tiers = generator::Difficulty::ALL;
+        let setup = match selection {
+            i if i < tiers.len() => Some(generator::Setup::Tier(tiers[i])),
+            i if i == tiers.len() => {
+                let custom = custom_form(terminal, prefs.custom)?;
+                prefs.custom = custom.or(prefs.custom);
+                custom.map(generator::Setup::Custom)
+            }
+            _ => preset_menu(terminal)?.map(generator::Setup::Preset),
+        };
+        // Esc in the form or list comes back to this menu
+        if let Some(setup) = setup {
+            prefs.selection = Some(selection);
+            prefs.rules = Some(menu.rules);
+            prefs.save();
+            return Ok(Some((setup, menu.rules)));
+        }
+    }
+}
+
+/// What `--quick [LEVEL]` plays: LEVEL, or else the tier last started from
+/// the difficulty menu or Medium, with the menu's last rules.
+pub fn quick_setup(args: &[String]) -> Result<(generator::Setup, Rules), Box<dyn Error>> {
+    let prefs = Prefs::load();
+    let difficulty = match args {
+        [] => prefs
+            .selection
+            .and_then(|i| generator::Difficulty::ALL.get(i).copied())
+            .unwrap_or(generator::Difficulty::Medium),
+        [level] => {
+            parse_difficulty(level).ok_or("--quick expects easy, medium, hard, expert or insane")?
+        }
+        _ => return Err("usage: tic-tac-go --quick [LEVEL]".into()),
+    };
+    let rules = prefs.rules.unwrap_or(crate::config::get().rules);
+    Ok((generator::Setup::Tier(difficulty), rules))
+}
+
+/// List of the fixed arenas; finishes with `None` when the player backs out.
+struct PresetMenu {
+    selection: usize,
+}
+
+impl Screen for PresetMenu {
+    type Output = Option<generator::Preset>;
+
+    fn update(&mut self, event: AppEvent) -> Step<Option<generator::Preset>> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        let presets = generator::Preset::ALL;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Step::Done(None),
+            KeyCode::Up | KeyCode::Char('w') => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('s') => {
+                self.selection = (self.selection + 1).min(presets.len() - 1)
+            }
+            KeyCode::Enter => return Step::Done(Some(presets[self.selection])),
+            _ => {}
+        }
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let presets = generator::Preset::ALL;
+        let size = f.size();
+        let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
+        let overlay_h = presets.len() as u16 + 7;
+        let area = layout::centered(size, overlay_w, overlay_h);
+
+        let mut lines: Vec<Spans> = vec![
+            Spans::from(Span::styled(
+                " Preset arena ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::raw("")),
+        ];
+        for (i, preset) in presets.iter().enumerate() {
+            if i == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {}", preset.label()),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}", preset.label()))));
+            }
+        }
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw("Enter to start, Esc to go back.")));
+
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+fn preset_menu(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+) -> Result<Option<generator::Preset>, Box<dyn Error>> {
+    app::run(terminal, &mut PresetMenu { selection: 0 })
+}
+
+/// Asks whether to resume the puzzle saved last time; finishes with the
+/// answer.
+struct ResumePrompt {
+    summary: String,
+}
+
+impl Screen for ResumePrompt {
+    type Output = bool;
+
+    fn update(&mut self, event: AppEvent) -> Step<bool> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => Step::Done(true),
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => Step::Done(false),
+            _ => Step::Idle,
+        }
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let overlay_w = std::cmp::min(44, size.width.saturating_sub(4));
+        let area = layout::centered(size, overlay_w, 8);
+        let lines = vec![
+            Spans::from(Span::styled(
+                " Resume previous puzzle? ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::raw("")),
+            Spans::from(Span::raw(self.summary.clone())),
+            Spans::from(Span::raw("")),
+            Spans::from(Span::raw("y/Enter: resume   n: discard it")),
+        ];
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// Ask whether to resume `save`.
+fn resume_prompt(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    save: &Autosave,
+) -> Result<bool, Box<dyn Error>> {
+    let kind = match save.daily {
+        Some(_) => "Daily puzzle".to_string(),
+        None => save.setup.label(),
+    };
+    let summary = format!(
+        "{}, {} moves, {}",
+        kind,
+        save.moves,
+        leaderboard::format_time(save.seconds)
+    );
+    app::run(terminal, &mut ResumePrompt { summary })
+}
+
+// Custom form fields: label, minimum, maximum
+const CUSTOM_FIELDS: [(&str, usize, usize); 10] = [
+    ("Rows", 3, 12),
+    ("Columns", 3, 16),
+    ("Holes (%)", 0, 40),
+    ("Hole blobs", 1, 6),
+    ("Corridors", 0, 1),
+    ("Ice (%)", 0, 40),
+    ("Circles", MIN_CIRCLES, MAX_CIRCLES),
+    ("Crosses", 0, 20),
+    ("Min. solution length", 0, 40),
+    ("Max. solution length", 0, 60),
+];
+// The on/off field among them
+const CORRIDORS_FIELD: usize = 4;
+// The field where 0 means no limit
+const MAX_MOVES_FIELD: usize = 9;
+// Rows and columns the Custom form goes up to with `large_boards` set
+const LARGE_BOARD_MAX: usize = 30;
+
+/// Form for the Custom difficulty; finishes with `None` when the player
+/// backs out.
+struct CustomForm {
+    values: [usize; 10],
+    selection: usize,
+}
+
+impl Screen for CustomForm {
+    type Output = Option<generator::CustomSettings>;
+
+    fn update(&mut self, event: AppEvent) -> Step<Option<generator::CustomSettings>> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        let (selection, values) = (&mut self.selection, &mut self.values);
+        let (_, min, mut max) = CUSTOM_FIELDS[*selection];
+        if *selection < 2 && crate::config::get().large_boards {
+            max = LARGE_BOARD_MAX;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Step::Done(None),
+            KeyCode::Up | KeyCode::Char('w') => *selection = selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('s') => {
+                *selection = (*selection + 1).min(CUSTOM_FIELDS.len() - 1)
+            }
+            KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('-') => {
+                values[*selection] = values[*selection].saturating_sub(1).max(min)
+            }
+            KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('+') => {
+                values[*selection] = (values[*selection] + 1).min(max)
+            }
+            KeyCode::Enter => {
+                return Step::Done(Some(generator::CustomSettings {
+                    rows: values[0],
+                    cols: values[1],
+                    hole_percent: values[2],
+                    blobs: values[3],
+                    corridors: values[CORRIDORS_FIELD] == 1,
+                    ice_percent: values[5],
+                    circles: values[6],
+                    crosses: values[7],
+                    min_moves: values[8],
+                    max_moves: values[MAX_MOVES_FIELD],
+                }));
+            }
+            _ => {}
+        }
+        // Leave room for the circles and a few free cells
+        let max_crosses = (values[0] * values[1]).saturating_sub(values[6] + 3) / 2;
+        values[7] = values[7].min(max_crosses);
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let overlay_w = std::cmp::min(44, size.width.saturating_sub(4));
+        let overlay_h = 17u16;
+        let area = layout::centered(size, overlay_w, overlay_h);
+
+        let mut lines: Vec<Spans> = vec![
+            Spans::from(Span::styled(
+                " Custom puzzle ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::raw("")),
+        ];
+        for (i, &(label, _, _)) in CUSTOM_FIELDS.iter().enumerate() {
+            let value = match (i, self.values[i]) {
+                (CORRIDORS_FIELD, 0) => "off".to_string(),
+                (CORRIDORS_FIELD, _) => "on".to_string(),
+                (MAX_MOVES_FIELD, 0) => "any".to_string(),
+                (_, v) => v.to_string(),
+            };
+            let text = format!("{:<22}< {:>3} >", label, value);
+            if i == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {}", text),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}", text))));
+            }
+        }
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw("↑/↓ select, ←/→ change")));
+        lines.push(Spans::from(Span::raw("Enter to start, Esc to go back.")));
+
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// The Custom form, filled in with `last` when the player has submitted it
+/// before.
+fn custom_form(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    last: Option<generator::CustomSettings>,
+) -> Result<Option<generator::CustomSettings>, Box<dyn Error>> {
+    let mut values = [6, 8, 10, 2, 1, 0, 3, 6, 8, 0];
+    if let Some(c) = last {
+        values = [
+            c.rows,
+            c.cols,
+            c.hole_percent,
+            c.blobs,
+            usize::from(c.corridors),
+            c.ice_percent,
+            c.circles,
+            c.crosses,
+            c.min_moves,
+            c.max_moves,
+        ];
+        // a hand-edited file or a change to `large_boards` can leave values
+        // the form could not have produced
+        for (i, value) in values.iter_mut().enumerate() {
+            let (_, min, mut max) = CUSTOM_FIELDS[i];
+            if i < 2 && crate::config::get().large_boards {
+                max = LARGE_BOARD_MAX;
+            }
+            *value = (*value).clamp(min, max);
+        }
+    }
+    let mut form = CustomForm {
+        values,
+        selection: 0,
+    };
+    app::run(terminal, &mut form)
+}
+
+// Solver budget for finding the par of a puzzle file under the move-limit rule
+const PAR_MAX_NODES: usize = 200_000;
+// Solver budget for the optimal moves left, searched for after every move
+const OPTIMAL_MAX_NODES: usize = 200_000;
+
+/// The puzzle for `seed`: from the bundled puzzles when they cover `setup`
+/// and `rules`, generated otherwise.
+fn puzzle_for(seed: u64, setup: generator::Setup, rules: Rules) -> generator::GeneratedPuzzle {
+    bundled::lookup(seed, setup, rules)
+        .unwrap_or_else(|| generator::generate_verified(seed, setup, rules))
+}
+
+/// Start generating the puzzle for `seed` on a background thread.
+fn spawn_generation(
+    seed: u64,
+    setup: generator::Setup,
+    rules: Rules,
+) -> mpsc::Receiver<generator::GeneratedPuzzle> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(puzzle_for(seed, setup, rules));
+    });
+    rx
+}
+
+/// Start placing new pieces on `board`, from a random seed, on a background
+/// thread.
+fn spawn_reroll(
+    board: &Board,
+    board_seed: u64,
+    setup: generator::Setup,
+) -> mpsc::Receiver<generator::GeneratedPuzzle> {
+    let (tx, rx) = mpsc::channel();
+    let board = board.clone();
+    thread::spawn(move || {
+        let seed = thread_rng().r#gen();
+        let _ = tx.send(generator::reroll(seed, board, board_seed, setup));
+    });
+    rx
+}
+
+/// How the player left a game.
+pub enum GameExit {
+    Quit,
+    /// Back to the menu the game was started from
+    Menu,
+}
+
+enum PlayOutcome {
+    Quit,
+    Menu,
+    NewPuzzle(Box<generator::GeneratedPuzzle>),
+}
+
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    setup: generator::Setup,
+    rules: Rules,
+) -> Result<GameExit, Box<dyn Error>> {
+    let puzzle = puzzle_for(thread_rng().r#gen(), setup, rules);
+    play_from(terminal, puzzle, setup, rules, None, None)
+}
+
+/// Pick up the puzzle `save` left off; new puzzles after it are generated
+/// as usual.
+fn resume(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    save: Autosave,
+) -> Result<GameExit, Box<dyn Error>> {
+    let puzzle = stored_puzzle(&save.puzzle, save.seed, save.par)?;
+    let rules = puzzle.board.rules;
+    log::info!("resuming seed {} after {} moves", save.seed, save.moves);
+    play_from(terminal, puzzle, save.setup, rules, save.daily, Some(&save))
+}
+
+/// Play the puzzle of a journal entry again from the start.
+pub fn retry_entry(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    entry: &Entry,
+) -> Result<GameExit, Box<dyn Error>> {
+    let puzzle = stored_puzzle(&entry.puzzle, entry.seed, entry.par)?;
+    let rules = puzzle.board.rules;
+    log::info!("retrying seed {} from the journal", entry.seed);
+    // a replay, not the day's result: nothing is sent
+    play_from(terminal, puzzle, entry.setup, rules, None, None)
+}
+
+/// A generated puzzle as stored in a file, e.g. by the autosave.
+fn stored_puzzle(
+    file: &PuzzleFile,
+    seed: u64,
+    par: Option<usize>,
+) -> Result<generator::GeneratedPuzzle, Box<dyn Error>> {
+    let board = file.board()?;
+    let flat = |pieces: &[(usize, usize)]| -> Vec<usize> {
+        pieces.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+    };
+    let (circles, crosses) = (flat(&file.circles), flat(&file.crosses));
+    Ok(generator::GeneratedPuzzle {
+        seed,
+        board,
+        circles,
+        crosses,
+        player_idx: file.player_idx(),
+        par,
+        relaxed: None,
+        board_seed: None,
+        rating: None,
+    })
+}
+
+/// Today's daily puzzle: the same Medium puzzle with the standard rules for
+/// everyone, its seed being the day (see `leaderboard`), whatever their
+/// config says. New puzzles after it are ordinary random ones.
+fn run_daily(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+) -> Result<GameExit, Box<dyn Error>> {
+    let day = leaderboard::today();
+    let setup = generator::Setup::Tier(generator::Difficulty::Medium);
+    let rules = Rules::default();
+    log::info!("daily puzzle for day {}", day);
+    let puzzle = generator::generate_daily(day, rules);
+    play_from(terminal, puzzle, setup, rules, Some(day), None)
+}
+
+/// Play `puzzle` and the new puzzles asked for after it; `daily` is the day
+/// when `puzzle` is a daily puzzle, and `saved` the game to continue it from.
+fn play_from(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    mut puzzle: generator::GeneratedPuzzle,
+    setup: generator::Setup,
+    rules: Rules,
+    mut daily: Option<u64>,
+    mut saved: Option<&Autosave>,
+) -> Result<GameExit, Box<dyn Error>> {
+    loop {
+        // The following puzzle is generated while this one is played, keyed by
+        // the next seed, so asking for a new puzzle is (nearly) instant. After
+        // a daily puzzle that would be tomorrow's, so a random one follows.
+        let next_seed = match daily {
+            Some(_) => thread_rng().r#gen(),
+            None => puzzle.seed.wrapping_add(1),
+        };
+        let next = spawn_generation(next_seed, setup, rules);
+        let screen = play_generated(
+            terminal,
+            &puzzle,
+            setup,
+            next_seed,
+            &next,
+            daily.take(),
+            saved.take(),
+        );
+        match screen? {
+            PlayOutcome::Quit => return Ok(GameExit::Quit),
+            PlayOutcome::Menu => return Ok(GameExit::Menu),
+            PlayOutcome::NewPuzzle(p) => puzzle = *p,
+        }
+    }
+}
+
+// Background of ice cells on the board
+const ICE_BG: Color = Color::Blue;
+// Background of portal cells; an empty portal shows its pair's letter
+const PORTAL_BG: Color = Color::Magenta;
+const PORTAL_LABELS: [&str; 8] = ["A", "B", "C", "D", "E", "F", "G", "H"];
+// Background of goal cells under the goal-cell rules
+const GOAL_BG: Color = Color::Green;
+// Colour of the solid blocks drawn for wall cells
+const WALL_FG: Color = Color::DarkGray;
+// Backgrounds of the threat overlay, for one, two and three or more cross
+// lines through a cell, and the counts it shows
+const HEAT_BG: [Color; 3] = [Color::DarkGray, Color::LightRed, Color::Red];
+const HEAT_GLYPHS: [&str; 10] = ["", "1", "2", "3", "4", "5", "6", "7", "8", "9+"];
+// Background of the cells a dash went through, and how long it stays
+const DASH_BG: Color = Color::DarkGray;
+const DASH_TRAIL: Duration = Duration::from_millis(400);
+// Largest repeat count a move may be prefixed with
+const MAX_COUNT: usize = 999;
+// What setting and going back to each bookmark says
+const BOOKMARK_SET: [&str; 3] = ["bookmark 1 set", "bookmark 2 set", "bookmark 3 set"];
+const BOOKMARK_BACK: [&str; 3] = [
+    "back at bookmark 1",
+    "back at bookmark 2",
+    "back at bookmark 3",
+];
+
+/// How the player's circle stands out from the others, besides its colour.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlayerStyle {
+    /// A bold `o`
+    #[default]
+    Bold,
+    /// An `@` instead of an `o`
+    At,
+    /// A blinking `o`, where the terminal blinks
+    Blink,
+    /// An `o` in inverted colours
+    Inverse,
+}
+
+impl PlayerStyle {
+    pub const ALL: [PlayerStyle; 4] = [
+        PlayerStyle::Bold,
+        PlayerStyle::At,
+        PlayerStyle::Blink,
+        PlayerStyle::Inverse,
+    ];
+
+    /// The player's glyph and its style, unless an animation overrides it.
+    fn look(self) -> (&'static str, Style) {
+        let style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        match self {
+            PlayerStyle::Bold => ("o", style),
+            PlayerStyle::At => ("@", style),
+            PlayerStyle::Blink => ("o", style.add_modifier(Modifier::SLOW_BLINK)),
+            PlayerStyle::Inverse => ("o", style.add_modifier(Modifier::REVERSED)),
+        }
+    }
+}
+
+/// How the board's grid lines are drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GridStyle {
+    /// Only the lines between two present cells
+    #[default]
+    Minimal,
+    /// A full box around every present cell
+    Classic,
+}
+
+impl GridStyle {
+    pub const ALL: [GridStyle; 2] = [GridStyle::Minimal, GridStyle::Classic];
+
+    /// Columns left of the first cell: the classic style's left edge.
+    fn left_edge(self) -> usize {
+        match self {
+            GridStyle::Minimal => 0,
+            GridStyle::Classic => 1,
+        }
+    }
+}
+
+/// The box-drawing character where grid lines meet, given which of the four
+/// directions they leave in.
+fn junction(up: bool, down: bool, left: bool, right: bool) -> &'static str {
+    match (up, down, left, right) {
+        (false, false, false, false) => " ",
+        (false, false, _, _) => "─",
+        (_, _, false, false) => "│",
+        (false, true, false, true) => "┌",
+        (false, true, true, false) => "┐",
+        (true, false, false, true) => "└",
+        (true, false, true, false) => "┘",
+        (true, true, false, true) => "├",
+        (true, true, true, false) => "┤",
+        (false, true, true, true) => "┬",
+        (true, false, true, true) => "┴",
+        (true, true, true, true) => "┼",
+    }
+}
+
+/// Background for the cell at (row, col): tinted for goals, portals and ice.
+fn cell_bg(board: &Board, row: usize, col: usize) -> Style {
+    if board.is_goal(row, col) {
+        Style::default().bg(GOAL_BG)
+    } else if board.portal_exit(row, col).is_some() {
+        Style::default().bg(PORTAL_BG)
+    } else if board.is_ice(row as isize, col as isize) {
+        Style::default().bg(ICE_BG)
+    } else {
+        Style::default()
+    }
+}
+
+/// For each cell by flat index, the losing lines through it that hold a
+/// cross and no circle or block: the lines a cross pushed onto it would add to.
+fn threat_heat(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+) -> Vec<usize> {
+    let scoring = board.line_crosses(crosses);
+    let blocks = &crosses[scoring.len()..];
+    let mut heat = vec![0; board.total_cells];
+    for line in board.rules.rule_set().losing_lines(board) {
+        if line
+            .iter()
+            .any(|p| circles.contains(p) || blocks.contains(p))
+            || !line.iter().any(|p| scoring.contains(p))
+        {
+            continue;
+        }
+        for &(r, c) in line.iter().filter(|p| !crosses.contains(p)) {
+            heat[board.to_flat(r, c)] += 1;
+        }
+    }
+    heat
+}
+
+/// Glyph for an empty cell: its portal letter, a ripple on ice, else blank.
+fn empty_glyph(board: &Board, row: usize, col: usize) -> &'static str {
+    if let Some(i) = board
+        .portal_pairs()
+        .iter()
+        .position(|&(a, b)| a == (row, col) || b == (row, col))
+    {
+        PORTAL_LABELS[i % PORTAL_LABELS.len()]
+    } else if board.is_ice(row as isize, col as isize) {
+        "~"
+    } else {
+        " "
+    }
+}
+
+/// One cell's content (`glyph` padded to three columns) on background `bg`,
+/// followed by its right-hand separator.
+fn cell_spans(
+    glyph: &'static str,
+    style: Style,
+    bg: Style,
+    next_present: bool,
+) -> [Span<'static>; 4] {
+    [
+        Span::styled(" ", bg),
+        Span::styled(glyph, bg.patch(style)),
+        Span::styled(" ", bg),
+        Span::raw(if next_present { "│" } else { " " }),
+    ]
+}
+
+/// The bookmark Shift+1, 2 or 3 goes back to: `!`, `@` and `#` as most
+/// terminals send them, or the digit with Shift where modifiers are reported.
+fn bookmark_slot(key: KeyEvent) -> Option<usize> {
+    match key.code {
+        KeyCode::Char('!') => Some(0),
+        KeyCode::Char('@') => Some(1),
+        KeyCode::Char('#') => Some(2),
+        KeyCode::Char(c @ '1'..='3') if key.modifiers.contains(KeyModifiers::SHIFT) => {
+            Some(c as usize - '1' as usize)
+        }
+        _ => None,
+    }
+}
+
+/// Direction for an arrow or WASD key (either case, so Shift+WASD counts too).
+fn key_direction(code: KeyCode) -> Option<(isize, isize)> {
+    match code {
+        KeyCode::Up => Some((-1, 0)),
+        KeyCode::Down => Some((1, 0)),
+        KeyCode::Left => Some((0, -1)),
+        KeyCode::Right => Some((0, 1)),
+        KeyCode::Char(c) => match c.to_ascii_lowercase() {
+            'w' => Some((-1, 0)),
+            's' => Some((1, 0)),
+            'a' => Some((0, -1)),
+            'd' => Some((0, 1)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The direction to pull in, when the pull rule is on and the key asks for a
+/// pull: a direction with Shift held, or any direction after `p`.
+fn pull_direction(
+    board: &Board,
+    pull_armed: bool,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) -> Option<(isize, isize)> {
+    if !board.rules.pull || !(pull_armed || modifiers.contains(KeyModifiers::SHIFT)) {
+        return None;
+    }
+    key_direction(code)
+}
+
+/// The single move a dash repeats: a direction with Shift held (or typed in
+/// upper case). Under the pull rule Shift pulls instead, so there is no dash.
+fn dash_key(board: &Board, key: KeyEvent) -> Option<KeyEvent> {
+    if board.rules.pull {
+        return None;
+    }
+    let code = match key.code {
+        KeyCode::Char(c) if c.is_ascii_uppercase() => KeyCode::Char(c.to_ascii_lowercase()),
+        code if key.modifiers.contains(KeyModifiers::SHIFT) => code,
+        _ => return None,
+    };
+    key_direction(code)?;
+    Some(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+// Log lines shown by the F12 overlay
+const LOG_OVERLAY_LINES: usize = 8;
+
+// How long a "blocked" toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_millis(1200);
+
+/// Toast message for a key that left the board unchanged, when that key was
+/// a move the player could have expected to work.
+fn blocked_toast(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    pull: bool,
+    code: KeyCode,
+) -> Option<&'static str> {
+    if pull {
+        return Some(movement::Blocked::Obstacle.message());
+    }
+    let (dr, dc) = key_direction(code)?;
+    movement::blocked_reason(circles, crosses, player_idx, dr, dc, board).map(|b| b.message())
+}
+
+// Victory and defeat animations play this long before the overlay appears,
+// advancing one frame per tick
+const END_ANIMATION: Duration = Duration::from_millis(900);
+const ANIMATION_FRAME: Duration = Duration::from_millis(150);
+
+/// Frame of the end-of-game animation still playing for a game that ended at
+/// `ended_at`, or `None` once it is over or when reduce motion is set.
+fn animation_frame(ended_at: Option<Instant>) -> Option<u32> {
+    let elapsed = ended_at?.elapsed();
+    if crate::config::get().reduce_motion || elapsed >= END_ANIMATION {
+        return None;
+    }
+    Some((elapsed.as_millis() / ANIMATION_FRAME.as_millis()) as u32)
+}
+
+/// Style of a piece in the winning or losing line: it flashes between
+/// `color` as background and as foreground.
+fn flash_style(color: Color, frame: u32) -> Style {
+    if frame.is_multiple_of(2) {
+        Style::default()
+            .fg(Color::Black)
+            .bg(color)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(color).add_modifier(Modifier::BOLD)
+    }
+}
+
+/// Board column while the defeat shake plays: one cell right, then left.
+fn shake(x: u16, frame: Option<u32>) -> u16 {
+    match frame.map(|f| f % 4) {
+        Some(1) => x.saturating_add(1),
+        Some(3) => x.saturating_sub(1),
+        _ => x,
+    }
+}
+
+/// Sound for a game that just ended.
+fn outcome_cue(won: bool, lost: bool) {
+    if won {
+        sound::play(Cue::Win);
+    } else if lost {
+        sound::play(Cue::Loss);
+    }
+}
+
+/// A cell showing its flat index, for the debug view.
+fn index_spans(idx: usize, bg: Style, next_present: bool) -> [Span<'static>; 2] {
+    [
+        Span::styled(
+            format!("{:^3}", idx),
+            bg.patch(Style::default().fg(Color::DarkGray)),
+        ),
+        Span::raw(if next_present { "│" } else { " " }),
+    ]
+}
+
+/// Debug view lines describing the current position.
+fn debug_lines(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    bound: &LowerBound,
+) -> Vec<String> {
+    let flat = |v: &[(usize, usize)]| -> Vec<usize> {
+        v.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+    };
+    let hash = PuzzleFile::from_parts(board, circles, crosses, player_idx, None).canonical_hash();
+    let estimate = match bound.estimate(circles, crosses, player_idx) {
+        Some(d) => format!("at least {} moves to win", d),
+        None => "no win reachable".to_string(),
+    };
+    vec![
+        format!("state {:016x}", hash),
+        estimate,
+        format!(
+            "player: circle {} at {}",
+            player_idx,
+            flat(circles)[player_idx]
+        ),
+        format!("circles {:?}", flat(circles)),
+        format!("crosses {:?}", flat(crosses)),
+    ]
+}
+
+/// Debug panel in the top left corner.
+fn render_debug<B: Backend>(f: &mut Frame<B>, lines: &[String]) {
+    let size = f.size();
+    let width = lines
+        .iter()
+        .map(|l| l.chars().count() as u16 + 2)
+        .max()
+        .unwrap_or(0)
+        .min(size.width);
+    let height = (lines.len() as u16 + 2).min(size.height);
+    let area = layout::left(layout::top(size, height), width);
+    let text: Vec<Spans> = lines
+        .iter()
+        .map(|l| Spans::from(Span::raw(l.clone())))
+        .collect();
+    let para = Paragraph::new(text)
+        .style(Style::default().fg(Color::Gray).bg(Color::Black))
+        .block(Block::default().borders(Borders::ALL).title("debug (F3)"));
+    f.render_widget(Clear, area);
+    f.render_widget(para, area);
+}
+
+/// Debug overlay along the bottom of the screen with the newest log lines.
+fn render_log<B: Backend>(f: &mut Frame<B>) {
+    let size = f.size();
+    let height = size.height.min(LOG_OVERLAY_LINES as u16 + 2);
+    let area = layout::bottom(size, height);
+    let lines: Vec<Spans> = logging::recent(height.saturating_sub(2) as usize)
+        .into_iter()
+        .map(|l| Spans::from(Span::raw(l)))
+        .collect();
+    let para = Paragraph::new(lines)
+        .style(Style::default().fg(Color::Gray).bg(Color::Black))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} (F12 to hide)", logging::LOG_FILE)),
+        );
+    f.render_widget(Clear, area);
+    f.render_widget(para, area);
+}
+
+/// The victory or defeat message over a finished game, with the lines under
+/// it (the keys that lead on from it, and the like), one per row.
+fn render_end_overlay<B: Backend>(f: &mut Frame<B>, play: &Play, lines: &[String]) {
+    let (title, headline, style) = if play.won {
+        (
+            "Victory",
+            " YOU WON! ",
+            Style::default().fg(Color::Black).bg(Color::Green),
+        )
+    } else if play.out_of_moves {
+        (
+            "Defeat",
+            " YOU LOST! out of moves ",
+            Style::default().fg(Color::White).bg(Color::Red),
+        )
+    } else if play.stalemate {
+        (
+            "Stalemate",
+            " DRAW! no move changes the board ",
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        )
+    } else if play.lost {
+        (
+            "Defeat",
+            " YOU LOST! three crosses aligned ",
+            Style::default().fg(Color::White).bg(Color::Red),
+        )
+    } else {
+        return;
+    };
+    let hint = Style::default().fg(Color::White).bg(Color::Black);
+    let mut msg_lines: Vec<Spans> = vec![
+        Spans::from(Span::raw("")),
+        Spans::from(Span::styled(headline, style.add_modifier(Modifier::BOLD))),
+        Spans::from(Span::raw("")),
+    ];
+    msg_lines.extend(
+        lines
+            .iter()
+            .map(|l| Spans::from(Span::styled(l.clone(), hint))),
+    );
+
+    let size = f.size();
+    let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
+    let overlay_h = msg_lines.len() as u16 + 2;
+    let o_area = layout::centered(size, overlay_w, overlay_h);
+    let overlay = Paragraph::new(msg_lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().bg(Color::Black))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .style(Style::default().bg(Color::Black)),
+        );
+    f.render_widget(Clear, o_area);
+    f.render_widget(
+        Block::default().style(Style::default().bg(Color::Black)),
+        o_area,
+    );
+    f.render_widget(overlay, o_area);
+}
+
+/// Say that the game is paused, in `area`, while the terminal is out of focus.
+fn render_paused<B: Backend>(f: &mut Frame<B>, play: &Play, area: Rect) {
+    if play.paused_at.is_none() {
+        return;
+    }
+    let line = Spans::from(Span::styled(
+        " Paused: the clock runs again when the window has focus ",
+        Style::default().fg(Color::Black).bg(Color::Gray),
+    ));
+    f.render_widget(Paragraph::new(line).alignment(Alignment::Center), area);
+}
 
-            for i in 0..options.len() {
-                if i == selection {
-                    lines.push(Spans::from(Span::styled(
-                        format!("> {}", options[i]),
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    )));
+/// Say in `area` that the position can't be won any more, while it is so.
+fn render_soft_lock<B: Backend>(f: &mut Frame<B>, play: &Play, area: Rect) {
+    if !play.soft_locked() {
+        return;
+    }
+    let line = Spans::from(Span::styled(
+        " this position is unwinnable - press r to restart ",
+        Style::default().fg(Color::Gray).bg(Color::DarkGray),
+    ));
+    f.render_widget(Paragraph::new(line).alignment(Alignment::Center), area);
+}
+
+/// Whether `toast` is still on screen.
+fn toast_shown(toast: Option<(&'static str, Instant)>) -> bool {
+    toast.is_some_and(|(_, since)| since.elapsed() < TOAST_DURATION)
+}
+
+/// Draw a toast centered in `area`, until it expires.
+fn render_toast<B: Backend>(f: &mut Frame<B>, toast: Option<(&'static str, Instant)>, area: Rect) {
+    let Some((text, _)) = toast else { return };
+    if !toast_shown(toast) {
+        return;
+    }
+    let line = Spans::from(Span::styled(
+        format!(" {} ", text),
+        Style::default()
+            .fg(Color::Black)
+            .bg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    ));
+    f.render_widget(Paragraph::new(line).alignment(Alignment::Center), area);
+}
+
+// Rows at the top of the screen the screen-reader line may wrap onto
+const ANNOUNCEMENT_ROWS: u16 = 2;
+
+/// Draw the screen-reader description of the last move at the top of the
+/// screen, as plain text that stays until the next move.
+fn render_announcement<B: Backend>(f: &mut Frame<B>, announcement: Option<&str>) {
+    let Some(text) = announcement else { return };
+    let area = layout::top(f.size(), ANNOUNCEMENT_ROWS);
+    let paragraph = Paragraph::new(Spans::from(Span::raw(text))).wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+}
+
+/// How big each board cell is drawn. Boards get bigger cells when the
+/// terminal has room for them, so pieces stay easy to see on large screens.
+#[derive(Clone, Copy, PartialEq)]
+enum CellScale {
+    Normal,
+    Large,
+    Huge,
+}
+
+impl CellScale {
+    /// Characters across a cell and rows of text down it, borders excluded.
+    fn cell_size(self) -> (usize, usize) {
+        match self {
+            CellScale::Normal => (3, 1),
+            CellScale::Large => (5, 2),
+            CellScale::Huge => (7, 3),
+        }
+    }
+
+    /// Width and height of the whole grid, borders included.
+    fn grid_size(self, board: &Board) -> (u16, u16) {
+        let (w, h) = self.cell_size();
+        (
+            ((w + 1) * board.cols + 1) as u16,
+            ((h + 1) * board.rows + 1) as u16,
+        )
+    }
+
+    /// The largest scale whose grid fits `size` with `margin` (columns, rows)
+    /// left over for the lines around the board.
+    fn fit(board: &Board, size: Rect, margin: (u16, u16)) -> Self {
+        [CellScale::Huge, CellScale::Large]
+            .into_iter()
+            .find(|s| {
+                let (w, h) = s.grid_size(board);
+                w + margin.0 <= size.width && h + margin.1 <= size.height
+            })
+            .unwrap_or(CellScale::Normal)
+    }
+
+    /// Rows of text for a cell showing `glyph`: circles and crosses are drawn
+    /// as outlines on the bigger scales (heavy crosses in block strokes),
+    /// anything else sits in the middle.
+    fn art(self, glyph: &str) -> Vec<String> {
+        let art: &[&str] = match (self, glyph) {
+            (CellScale::Large, "o") => &[" ╭─╮ ", " ╰─╯ "],
+            (CellScale::Large, "x") => &[" ╲ ╱ ", " ╱ ╲ "],
+            (CellScale::Large, "X") => &[" ▚ ▞ ", " ▞ ▚ "],
+            (CellScale::Huge, "o") => &[" ╭───╮ ", " │   │ ", " ╰───╯ "],
+            (CellScale::Huge, "x") => &[" ╲   ╱ ", "   ╳   ", " ╱   ╲ "],
+            (CellScale::Huge, "X") => &[" ▚   ▞ ", "   █   ", " ▞   ▚ "],
+            _ => &[],
+        };
+        if !art.is_empty() {
+            return art.iter().map(|s| s.to_string()).collect();
+        }
+        let (w, h) = self.cell_size();
+        (0..h)
+            .map(|k| {
+                let text = if k == (h - 1) / 2 { glyph } else { "" };
+                format!("{:^w$}", text)
+            })
+            .collect()
+    }
+
+    /// Spans for each text row of a cell showing `glyph`.
+    fn cell(
+        self,
+        glyph: &'static str,
+        style: Style,
+        bg: Style,
+        next_present: bool,
+    ) -> Vec<Vec<Span<'static>>> {
+        if self == CellScale::Normal {
+            return vec![cell_spans(glyph, style, bg, next_present).to_vec()];
+        }
+        let separator = if next_present { "│" } else { " " };
+        self.art(glyph)
+            .into_iter()
+            .map(|row| vec![Span::styled(row, bg.patch(style)), Span::raw(separator)])
+            .collect()
+    }
+
+    /// Spans for each text row of a cell showing its flat index.
+    fn index_cell(self, idx: usize, bg: Style, next_present: bool) -> Vec<Vec<Span<'static>>> {
+        if self == CellScale::Normal {
+            return vec![index_spans(idx, bg, next_present).to_vec()];
+        }
+        let separator = if next_present { "│" } else { " " };
+        let style = bg.patch(Style::default().fg(Color::DarkGray));
+        self.art(&idx.to_string())
+            .into_iter()
+            .map(|row| vec![Span::styled(row, style), Span::raw(separator)])
+            .collect()
+    }
+}
+
+/// The board as lines of text at `scale`: borders in the config's
+/// `grid_style`, pieces, walls, portals and ice, and the line that won or
+/// lost flashing while the end-of-game animation plays `frame`.
+fn grid_lines(play: &Play, frame: Option<u32>, scale: CellScale) -> Vec<Spans<'static>> {
+    let board = play.board;
+    let (rows, cols, row_widths) = (board.rows, board.cols, &board.row_widths);
+    let (circles, crosses) = (&play.circles, &play.crosses);
+    let (cell_w, cell_h) = scale.cell_size();
+    // pieces of the winning or losing line, flashed while the animation plays
+    let flashing = match frame {
+        Some(_) if play.won => board.rules.rule_set().winning_cells(circles, board),
+        Some(_) if play.lost => board
+            .rules
+            .rule_set()
+            .losing_cells(board.line_crosses(crosses), board),
+        _ => Vec::new(),
+    };
+    let present = |row: usize, col: usize| col < row_widths[row] && board.is_cell_present(row, col);
+    let style = crate::config::get().grid_style;
+    // classic style: the box line above `row` (the bottom one for `rows`),
+    // with a line along every edge of a present cell
+    let box_line = |row: usize| {
+        let above = |col: usize| row > 0 && col < cols && present(row - 1, col);
+        let below = |col: usize| row < rows && col < cols && present(row, col);
+        let edge = |col: usize| above(col) || below(col);
+        let mut line = String::new();
+        for col in 0..=cols {
+            let before = |side: &dyn Fn(usize) -> bool| col > 0 && side(col - 1);
+            line.push_str(junction(
+                before(&above) || above(col),
+                before(&below) || below(col),
+                col > 0 && edge(col - 1),
+                edge(col),
+            ));
+            if col < cols {
+                line.push_str(&(if edge(col) { "─" } else { " " }).repeat(cell_w));
+            }
+        }
+        Spans::from(Span::raw(line))
+    };
+    // the separator right of (row, col) in the classic style
+    let classic_separator = |row: usize, col: usize| {
+        let edge = present(row, col) || (col + 1 < cols && present(row, col + 1));
+        Span::raw(if edge { "│" } else { " " })
+    };
+    let heat = if play.show_heat {
+        threat_heat(board, circles, crosses)
+    } else {
+        vec![0; board.total_cells]
+    };
+    // a horizontal border segment under or over each cell where `drawn` says so
+    let border = |drawn: &dyn Fn(usize) -> bool| {
+        let line: String = (0..cols)
+            .map(|col| {
+                let fill = if drawn(col) { "─" } else { " " };
+                format!("{} ", fill.repeat(cell_w))
+            })
+            .collect();
+        Spans::from(Span::raw(line))
+    };
+
+    let mut lines: Vec<Spans> = Vec::new();
+
+    // Top border (aggressive removal): horizontal dashes only where top cell exists
+    lines.push(match style {
+        GridStyle::Minimal => border(&|col| rows > 0 && present(0, col)),
+        GridStyle::Classic => box_line(0),
+    });
+
+    for row in 0..rows {
+        // Content lines: draw only internal vertical separators between adjacent present cells
+        let mut span_lines: Vec<Vec<Span>> = vec![Vec::new(); cell_h];
+        if style == GridStyle::Classic {
+            let left = if present(row, 0) { "│" } else { " " };
+            for line in &mut span_lines {
+                line.push(Span::raw(left));
+            }
+        }
+        for col in 0..cols {
+            let cell = if board.is_wall(row, col) {
+                // wall: a solid block inside the board
+                let block = Span::styled("█".repeat(cell_w), Style::default().fg(WALL_FG));
+                vec![vec![block, Span::raw(" ")]; cell_h]
+            } else if !present(row, col) {
+                // missing cell: reserve full cell width
+                vec![vec![Span::raw(" ".repeat(cell_w)), Span::raw(" ")]; cell_h]
+            } else {
+                let next_present = present(row, col + 1);
+                let bg = if play.dash_trail().contains(&(row, col)) {
+                    Style::default().bg(DASH_BG)
                 } else {
-                    lines.push(Spans::from(Span::raw(format!("  {}", options[i]))));
+                    cell_bg(board, row, col)
+                };
+                let flash = |color| match frame {
+                    Some(n) if flashing.contains(&(row, col)) => Some(flash_style(color, n)),
+                    _ => None,
+                };
+                if let Some(idx) = circles.iter().position(|&p| p == (row, col)) {
+                    let (glyph, style) = if idx == play.player_idx {
+                        crate::config::get().player_style.look()
+                    } else {
+                        ("o", Style::default().fg(Color::LightBlue))
+                    };
+                    let style = flash(Color::Green).unwrap_or(style);
+                    scale.cell(glyph, style, bg, next_present)
+                } else if let Some(idx) = crosses.iter().position(|&p| p == (row, col)) {
+                    let (glyph, color) = if board.is_block(idx, crosses.len()) {
+                        ("■", Color::Gray)
+                    } else if board.is_heavy(row, col) {
+                        ("X", Color::Red)
+                    } else {
+                        ("x", Color::Red)
+                    };
+                    let style = flash(color).unwrap_or(Style::default().fg(color));
+                    scale.cell(glyph, style, bg, next_present)
+                } else if board.powerups[board.to_flat(row, col)] && !play.picked_up((row, col)) {
+                    let style = Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD);
+                    scale.cell("+", style, bg, next_present)
+                } else if board.is_bomb(row, col) {
+                    let style = Style::default()
+                        .fg(Color::LightRed)
+                        .add_modifier(Modifier::BOLD);
+                    scale.cell("*", style, bg, next_present)
+                } else if play.show_debug {
+                    scale.index_cell(board.to_flat(row, col), bg, next_present)
+                } else if let n @ 1.. = heat[board.to_flat(row, col)] {
+                    let bg = Style::default().bg(HEAT_BG[n.min(HEAT_BG.len()) - 1]);
+                    let glyph = HEAT_GLYPHS[n.min(HEAT_GLYPHS.len() - 1)];
+                    scale.cell(glyph, Style::default().fg(Color::White), bg, next_present)
+                } else {
+                    // empty present cell: portal letter, ripple on ice
+                    let glyph = empty_glyph(board, row, col);
+                    let style = Style::default().fg(Color::LightCyan);
+                    scale.cell(glyph, style, bg, next_present)
+                }
+            };
+            for (line, mut spans) in span_lines.iter_mut().zip(cell) {
+                if style == GridStyle::Classic
+                    && let Some(separator) = spans.last_mut()
+                {
+                    *separator = classic_separator(row, col);
+                }
+                line.extend(spans);
+            }
+        }
+        lines.extend(span_lines.into_iter().map(Spans::from));
+
+        // Middle border or bottom - draw horizontal only where both rows have present cell (more aggressive)
+        if style == GridStyle::Classic {
+            lines.push(box_line(row + 1));
+        } else if row + 1 < rows {
+            lines.push(border(&|col| present(row, col) && present(row + 1, col)));
+        } else {
+            lines.push(border(&|col| present(row, col)));
+        }
+    }
+    if play.paused_at.is_some() {
+        // out of focus: the whole board in grey
+        let grey = Style::default().fg(Color::DarkGray);
+        for line in &mut lines {
+            for span in &mut line.0 {
+                span.style = grey;
+            }
+        }
+    }
+    lines
+}
+
+/// Scroll offset (rows, columns) that keeps the player's cell in the middle
+/// of a `view`-sized window onto the rendered `grid`, without scrolling past
+/// its edges. Boards that fit are not scrolled at all.
+fn camera(player: (usize, usize), grid: (u16, u16), view: (u16, u16)) -> (u16, u16) {
+    let axis = |at: usize, full: u16, shown: u16| {
+        (at as u16)
+            .saturating_sub(shown / 2)
+            .min(full.saturating_sub(shown))
+    };
+    // each cell is four columns wide and two rows high, below a border row
+    (
+        axis(2 * player.0 + 1, grid.1, view.1),
+        axis(4 * player.1 + 1, grid.0, view.0),
+    )
+}
+
+/// The whole board at one character per cell, pieces included.
+pub fn minimap_lines(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+) -> Vec<Spans<'static>> {
+    (0..board.rows)
+        .map(|r| {
+            let spans: Vec<Span> = (0..board.cols)
+                .map(|c| {
+                    let (glyph, color) = if circles.get(player_idx) == Some(&(r, c)) {
+                        (crate::config::get().player_style.look().0, Color::Yellow)
+                    } else if circles.contains(&(r, c)) {
+                        ("o", Color::LightBlue)
+                    } else if board.line_crosses(crosses).contains(&(r, c))
+                        && board.has_cell(r as isize, c as isize)
+                    {
+                        (if board.is_heavy(r, c) { "X" } else { "x" }, Color::Red)
+                    } else if crosses.contains(&(r, c)) && board.has_cell(r as isize, c as isize) {
+                        ("■", Color::Gray)
+                    } else if board.is_wall(r, c) {
+                        ("█", WALL_FG)
+                    } else if board.is_bomb(r, c) {
+                        ("*", Color::LightRed)
+                    } else if board.has_cell(r as isize, c as isize) {
+                        ("·", Color::Gray)
+                    } else {
+                        (" ", Color::Gray)
+                    };
+                    Span::styled(glyph, Style::default().fg(color))
+                })
+                .collect();
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+/// Small map of the whole board in the top right corner, for boards too
+/// large to see at once.
+fn render_minimap<B: Backend>(
+    f: &mut Frame<B>,
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+) {
+    let size = f.size();
+    let width = (board.cols as u16 + 2).min(size.width);
+    let height = (board.rows as u16 + 2).min(size.height);
+    let area = layout::right(layout::top(size, height), width);
+    let lines = minimap_lines(board, circles, crosses, player_idx);
+    let para = Paragraph::new(lines)
+        .style(Style::default().bg(Color::Black))
+        .block(Block::default().borders(Borders::ALL).title("map"));
+    f.render_widget(Clear, area);
+    f.render_widget(para, area);
+}
+
+/// Circles and crosses of a position.
+pub type Position = (Vec<(usize, usize)>, Vec<(usize, usize)>);
+
+/// A position saved with `b` and a digit, with the moves that led to it.
+#[derive(Clone)]
+struct Bookmark {
+    position: Position,
+    history: Vec<Position>,
+    removals: Vec<bool>,
+    moves: usize,
+}
+
+/// The optimal number of moves left, worked out on another thread for the
+/// position it was last asked about.
+#[derive(Default)]
+struct Optimal {
+    position: Position,
+    // the search, until it reports
+    rx: Option<mpsc::Receiver<Outcome>>,
+    found: Option<Outcome>,
+}
+
+/// Position and view state shared by the play screens: the pieces, the
+/// win/lose state and the overlays the player can toggle.
+struct Play<'a> {
+    board: &'a Board,
+    player_idx: usize,
+    bound: LowerBound<'a>,
+    circles: Vec<(usize, usize)>,
+    crosses: Vec<(usize, usize)>,
+    won: bool,
+    lost: bool,
+    // move-limit rule: moves allowed for this puzzle, and moves made so far
+    budget: Option<usize>,
+    moves_made: usize,
+    out_of_moves: bool,
+    // no move the player has left changes the board
+    stalemate: bool,
+    // `p` was pressed and the next direction is a pull
+    pull_armed: bool,
+    // `x` was pressed with a power-up in hand and the next direction removes
+    // the cross that way
+    remove_armed: bool,
+    // `b` was pressed and the next digit sets a bookmark
+    bookmark_armed: bool,
+    // moves that would line up three crosses need a second press, and the
+    // key of such a move pressed once already
+    warn_losing: bool,
+    losing_key: Option<KeyCode>,
+    // Easy's assist: such moves are refused outright
+    assist: bool,
+    // with `optimal_distance` or `soft_lock_warning` on, the optimal moves
+    // left; not under the adversarial rule, as the solver does not know the
+    // crosses' replies
+    optimal: Option<Optimal>,
+    // positions to go back to with Shift+1/2/3; none in a race, where there
+    // is no going back
+    bookmarks: Option<[Option<Bookmark>; 3]>,
+    // cells the player crossed in the last dash or repeated move, and when
+    // it ended
+    trail: Option<(Vec<(usize, usize)>, Instant)>,
+    // digits typed so far for repeating the next move
+    count: Option<usize>,
+    // last "blocked" message and when it appeared
+    toast: Option<(&'static str, Instant)>,
+    // when the game was won or lost, for the end-of-game animation
+    ended_at: Option<Instant>,
+    // F12 shows the newest log lines over the board
+    show_log: bool,
+    // what the screen showed last time besides the position itself
+    last_view: (Option<u32>, bool, bool),
+    // F3 shows cell indices and a panel of internal state
+    show_debug: bool,
+    // Tab shows a map of the whole board in the corner
+    show_minimap: bool,
+    // `t` shades empty cells by the cross lines through them
+    show_heat: bool,
+    // screen-reader mode: description of the last move, on the top row
+    announcement: Option<String>,
+    // positions before each move, for taking moves back, and how many may be
+    history: Vec<Position>,
+    // whether each of those moves spent a power-up
+    removals: Vec<bool>,
+    undos: Undos,
+    undos_used: usize,
+    // when the puzzle was started, for timing the solve
+    started_at: Instant,
+    // when the terminal lost focus, until it gets it back; the clock stands
+    // still in between
+    paused_at: Option<Instant>,
+    // the puzzle's starting position, for retrying it
+    start: Position,
+}
+
+impl<'a> Play<'a> {
+    fn new(
+        board: &'a Board,
+        circles: Vec<(usize, usize)>,
+        crosses: Vec<(usize, usize)>,
+        player_idx: usize,
+    ) -> Self {
+        let start = (circles.clone(), crosses.clone());
+        let mut play = Play {
+            board,
+            player_idx,
+            bound: LowerBound::new(board),
+            circles,
+            crosses,
+            won: false,
+            lost: false,
+            budget: None,
+            moves_made: 0,
+            out_of_moves: false,
+            stalemate: false,
+            pull_armed: false,
+            remove_armed: false,
+            bookmark_armed: false,
+            bookmarks: Some(Default::default()),
+            warn_losing: crate::config::get().warn_losing_moves,
+            losing_key: None,
+            assist: false,
+            optimal: {
+                let config = crate::config::get();
+                ((config.optimal_distance || config.soft_lock_warning) && !board.rules.adversarial)
+                    .then(Optimal::default)
+            },
+            trail: None,
+            count: None,
+            toast: None,
+            ended_at: None,
+            show_log: false,
+            last_view: (None, false, false),
+            show_debug: false,
+            show_minimap: false,
+            show_heat: false,
+            announcement: None,
+            history: Vec::new(),
+            removals: Vec::new(),
+            undos: Undos::Unlimited,
+            undos_used: 0,
+            started_at: Instant::now(),
+            paused_at: None,
+            start,
+        };
+        // initial win/lose checks
+        (play.won, play.lost) = play.outcome();
+        play
+    }
+
+    /// Whether the circles and the crosses currently form a line.
+    fn outcome(&self) -> (bool, bool) {
+        let board = self.board;
+        let flat = |pieces: &[(usize, usize)]| -> Vec<usize> {
+            pieces.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+        };
+        (
+            is_win_flat(&flat(&self.circles), board),
+            check_lose_flat(&flat(&self.crosses), board),
+        )
+    }
+
+    /// Whether `u` can take the last move back: there is one, the puzzle is
+    /// not solved and the undo allowance is not used up.
+    fn can_undo(&self) -> bool {
+        !self.won && !self.history.is_empty() && self.undos != Undos::Limit(0)
+    }
+
+    /// Time spent on the puzzle, up to the end of the game.
+    fn elapsed(&self) -> Duration {
+        let end = self
+            .ended_at
+            .or(self.paused_at)
+            .unwrap_or_else(Instant::now);
+        end.duration_since(self.started_at)
+    }
+
+    /// Stop the clock while the terminal is out of focus, and start it again
+    /// where it stopped. Whether anything on screen changed.
+    fn focus(&mut self, focused: bool) -> bool {
+        match self.paused_at {
+            Some(paused_at) if focused => {
+                self.started_at += paused_at.elapsed();
+                self.paused_at = None;
+                true
+            }
+            None if !focused && self.ended_at.is_none() => {
+                self.paused_at = Some(Instant::now());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The undo allowance as shown under the board, if there is one.
+    fn undo_hint(&self) -> Option<String> {
+        match self.undos {
+            Undos::Unlimited => Some("u: undo".to_string()),
+            Undos::Limit(0) => None,
+            Undos::Limit(n) => Some(format!("u: undo ({} left)", n)),
+        }
+    }
+
+    /// Go back to the position before the last move.
+    fn undo(&mut self) {
+        let Some((circles, crosses)) = self.history.pop() else {
+            return;
+        };
+        self.circles = circles;
+        self.crosses = crosses;
+        self.removals.pop();
+        self.moves_made -= 1;
+        self.lost = false;
+        self.out_of_moves = false;
+        self.stalemate = false;
+        self.ended_at = None;
+        self.toast = None;
+        self.trail = None;
+        if let Undos::Limit(n) = &mut self.undos {
+            *n -= 1;
+        }
+        self.undos_used += 1;
+        if crate::config::get().screen_reader {
+            self.announcement = Some("took back the last move".to_string());
+        }
+        log::info!("undid move {}", self.moves_made + 1);
+    }
+
+    /// The puzzle as it started, in the puzzle file format.
+    fn start_file(&self) -> PuzzleFile {
+        let (circles, crosses) = &self.start;
+        PuzzleFile::from_parts(self.board, circles, crosses, self.player_idx, None)
+    }
+
+    /// Continue from the position and clock of a saved game.
+    fn resume(&mut self, save: &Autosave) {
+        (self.circles, self.crosses) = save.position.clone();
+        self.history = save.history.clone();
+        self.removals = save.removals.clone();
+        self.moves_made = save.moves;
+        self.undos = save.undos;
+        self.undos_used = save.undos_used;
+        let spent = Duration::try_from_secs_f64(save.seconds).unwrap_or_default();
+        self.started_at = Instant::now()
+            .checked_sub(spent)
+            .unwrap_or_else(Instant::now);
+        (self.won, self.lost) = self.outcome();
+    }
+
+    /// Keep the position in bookmark `slot`, while the game is on.
+    fn set_bookmark(&mut self, slot: usize) {
+        let Some(bookmarks) = self.bookmarks.as_mut() else {
+            return;
+        };
+        if self.won || self.lost {
+            return;
+        }
+        bookmarks[slot] = Some(Bookmark {
+            position: (self.circles.clone(), self.crosses.clone()),
+            history: self.history.clone(),
+            removals: self.removals.clone(),
+            moves: self.moves_made,
+        });
+        self.toast = Some((BOOKMARK_SET[slot], Instant::now()));
+        log::info!("bookmark {} set at move {}", slot + 1, self.moves_made);
+    }
+
+    /// Go back to bookmark `slot`, which scores like an undo but does not
+    /// use up the allowance.
+    fn restore_bookmark(&mut self, slot: usize) {
+        let Some(bookmarks) = self.bookmarks.as_ref() else {
+            return;
+        };
+        let Some(mark) = bookmarks[slot].clone() else {
+            self.toast = Some(("no bookmark there; b and a digit sets one", Instant::now()));
+            return;
+        };
+        if self.won {
+            return;
+        }
+        (self.circles, self.crosses) = mark.position;
+        self.history = mark.history;
+        self.removals = mark.removals;
+        self.moves_made = mark.moves;
+        self.lost = false;
+        self.out_of_moves = false;
+        self.stalemate = false;
+        self.ended_at = None;
+        self.trail = None;
+        self.undos_used += 1;
+        self.toast = Some((BOOKMARK_BACK[slot], Instant::now()));
+        if crate::config::get().screen_reader {
+            self.announcement = Some(BOOKMARK_BACK[slot].to_string());
+        }
+        log::info!("back at bookmark {}, move {}", slot + 1, self.moves_made);
+    }
+
+    /// The bookmarks set so far, as shown under the board.
+    fn bookmark_hint(&self) -> Option<String> {
+        let set: Vec<String> = self
+            .bookmarks
+            .as_ref()?
+            .iter()
+            .enumerate()
+            .filter(|(_, mark)| mark.is_some())
+            .map(|(i, _)| (i + 1).to_string())
+            .collect();
+        (!set.is_empty()).then(|| format!("bookmarks {}", set.join(" ")))
+    }
+
+    /// Whether the player has stood on the power-up tile at `cell`, now or
+    /// earlier; taking the moves back puts the power-up back.
+    fn picked_up(&self, cell: (usize, usize)) -> bool {
+        self.circles[self.player_idx] == cell
+            || self
+                .history
+                .iter()
+                .any(|(circles, _)| circles[self.player_idx] == cell)
+    }
+
+    /// The tiles picked up and the power-ups spent, as the solver sees them.
+    fn powerups(&self) -> PowerUps {
+        let used = self.removals.iter().filter(|&&r| r).count();
+        let mut powerups = PowerUps { taken: 0, used };
+        for cell in self.board.powerup_cells() {
+            if self.picked_up(cell) {
+                powerups.pick_up(self.board, cell);
+            }
+        }
+        powerups
+    }
+
+    /// Power-ups picked up and not used yet.
+    fn powerups_held(&self) -> usize {
+        self.powerups().held()
+    }
+
+    /// The power-ups in hand as shown under the board.
+    fn powerup_hint(&self) -> Option<String> {
+        match self.powerups_held() {
+            0 => None,
+            n => Some(format!("x: remove a cross ({} left)", n)),
+        }
+    }
+
+    /// Start the puzzle over, keeping the move budget and the toggled views.
+    fn retry(&mut self) {
+        let (circles, crosses) = self.start.clone();
+        let fresh = Play::new(self.board, circles, crosses, self.player_idx);
+        *self = Play {
+            budget: self.budget,
+            undos: self.undos,
+            undos_used: self.undos_used,
+            show_log: self.show_log,
+            show_debug: self.show_debug,
+            show_minimap: self.show_minimap,
+            show_heat: self.show_heat,
+            bookmarks: self.bookmarks.take(),
+            ..fresh
+        };
+        log::info!("retrying the puzzle");
+    }
+
+    /// The cells of a dash or repeated move that ended a moment ago, to
+    /// highlight its path.
+    fn dash_trail(&self) -> &[(usize, usize)] {
+        match &self.trail {
+            Some((trail, at)) if at.elapsed() < DASH_TRAIL => trail,
+            _ => &[],
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) {
+        if let Some(slot) = bookmark_slot(key) {
+            self.restore_bookmark(slot);
+            return;
+        }
+        if std::mem::take(&mut self.bookmark_armed)
+            && let KeyCode::Char(c @ '1'..='3') = key.code
+        {
+            self.set_bookmark(c as usize - '1' as usize);
+            return;
+        }
+        // digits before a move repeat it, as in vim: `5d` moves right five times
+        if let KeyCode::Char(c) = key.code
+            && let Some(digit) = c.to_digit(10)
+            && (digit > 0 || self.count.is_some())
+        {
+            let count = self.count.unwrap_or(0) * 10 + digit as usize;
+            self.count = Some(count.min(MAX_COUNT));
+            return;
+        }
+        let count = self.count.take();
+        match dash_key(self.board, key) {
+            Some(step) => self.repeat(step, count.unwrap_or(usize::MAX), true),
+            None if count.is_some() && key_direction(key.code).is_some() => {
+                self.repeat(key, count.unwrap_or(1), false)
+            }
+            None => {
+                self.step(key);
+            }
+        }
+    }
+
+    /// Repeat the move `key` up to `limit` times, until the player is
+    /// blocked or the game ends. A dash also stops after a push. Every step
+    /// counts as a move of its own.
+    fn repeat(&mut self, key: KeyEvent, limit: usize, dash: bool) {
+        let Some((dr, dc)) = key_direction(key.code) else {
+            return;
+        };
+        let mut trail = vec![self.circles[self.player_idx]];
+        while trail.len() <= limit && !(self.won || self.lost) {
+            // only a dash that can't even start says why
+            if dash
+                && trail.len() > 1
+                && movement::blocked_reason(
+                    &self.circles,
+                    &self.crosses,
+                    self.player_idx,
+                    dr,
+                    dc,
+                    self.board,
+                )
+                .is_some()
+            {
+                break;
+            }
+            let moves = self.moves_made;
+            let pushed = self.step(key);
+            if self.moves_made == moves {
+                break;
+            }
+            let to = self.circles[self.player_idx];
+            // on a wrapping board an open row would go round forever
+            let looped = trail.contains(&to);
+            trail.push(to);
+            if dash && (pushed || looped) {
+                break;
+            }
+        }
+        let steps = trail.len() - 1;
+        if steps > 1 {
+            let what = if dash { "dashed" } else { "repeated" };
+            log::info!("{} {} moves", what, steps);
+            if let Some(text) = self.announcement.as_mut() {
+                *text = format!("{} {} moves; {}", what, steps, text);
+            }
+        }
+        if steps > 0 {
+            self.trail = Some((trail, Instant::now()));
+        }
+    }
+
+    // Input handling: arrows and WASD. movement blocked by walls and other objects.
+    // Returns whether the move pushed a piece.
+    fn step(&mut self, key: KeyEvent) -> bool {
+        if matches!(key.code, KeyCode::Char(c) if c.eq_ignore_ascii_case(&'u')) {
+            if self.can_undo() {
+                self.undo();
+            } else if !self.won && !self.history.is_empty() {
+                self.toast = Some(("no undos left", Instant::now()));
+            }
+            return false;
+        }
+        let board = self.board;
+        let player_idx = self.player_idx;
+        let over = self.won || self.lost;
+        let confirmed = self.losing_key.take() == Some(key.code);
+        let (circles_before, crosses_before) = (self.circles.clone(), self.crosses.clone());
+        let pull = pull_direction(board, self.pull_armed, key.code, key.modifiers);
+        let remove_armed = std::mem::take(&mut self.remove_armed);
+        let removal = key_direction(key.code).filter(|_| remove_armed && !over);
+        let held_before = self.powerups_held();
+        match key.code {
+            _ if removal.is_some() => {
+                if let Some((dr, dc)) = removal
+                    && !movement::remove_cross(
+                        &self.circles,
+                        &mut self.crosses,
+                        player_idx,
+                        dr,
+                        dc,
+                        board,
+                    )
+                {
+                    self.toast = Some(("no light cross there to remove", Instant::now()));
+                    return false;
+                }
+            }
+            _ if pull.is_some() => {
+                self.pull_armed = false;
+                if let Some((dr, dc)) = pull
+                    && !over
+                {
+                    movement::apply_move(
+                        &mut self.circles,
+                        &mut self.crosses,
+                        player_idx,
+                        dr,
+                        dc,
+                        true,
+                        board,
+                    )
+                }
+            }
+            KeyCode::Char('p') if board.rules.pull => self.pull_armed = !self.pull_armed,
+            KeyCode::Char('x') if held_before > 0 && !over => self.remove_armed = !remove_armed,
+            KeyCode::Char('b') if self.bookmarks.is_some() => self.bookmark_armed = true,
+            KeyCode::F(12) => self.show_log = !self.show_log,
+            KeyCode::F(3) => self.show_debug = !self.show_debug,
+            KeyCode::Tab => self.show_minimap = !self.show_minimap,
+            KeyCode::Char('t') => self.show_heat = !self.show_heat,
+            code => {
+                if let Some((dr, dc)) = key_direction(code)
+                    && !over
+                {
+                    movement::apply_move(
+                        &mut self.circles,
+                        &mut self.crosses,
+                        player_idx,
+                        dr,
+                        dc,
+                        false,
+                        board,
+                    )
+                }
+            }
+        }
+        let warned = self.warn_losing && !confirmed;
+        if (self.assist || warned) && self.crosses != crosses_before && self.outcome().1 {
+            // take the move back: for good under the assist, otherwise until
+            // it is pressed again
+            (self.circles, self.crosses) = (circles_before, crosses_before);
+            let warning = if self.assist {
+                sound::play(Cue::Blocked);
+                "assist: that would line up three crosses"
+            } else {
+                self.losing_key = Some(key.code);
+                "this completes a cross line; press again to confirm"
+            };
+            self.toast = Some((warning, Instant::now()));
+            if crate::config::get().screen_reader {
+                self.announcement = Some(warning.to_string());
+            }
+            log::info!("{:?}: would line up three crosses", key.code);
+            return false;
+        }
+        let moved = self.circles != circles_before || self.crosses != crosses_before;
+        let direction = key_direction(key.code);
+        let mut announcement = None;
+        let mut pushed = false;
+        if moved {
+            if let Some((dr, dc)) = removal {
+                let (r, c) = board.wrap(
+                    self.circles[player_idx].0 as isize + dr,
+                    self.circles[player_idx].1 as isize + dc,
+                );
+                announcement = Some(announce::describe_removal((r as usize, c as usize)));
+            } else if let Some(dir) = direction {
+                announcement = Some(announce::describe_move(
+                    player_idx,
+                    dir,
+                    pull.is_some(),
+                    (&circles_before, &crosses_before),
+                    (&self.circles, &self.crosses),
+                ));
+            }
+            self.moves_made += 1;
+            self.toast = None;
+            self.history
+                .push((circles_before.clone(), crosses_before.clone()));
+            self.removals.push(removal.is_some());
+            pushed = self
+                .circles
+                .iter()
+                .zip(&circles_before)
+                .enumerate()
+                .any(|(i, (a, b))| i != player_idx && a != b)
+                || self.crosses != crosses_before;
+            if pushed {
+                sound::play(Cue::Push);
+            }
+            if removal.is_none() && self.crosses.len() < crosses_before.len() {
+                self.toast = Some(("a bomb went off", Instant::now()));
+            }
+            if self.powerups_held() > held_before {
+                let text = "picked up a power-up: x and a direction removes a cross";
+                self.toast = Some((text, Instant::now()));
+                if let Some(a) = announcement.as_mut() {
+                    a.push_str("; ");
+                    a.push_str(text);
+                }
+            }
+            log::info!(
+                "{:?}: {} (move {})",
+                key.code,
+                if pushed { "push" } else { "step" },
+                self.moves_made
+            );
+        } else if !over {
+            let blocked = blocked_toast(
+                board,
+                &self.circles,
+                &self.crosses,
+                player_idx,
+                pull.is_some(),
+                key.code,
+            );
+            if let Some(reason) = blocked {
+                sound::play(Cue::Blocked);
+                log::info!("{:?}: {}", key.code, reason);
+                if let Some((dr, dc)) = direction {
+                    announcement = Some(format!(
+                        "player could not move {}: {}",
+                        announce::direction_name(dr, dc),
+                        reason
+                    ));
                 }
             }
+            self.toast = blocked.map(|t| (t, Instant::now())).or(self.toast);
+        }
+        // the crosses get a turn after every move that changed the board
+        if board.rules.adversarial && moved {
+            let crosses_moved = self.crosses.clone();
+            ai::respond(&self.circles, &mut self.crosses, player_idx, board);
+            if let (Some(text), Some(reply)) = (
+                announcement.as_mut(),
+                announce::describe_reply(&crosses_moved, &self.crosses),
+            ) {
+                text.push_str("; ");
+                text.push_str(&reply);
+            }
+        }
 
-            lines.push(Spans::from(Span::raw("")));
-            lines.push(Spans::from(Span::raw(
-                "Use ↑/↓ or w/s to move, Enter to select, q to quit.",
-            )));
-
-            let para = Paragraph::new(lines)
-                .alignment(Alignment::Center)
-                .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
-
-            f.render_widget(Clear, area);
-            f.render_widget(
-                Block::default().style(Style::default().bg(Color::Black)),
-                area,
-            );
-            f.render_widget(para, area);
-        })?;
-
-        if event::poll(Duration::from_millis(150))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Err("user quit".into()),
-                    KeyCode::Up => {
-                        if selection > 0 {
-                            selection -= 1;
-                        }
-                    }
-                    KeyCode::Down => {
-                        if selection < options.len() - 1 {
-                            selection += 1;
-                        }
-                    }
-                    KeyCode::Char('w') => {
-                        if selection > 0 {
-                            selection -= 1;
-                        }
-                    }
-                    KeyCode::Char('s') => {
-                        if selection < options.len() - 1 {
-                            selection += 1;
-                        }
-                    }
-                    KeyCode::Char('1') => selection = 0,
-                    KeyCode::Char('2') => selection = 1,
-                    KeyCode::Char('3') => selection = 2,
-                    KeyCode::Enter => {
-                        if selection == 0 {
-                            let diff = select_difficulty(terminal)?;
-                            return Ok(StartupMode::Play(diff));
-                        } else if selection == 1 {
-                            return Ok(StartupMode::Create);
-                        } else if selection == 2 {
-                            return Ok(StartupMode::Browse);
-                        }
-                    }
-                    _ => {}
-                }
+        // re-evaluate win/lose state after handling input
+        let (won, lines_lost) = self.outcome();
+        self.won = won;
+        self.out_of_moves = !won && self.budget.is_some_and(|b| self.moves_made >= b);
+        self.stalemate = !won
+            && !lines_lost
+            && movement::legal_moves(
+                &self.circles,
+                &self.crosses,
+                player_idx,
+                self.powerups_held(),
+                board,
+            )
+            .is_empty();
+        self.lost = lines_lost || self.out_of_moves || self.stalemate;
+        if crate::config::get().screen_reader
+            && let Some(mut text) = announcement
+        {
+            if self.won {
+                text.push_str("; circles aligned, puzzle solved");
+            } else if self.out_of_moves {
+                text.push_str("; out of moves, puzzle lost");
+            } else if self.stalemate {
+                text.push_str("; no move changes the board, stalemate");
+            } else if self.lost {
+                text.push_str("; three crosses aligned, puzzle lost");
+            } else {
+                text = announce::with_warnings(text, board, (&self.circles, &self.crosses));
             }
+            self.announcement = Some(text);
+        }
+        if !over && (self.won || self.lost) {
+            outcome_cue(self.won, self.lost);
+            self.ended_at = Some(Instant::now());
+            log::info!(
+                "{} after {} moves",
+                if self.won { "won" } else { "lost" },
+                self.moves_made
+            );
         }
+        pushed
     }
-}
 
-pub fn select_difficulty(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-) -> Result<generator::Difficulty, Box<dyn Error>> {
-    let mut selection: usize = 1; // 0: Easy, 1: Medium, 2: Hard
+    /// Search for the optimal moves left whenever the position changed, and
+    /// pick up what the search found. Whether it just reported.
+    fn poll_optimal(&mut self) -> bool {
+        let powerups = self.powerups();
+        let Some(optimal) = self.optimal.as_mut() else {
+            return false;
+        };
+        if optimal.position.0 != self.circles || optimal.position.1 != self.crosses {
+            optimal.position = (self.circles.clone(), self.crosses.clone());
+            optimal.found = None;
+            let (board, player_idx) = (self.board.clone(), self.player_idx);
+            let (circles, crosses) = optimal.position.clone();
+            let (tx, rx) = mpsc::channel();
+            // an outdated search runs out its budget unheard
+            thread::spawn(move || {
+                let outcome = solver::search(
+                    &board,
+                    &circles,
+                    &crosses,
+                    player_idx,
+                    powerups,
+                    OPTIMAL_MAX_NODES,
+                );
+                let _ = tx.send(outcome);
+            });
+            optimal.rx = Some(rx);
+        }
+        let found = match optimal.rx.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok(outcome)) => outcome,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => Outcome::GaveUp,
+            Some(Err(mpsc::TryRecvError::Empty)) | None => return false,
+        };
+        optimal.rx = None;
+        optimal.found = Some(found);
+        true
+    }
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
-            let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
-            let overlay_h = 7u16;
-            let ox = (size.width.saturating_sub(overlay_w)) / 2;
-            let oy = (size.height.saturating_sub(overlay_h)) / 2;
-            let area = Rect::new(ox, oy, overlay_w, overlay_h);
-
-            let mut lines: Vec<Spans> = Vec::new();
-            lines.push(Spans::from(Span::styled(
-                " Select difficulty ",
-                Style::default().add_modifier(Modifier::BOLD),
-            )));
-            lines.push(Spans::from(Span::raw("")));
+    /// The optimal moves left as shown under the board, while the game is on.
+    fn optimal_hint(&self) -> Option<String> {
+        let optimal = self.optimal.as_ref()?;
+        if self.won || self.lost || !crate::config::get().optimal_distance {
+            return None;
+        }
+        Some(match &optimal.found {
+            None => "optimal remaining: ...".to_string(),
+            Some(Outcome::Solved(path)) => format!("optimal remaining: {}", path.len()),
+            Some(Outcome::GaveUp) => "optimal remaining: unknown".to_string(),
+            // a soft lock: nothing but undo helps now
+            Some(Outcome::Unsolvable) => "optimal remaining: no win from here".to_string(),
+        })
+    }
 
-            for i in 0..3 {
-                let label = match i {
-                    0 => "Easy",
-                    1 => "Medium",
-                    _ => "Hard",
-                };
-                if i == selection {
-                    lines.push(Spans::from(Span::styled(
-                        format!("> {}", label),
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
-                    )));
-                } else {
-                    lines.push(Spans::from(Span::raw(format!("  {}", label))));
-                }
-            }
+    /// Whether `soft_lock_warning` is on and the search proved the puzzle
+    /// can no longer be won from here, although it is not lost yet.
+    fn soft_locked(&self) -> bool {
+        crate::config::get().soft_lock_warning
+            && !self.won
+            && !self.lost
+            && self
+                .optimal
+                .as_ref()
+                .is_some_and(|o| matches!(o.found, Some(Outcome::Unsolvable)))
+    }
 
-            lines.push(Spans::from(Span::raw("")));
-            lines.push(Spans::from(Span::raw(
-                "Use ↑/↓ or w/s to move, Enter to select, q to quit.",
-            )));
+    /// Whether the screen needs a redraw although no key was pressed.
+    fn tick(&mut self) -> bool {
+        // animation frames, expiring toasts and trails and new log lines need
+        // redraws too
+        let view = (
+            animation_frame(self.ended_at),
+            toast_shown(self.toast),
+            !self.dash_trail().is_empty(),
+        );
+        let found = self.poll_optimal();
+        let changed = view != self.last_view || self.show_log || found;
+        self.last_view = view;
+        changed
+    }
+}
+
+// Characters of a player's name shown in the daily top times
+const TOP_TIME_NAME_LEN: usize = 12;
+
+/// A generated puzzle being played while the next one is generated in the
+/// background.
+struct PlayScreen<'a> {
+    play: Play<'a>,
+    puzzle: &'a generator::GeneratedPuzzle,
+    setup: generator::Setup,
+    next: &'a mpsc::Receiver<generator::GeneratedPuzzle>,
+    // set once "new puzzle" was requested but the background worker is not done yet
+    waiting_for_next: bool,
+    // new pieces for this board, being placed after Shift+N
+    reroll: Option<mpsc::Receiver<generator::GeneratedPuzzle>>,
+    // the game's score (none when lost) and the saved stats, once recorded
+    result: Option<(Option<u32>, Stats)>,
+    // seed the background worker is generating
+    next_seed: u64,
+    // the day of a daily puzzle, and its top times while being fetched and
+    // once they are back
+    daily: Option<u64>,
+    top_times_rx: Option<mpsc::Receiver<TopTimes>>,
+    top_times: Option<TopTimes>,
+    // whether the daily result went out, or was found sent before; solves
+    // after a retry are replays and are not sent
+    submitted: bool,
+}
 
-            let para = Paragraph::new(lines)
-                .alignment(Alignment::Center)
-                .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+impl PlayScreen<'_> {
+    /// Lines under the end-of-game overlay's headline: the score, then the
+    /// keys it offers.
+    fn end_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        if let Some((Some(score), stats)) = &self.result {
+            keys.push(format!("score {}  -  total {}", score, stats.total_score));
+        }
+        if self.top_times_rx.is_some() {
+            keys.push("fetching today's top times...".to_string());
+        }
+        match &self.top_times {
+            Some(Ok(entries)) => {
+                keys.push("today's top times:".to_string());
+                keys.extend(entries.iter().enumerate().map(|(i, e)| {
+                    let name: String = e.name.chars().take(TOP_TIME_NAME_LEN).collect();
+                    format!(
+                        "{}. {}  {}  {} moves",
+                        i + 1,
+                        name,
+                        leaderboard::format_time(e.seconds),
+                        e.moves
+                    )
+                }));
+            }
+            Some(Err(_)) => keys.push("top times unavailable".to_string()),
+            _ => {}
+        }
+        if self.play.can_undo() {
+            keys.push("u: undo that move".to_string());
+        }
+        keys.push("n: new puzzle   N: new pieces".to_string());
+        keys.push("r: retry   m: menu   q: quit".to_string());
+        keys
+    }
 
-            f.render_widget(Clear, area);
-            f.render_widget(
-                Block::default().style(Style::default().bg(Color::Black)),
-                area,
+    /// Score a solve and add it to the stats as soon as it happens.
+    fn record_win(&mut self) {
+        let play = &self.play;
+        if play.won && self.result.is_none() {
+            let score = score::score(
+                self.setup,
+                self.puzzle.par,
+                play.moves_made,
+                play.elapsed(),
+                play.undos_used,
             );
-            f.render_widget(para, area);
-        })?;
-
-        if event::poll(Duration::from_millis(150))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') | KeyCode::Esc => return Err("user quit".into()),
-                    KeyCode::Up => {
-                        if selection > 0 {
-                            selection -= 1;
-                        }
-                    }
-                    KeyCode::Down => {
-                        if selection < 2 {
-                            selection += 1;
-                        }
-                    }
-                    KeyCode::Char('w') => {
-                        if selection > 0 {
-                            selection -= 1;
-                        }
-                    }
-                    KeyCode::Char('s') => {
-                        if selection < 2 {
-                            selection += 1;
-                        }
-                    }
-                    KeyCode::Char('1') => selection = 0,
-                    KeyCode::Char('2') => selection = 1,
-                    KeyCode::Char('3') => selection = 2,
-                    KeyCode::Enter => break,
-                    _ => {}
-                }
+            log::info!("score {}", score);
+            self.result = Some((Some(score), Stats::record(Some(score))));
+            self.journal(Some(score));
+            let online = &crate::config::get().online;
+            if let Some(day) = self.daily
+                && online.enabled
+                && !self.submitted
+            {
+                // a daily solved again, e.g. from the autosave, only fetches
+                // the top times
+                let result = score::first_daily_result(day).then(|| Submission {
+                    name: online.name.clone(),
+                    seed: self.puzzle.seed,
+                    hash: play.start_file().canonical_hash(),
+                    moves: play.moves_made,
+                    seconds: play.elapsed().as_secs_f64(),
+                    score,
+                });
+                self.top_times_rx = Some(leaderboard::spawn_submit(online, day, result));
+                self.submitted = true;
             }
         }
     }
 
-    match selection {
-        0 => Ok(generator::Difficulty::Easy),
-        1 => Ok(generator::Difficulty::Medium),
-        _ => Ok(generator::Difficulty::Hard),
+    /// The seed the board came from, which rerolls keep.
+    fn board_seed(&self) -> u64 {
+        self.puzzle.board_seed.unwrap_or(self.puzzle.seed)
     }
-}
 
-pub fn run_app(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    difficulty: generator::Difficulty,
-) -> Result<(), Box<dyn Error>> {
-    // Create board and helpers
-    let board = Board::random();
-    let rows = board.rows;
-    let cols = board.cols;
-    let row_widths = &board.row_widths;
-    let to_flat = |r: usize, c: usize| board.to_flat(r, c);
-    let from_flat = |idx: usize| board.from_flat(idx);
-    let default_grid_w = board.default_grid_w;
-    let default_grid_h = board.default_grid_h;
-
-    // Generate puzzle
-    let (mut circles_flat, mut crosses_flat, mut player_idx) =
-        generator::generate_puzzle_constructive(&board, difficulty);
-
-    // fallback deterministic layout if generation failed
-    if circles_flat.is_empty() {
-        let center_row = rows / 2;
-        let c2 = std::cmp::min(2, row_widths[center_row].saturating_sub(1));
-        let c3 = std::cmp::min(3, row_widths[center_row].saturating_sub(1));
-        let c4 = std::cmp::min(4, row_widths[center_row].saturating_sub(1));
-        circles_flat = vec![
-            to_flat(center_row, c2),
-            to_flat(center_row, c3),
-            to_flat(center_row, c4),
-        ];
-        player_idx = 1;
-        crosses_flat = Vec::new();
-        'outer: for r in 0..rows {
-            for c in 0..row_widths[r] {
-                let f = to_flat(r, c);
-                if circles_flat.contains(&f) {
-                    continue;
-                }
-                crosses_flat.push(f);
-                if crosses_flat.len() >= 5 {
-                    break 'outer;
-                }
+    /// Copy the puzzle's seed, so it can be shared, and say where it went.
+    fn copy_seed(&mut self) {
+        let message = match clipboard::copy(&self.puzzle.seed.to_string()) {
+            Ok(clipboard::Copied::Clipboard) => "seed copied",
+            Ok(clipboard::Copied::Terminal) => "seed sent to the terminal's clipboard",
+            Err(e) => {
+                log::warn!("could not copy the seed: {}", e);
+                "could not copy the seed"
             }
+        };
+        self.play.toast = Some((message, Instant::now()));
+    }
+
+    /// Add a lost game to the stats once the player moves on from it; until
+    /// then the losing move can still be taken back.
+    fn record_loss(&mut self) {
+        if self.play.lost && self.result.is_none() {
+            self.result = Some((None, Stats::record(None)));
+            self.journal(None);
+            autosave::delete();
         }
     }
 
-    // convert flat positions to (r,c)
-    let mut circles: Vec<(usize, usize)> = circles_flat.iter().map(|&f| from_flat(f)).collect();
-    let mut crosses: Vec<(usize, usize)> = crosses_flat.iter().map(|&f| from_flat(f)).collect();
+    /// Add the finished game to the journal.
+    fn journal(&self, score: Option<u32>) {
+        let play = &self.play;
+        let mut positions = play.history.clone();
+        positions.push((play.circles.clone(), play.crosses.clone()));
+        journal::append(&Entry {
+            finished_at: journal::now(),
+            setup: self.setup,
+            daily: self.daily,
+            seed: self.puzzle.seed,
+            par: self.puzzle.par,
+            won: play.won,
+            moves: play.moves_made,
+            seconds: play.elapsed().as_secs_f64(),
+            score,
+            puzzle: play.start_file(),
+            positions,
+        });
+    }
 
-    // initial win/lose checks
-    let mut circles_flat_now: Vec<usize> = circles.iter().map(|&(r, c)| to_flat(r, c)).collect();
-    let mut crosses_flat_now: Vec<usize> = crosses.iter().map(|&(r, c)| to_flat(r, c)).collect();
-    let mut won = is_win_flat(&circles_flat_now, &board);
-    let mut lost = check_lose_flat(&crosses_flat_now, &board);
+    /// Keep the game on disk while it is in progress. A lost game keeps the
+    /// position before the losing move until the loss is recorded.
+    fn autosave(&self) {
+        let play = &self.play;
+        if play.lost {
+            return;
+        }
+        if play.won || play.moves_made == 0 {
+            autosave::delete();
+            return;
+        }
+        let save = Autosave {
+            setup: self.setup,
+            seed: self.puzzle.seed,
+            par: self.puzzle.par,
+            daily: self.daily,
+            puzzle: play.start_file(),
+            position: (play.circles.clone(), play.crosses.clone()),
+            history: play.history.clone(),
+            removals: play.removals.clone(),
+            moves: play.moves_made,
+            undos: play.undos,
+            undos_used: play.undos_used,
+            seconds: play.elapsed().as_secs_f64(),
+        };
+        if let Err(e) = save.save() {
+            log::warn!("could not save {}: {}", autosave::AUTOSAVE_FILE, e);
+        }
+    }
+}
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
+impl Screen for PlayScreen<'_> {
+    type Output = PlayOutcome;
 
-            // ensure grid fits terminal
-            let grid_w = if default_grid_w + 2 > size.width {
-                size.width.saturating_sub(2)
-            } else {
-                default_grid_w
+    fn poll(&mut self) -> Option<AppEvent> {
+        if let Some(rx) = &self.top_times_rx {
+            let top = match rx.try_recv() {
+                Ok(top) => top,
+                Err(mpsc::TryRecvError::Disconnected) => Err("submission failed".to_string()),
+                Err(mpsc::TryRecvError::Empty) => return None,
             };
-            let grid_h = if default_grid_h + 2 > size.height {
-                size.height.saturating_sub(2)
-            } else {
-                default_grid_h
+            self.top_times_rx = None;
+            return Some(AppEvent::TopTimes(top));
+        }
+        if let Some(rx) = &self.reroll {
+            let p = match rx.try_recv() {
+                Ok(p) => p,
+                // worker died; reroll on this thread instead
+                Err(mpsc::TryRecvError::Disconnected) => generator::reroll(
+                    thread_rng().r#gen(),
+                    self.puzzle.board.clone(),
+                    self.board_seed(),
+                    self.setup,
+                ),
+                Err(mpsc::TryRecvError::Empty) => return None,
             };
+            self.reroll = None;
+            if p.circles.is_empty() {
+                // the next tick redraws for the toast
+                self.play.toast = Some(("no other puzzle fits this board", Instant::now()));
+                return None;
+            }
+            return Some(AppEvent::GenerationDone(Box::new(p)));
+        }
+        if !self.waiting_for_next {
+            return None;
+        }
+        match self.next.try_recv() {
+            Ok(p) => Some(AppEvent::GenerationDone(Box::new(p))),
+            // worker died; generate the same seed on this thread instead
+            Err(mpsc::TryRecvError::Disconnected) => {
+                let rules = self.puzzle.board.rules;
+                let p = puzzle_for(self.next_seed, self.setup, rules);
+                Some(AppEvent::GenerationDone(Box::new(p)))
+            }
+            Err(mpsc::TryRecvError::Empty) => None,
+        }
+    }
 
-            let x = (size.width.saturating_sub(grid_w)) / 2;
-            let y = (size.height.saturating_sub(grid_h)) / 2;
-            let area = Rect::new(x, y, grid_w, grid_h);
-
-            let mut lines: Vec<Spans> = Vec::new();
-
-            // Top border (aggressive removal): horizontal dashes only where top cell exists
-            let mut top = String::new();
-            if rows > 0 {
-                for col in 0..cols {
-                    let present = col < row_widths[0] && board.is_cell_present(0, col);
-                    if present {
-                        top.push_str("─── ");
-                    } else {
-                        top.push_str("    ");
-                    }
+    fn update(&mut self, event: AppEvent) -> Step<PlayOutcome> {
+        let over = self.play.won || self.play.lost;
+        if let AppEvent::KeyPressed(key) = &event {
+            let pressed =
+                |k: char| matches!(key.code, KeyCode::Char(c) if c.eq_ignore_ascii_case(&k));
+            if key.code == KeyCode::Esc
+                || pressed('q')
+                || pressed('n')
+                || (over && (pressed('r') || pressed('m')))
+            {
+                self.record_loss();
+            }
+        }
+        match event {
+            AppEvent::KeyPressed(key) => match key.code {
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&'q') => {
+                    self.autosave();
+                    Step::Done(PlayOutcome::Quit)
                 }
-            } else {
-                for _ in 0..cols {
-                    top.push_str("    ");
+                KeyCode::Esc => {
+                    self.autosave();
+                    Step::Done(PlayOutcome::Quit)
                 }
-            }
-            lines.push(Spans::from(Span::raw(top)));
-
-            for row in 0..rows {
-                // Content line: draw only internal vertical separators between adjacent present cells
-                let mut span_line: Vec<Span> = Vec::new();
-                for col in 0..cols {
-                    let present = col < row_widths[row] && board.is_cell_present(row, col);
-                    if !present {
-                        // missing cell: reserve full cell width
-                        span_line.push(Span::raw("    "));
-                        continue;
+                // Shift+N: the same board with new pieces
+                KeyCode::Char('N') if !self.waiting_for_next => {
+                    autosave::delete();
+                    if self.reroll.is_none() {
+                        self.reroll = Some(spawn_reroll(
+                            &self.puzzle.board,
+                            self.board_seed(),
+                            self.setup,
+                        ));
                     }
-                    let next_present =
-                        (col + 1) < row_widths[row] && board.is_cell_present(row, col + 1);
-
-                    if let Some(idx) = circles.iter().position(|&(rr, cc)| rr == row && cc == col) {
-                        let is_player = idx == player_idx;
-                        let symbol = "o";
-                        let style = if is_player {
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::BOLD)
-                        } else {
-                            Style::default().fg(Color::LightBlue)
-                        };
-                        span_line.push(Span::raw(" "));
-                        span_line.push(Span::styled(symbol.to_string(), style));
-                        span_line.push(Span::raw(if next_present { " │" } else { "  " }));
-                        continue;
-                    }
-                    if let Some(_) = crosses.iter().position(|&(rr, cc)| rr == row && cc == col) {
-                        let style = Style::default().fg(Color::Red);
-                        span_line.push(Span::raw(" "));
-                        span_line.push(Span::styled("x".to_string(), style));
-                        span_line.push(Span::raw(if next_present { " │" } else { "  " }));
-                        continue;
-                    }
-
-                    // empty present cell
-                    span_line.push(Span::raw(if next_present { "   │" } else { "    " }));
+                    Step::Redraw
                 }
-                lines.push(Spans::from(span_line));
-
-                // Middle border or bottom - draw horizontal only where both rows have present cell (more aggressive)
-                if row != rows - 1 {
-                    let mut mid = String::new();
-                    for col in 0..cols {
-                        let top_here = col < row_widths[row] && board.is_cell_present(row, col);
-                        let bottom_here =
-                            col < row_widths[row + 1] && board.is_cell_present(row + 1, col);
-                        if top_here && bottom_here {
-                            mid.push_str("─── ");
-                        } else {
-                            mid.push_str("    ");
-                        }
-                    }
-                    lines.push(Spans::from(Span::raw(mid)));
-                } else {
-                    let mut bot = String::new();
-                    for col in 0..cols {
-                        let bot_seg = col < row_widths[row] && board.is_cell_present(row, col);
-                        if bot_seg {
-                            bot.push_str("─── ");
-                        } else {
-                            bot.push_str("    ");
-                        }
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&'n') => {
+                    // a puzzle left for a new one is not resumed
+                    autosave::delete();
+                    self.waiting_for_next = true;
+                    Step::Redraw
+                }
+                // the end-of-game overlay's other choices; `r` also restarts
+                // a puzzle that can no longer be won
+                KeyCode::Char(c)
+                    if (over || self.play.soft_locked()) && c.eq_ignore_ascii_case(&'r') =>
+                {
+                    // the day's top times stay for the next end of game
+                    self.play.retry();
+                    self.result = None;
+                    Step::Redraw
+                }
+                KeyCode::Char(c) if over && c.eq_ignore_ascii_case(&'m') => {
+                    Step::Done(PlayOutcome::Menu)
+                }
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&'c') => {
+                    self.copy_seed();
+                    Step::Redraw
+                }
+                _ => {
+                    let moves = self.play.moves_made;
+                    self.play.key(key);
+                    self.record_win();
+                    if self.play.moves_made != moves {
+                        self.autosave();
                     }
-                    lines.push(Spans::from(Span::raw(bot)));
+                    Step::Redraw
                 }
+            },
+            AppEvent::GenerationDone(p) => Step::Done(PlayOutcome::NewPuzzle(p)),
+            AppEvent::TopTimes(top) => {
+                self.top_times = Some(top);
+                Step::Redraw
             }
+            AppEvent::Resize => Step::Redraw,
+            AppEvent::Focus(focused) if self.play.focus(focused) => Step::Redraw,
+            AppEvent::Focus(_) => Step::Idle,
+            AppEvent::Tick if self.play.tick() => Step::Redraw,
+            AppEvent::Tick => Step::Idle,
+        }
+    }
 
-            let paragraph = Paragraph::new(lines).block(Block::default());
-            f.render_widget(paragraph, area);
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let play = &self.play;
+        let puzzle = self.puzzle;
+        let setup = self.setup;
+        let board = &puzzle.board;
+        let rows = board.rows;
+        let cols = board.cols;
+        let player_idx = puzzle.player_idx;
+        let (circles, crosses) = (&play.circles, &play.crosses);
+        let lost = play.lost;
+
+        let debug = play.show_debug.then(|| {
+            let mut lines = debug_lines(board, circles, crosses, player_idx, &play.bound);
+            lines.push(format!(
+                "seed {}, {}, par {}",
+                puzzle.seed,
+                setup.label(),
+                puzzle.par.map_or("unknown".to_string(), |p| p.to_string())
+            ));
+            lines
+        });
+        let frame = animation_frame(play.ended_at);
+
+        let size = f.size();
+        let scale = CellScale::fit(board, size, (2, 3));
+        let (full_w, full_h) = scale.grid_size(board);
+        let areas = layout::play(size, (full_w, full_h), true);
+        let mut area = areas.board;
+        if lost {
+            area.x = shake(area.x, frame);
+        }
 
-            // Render difficulty centered under the board
-            let diff_label = match difficulty {
-                generator::Difficulty::Easy => "Easy",
-                generator::Difficulty::Medium => "Medium",
-                generator::Difficulty::Hard => "Hard",
-            };
-            let diff_text = format!("Difficulty: {}", diff_label);
-            let diff_lines = vec![Spans::from(Span::styled(
-                diff_text,
-                Style::default().fg(Color::White),
-            ))];
-            let diff_y = y.saturating_add(grid_h);
-            if diff_y < size.height {
-                let diff_area = Rect::new(x, diff_y, grid_w, 1);
-                let diff_para = Paragraph::new(diff_lines).alignment(Alignment::Center);
-                f.render_widget(diff_para, diff_area);
-            }
-
-            // If won, render an overlay message centered on screen
-            if won {
-                let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
-                let overlay_h = 5u16;
-                let ox = (size.width.saturating_sub(overlay_w)) / 2;
-                let oy = (size.height.saturating_sub(overlay_h)) / 2;
-                let o_area = Rect::new(ox, oy, overlay_w, overlay_h);
-
-                let mut msg_lines: Vec<Spans> = Vec::new();
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    " YOU WON! ",
-                    Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                )));
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    "press q to quit",
-                    Style::default().fg(Color::White).bg(Color::Black),
-                )));
+        let lines = grid_lines(play, frame, scale);
+
+        // large boards scroll to keep the player in view
+        let scroll = camera(
+            circles[player_idx],
+            (full_w, full_h),
+            (area.width, area.height),
+        );
+        let paragraph = Paragraph::new(lines).block(Block::default()).scroll(scroll);
+        f.render_widget(paragraph, area);
+        if play.show_minimap {
+            render_minimap(f, board, circles, crosses, player_idx);
+        }
 
-                let overlay = Paragraph::new(msg_lines)
-                    .alignment(Alignment::Center)
-                    .style(Style::default().bg(Color::Black))
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title("Victory")
-                            .style(Style::default().bg(Color::Black)),
-                    );
-                f.render_widget(Clear, o_area);
-                f.render_widget(
-                    Block::default().style(Style::default().bg(Color::Black)),
-                    o_area,
-                );
-                f.render_widget(overlay, o_area);
-            }
+        // Render difficulty centered under the board
+        let mut diff_label = match self.daily {
+            Some(_) => format!("Daily puzzle ({})", setup.label()),
+            None => setup.label(),
+        };
+        for name in board.rules.names() {
+            diff_label.push_str(&format!(" + {}", name));
+        }
+        let mut diff_text = if play.pull_armed {
+            format!("Difficulty: {}  -  pull: pick a direction", diff_label)
+        } else if play.remove_armed {
+            format!(
+                "Difficulty: {}  -  remove a cross: pick a direction",
+                diff_label
+            )
+        } else if play.bookmark_armed {
+            format!("Difficulty: {}  -  bookmark: press 1, 2 or 3", diff_label)
+        } else if let Some(n) = play.count {
+            format!(
+                "Difficulty: {}  -  {} times: pick a direction",
+                diff_label, n
+            )
+        } else if self.waiting_for_next {
+            format!("Difficulty: {}  -  generating next puzzle...", diff_label)
+        } else if self.reroll.is_some() {
+            format!("Difficulty: {}  -  placing new pieces...", diff_label)
+        } else {
+            format!(
+                "Difficulty: {}  -  n: new puzzle  -  N: new pieces",
+                diff_label
+            )
+        };
+        if let Some(b) = play.budget {
+            diff_text.push_str(&format!(
+                "  -  moves left: {}",
+                b.saturating_sub(play.moves_made)
+            ));
+        }
+        if let Some(undo) = play.undo_hint() {
+            diff_text.push_str(&format!("  -  {}", undo));
+        }
+        if let Some(bookmarks) = play.bookmark_hint() {
+            diff_text.push_str(&format!("  -  {}", bookmarks));
+        }
+        if let Some(powerups) = play.powerup_hint() {
+            diff_text.push_str(&format!("  -  {}", powerups));
+        }
+        if play.assist {
+            diff_text.push_str("  -  assist on");
+        }
+        if let Some(optimal) = play.optimal_hint() {
+            diff_text.push_str(&format!("  -  {}", optimal));
+        }
+        let diff_lines = vec![Spans::from(Span::styled(
+            diff_text,
+            Style::default().fg(Color::White),
+        ))];
+        // full width so long labels are not clipped on narrow boards
+        let diff_para = Paragraph::new(diff_lines).alignment(Alignment::Center);
+        f.render_widget(diff_para, areas.hud);
+
+        render_soft_lock(f, play, areas.toast);
+        render_toast(f, play.toast, areas.toast);
+        render_paused(f, play, areas.toast);
+        render_announcement(f, play.announcement.as_deref());
+
+        // Status bar on the bottom row: everything needed to reproduce this
+        // puzzle, so it ends up in screenshots and bug reports
+        let mut status = format!(
+            " {}  |  seed {} (c: copy)  |  {}x{}  |  moves {}",
+            diff_label, puzzle.seed, rows, cols, play.moves_made
+        );
+        // the setup gave no puzzle for this seed and had to be relaxed
+        if let Some(relaxed) = puzzle.relaxed {
+            status.push_str(&format!("  |  relaxed: {}", relaxed));
+        }
+        // the pieces were rerolled on the board of another seed
+        if let Some(board_seed) = puzzle.board_seed {
+            status.push_str(&format!("  |  board of seed {}", board_seed));
+        }
+        let status_bar = Paragraph::new(Spans::from(Span::raw(status)))
+            .style(Style::default().fg(Color::Black).bg(Color::Gray));
+        f.render_widget(status_bar, areas.status);
 
-            // If lost, render an overlay message centered on screen
-            if lost {
-                let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
-                let overlay_h = 5u16;
-                let ox = (size.width.saturating_sub(overlay_w)) / 2;
-                let oy = (size.height.saturating_sub(overlay_h)) / 2;
-                let o_area = Rect::new(ox, oy, overlay_w, overlay_h);
+        if frame.is_none() {
+            render_end_overlay(f, play, &self.end_keys());
+        }
 
-                let mut msg_lines: Vec<Spans> = Vec::new();
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    " YOU LOST! three crosses aligned ",
-                    Style::default()
-                        .fg(Color::White)
-                        .bg(Color::Red)
-                        .add_modifier(Modifier::BOLD),
-                )));
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    "press q to quit",
-                    Style::default().fg(Color::White).bg(Color::Black),
-                )));
+        if let Some(lines) = &debug {
+            render_debug(f, lines);
+        }
+        if play.show_log {
+            render_log(f);
+        }
+    }
+}
 
-                let overlay = Paragraph::new(msg_lines)
-                    .alignment(Alignment::Center)
-                    .style(Style::default().bg(Color::Black))
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title("Defeat")
-                            .style(Style::default().bg(Color::Black)),
-                    );
-                f.render_widget(Clear, o_area);
-                f.render_widget(
-                    Block::default().style(Style::default().bg(Color::Black)),
-                    o_area,
-                );
-                f.render_widget(overlay, o_area);
-            }
-        })?;
-
-        // Input handling: arrows and WASD. movement blocked by walls and other objects
-        if event::poll(Duration::from_millis(150))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char(c) => match c.to_ascii_lowercase() {
-                        'q' => break,
-                        'w' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    -1,
-                                    0,
-                                    &board,
-                                )
-                            }
-                        }
-                        'a' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    0,
-                                    -1,
-                                    &board,
-                                )
-                            }
-                        }
-                        's' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    1,
-                                    0,
-                                    &board,
-                                )
-                            }
-                        }
-                        'd' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    0,
-                                    1,
-                                    &board,
-                                )
-                            }
-                        }
-                        _ => {}
-                    },
-                    KeyCode::Up => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                -1,
-                                0,
-                                &board,
-                            )
-                        }
-                    }
-                    KeyCode::Left => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                0,
-                                -1,
-                                &board,
-                            )
-                        }
-                    }
-                    KeyCode::Down => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                1,
-                                0,
-                                &board,
-                            )
-                        }
+fn play_generated(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    puzzle: &generator::GeneratedPuzzle,
+    setup: generator::Setup,
+    next_seed: u64,
+    next: &mpsc::Receiver<generator::GeneratedPuzzle>,
+    daily: Option<u64>,
+    saved: Option<&Autosave>,
+) -> Result<PlayOutcome, Box<dyn Error>> {
+    if puzzle.circles.is_empty() {
+        return Err(format!("could not generate a puzzle from seed {}", puzzle.seed).into());
+    }
+    let board = &puzzle.board;
+    // convert flat positions to (r,c)
+    let circles = puzzle.circles.iter().map(|&f| board.from_flat(f)).collect();
+    let crosses = puzzle.crosses.iter().map(|&f| board.from_flat(f)).collect();
+    let mut play = Play::new(board, circles, crosses, puzzle.player_idx);
+    play.budget = puzzle
+        .par
+        .filter(|_| board.rules.move_limit)
+        .map(move_budget);
+    play.undos = setup.params().undos;
+    play.assist =
+        crate::config::get().assist && setup == generator::Setup::Tier(generator::Difficulty::Easy);
+    if let Some(save) = saved {
+        play.resume(save);
+    }
+    let mut screen = PlayScreen {
+        play,
+        puzzle,
+        setup,
+        next,
+        waiting_for_next: false,
+        reroll: None,
+        result: None,
+        next_seed,
+        daily,
+        top_times_rx: None,
+        top_times: None,
+        submitted: false,
+    };
+    app::run(terminal, &mut screen)
+}
+
+/// A puzzle from a file, or the shared puzzle of a race.
+struct PuzzleScreen<'a, 'r> {
+    play: Play<'a>,
+    race: Option<&'r mut race::Link>,
+    // in a race: the opponent's latest report
+    opponent: Option<race::Progress>,
+    // won at some point, even if retried since
+    solved: bool,
+}
+
+impl PuzzleScreen<'_, '_> {
+    /// Keys offered by the end-of-game overlay; a race can only be left.
+    fn end_keys(&self) -> Vec<String> {
+        if self.race.is_some() {
+            return vec!["q: quit".to_string()];
+        }
+        let mut keys = Vec::new();
+        if self.play.can_undo() {
+            keys.push("u: undo that move".to_string());
+        }
+        keys.push("r: retry   m: menu".to_string());
+        keys.push("q: quit".to_string());
+        keys
+    }
+}
+
+impl Screen for PuzzleScreen<'_, '_> {
+    type Output = GameExit;
+
+    fn update(&mut self, event: AppEvent) -> Step<GameExit> {
+        self.solved |= self.play.won;
+        match event {
+            AppEvent::KeyPressed(key) => {
+                let pressed =
+                    |k: char| matches!(key.code, KeyCode::Char(c) if c.eq_ignore_ascii_case(&k));
+                if matches!(key.code, KeyCode::Esc) || pressed('q') {
+                    return Step::Done(GameExit::Quit);
+                }
+                let was_over = self.play.won || self.play.lost;
+                // a race is played once, from the start both players got
+                if self.race.is_none() {
+                    if pressed('r') && (was_over || self.play.soft_locked()) {
+                        self.play.retry();
+                        return Step::Redraw;
+                    } else if pressed('m') && was_over {
+                        return Step::Done(GameExit::Menu);
                     }
-                    KeyCode::Right => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                0,
-                                1,
-                                &board,
-                            )
-                        }
+                }
+                self.play.key(key);
+                let play = &self.play;
+                if let Some(link) = self.race.as_mut()
+                    && !was_over
+                {
+                    if play.won {
+                        link.send(race::Progress::Won(play.moves_made));
+                    } else if play.lost {
+                        link.send(race::Progress::Lost(play.moves_made));
+                    } else if play.moves_made > 0 {
+                        link.send(race::Progress::Moves(play.moves_made));
                     }
-                    KeyCode::Esc => break,
-                    _ => {}
                 }
+                Step::Redraw
+            }
+            AppEvent::Tick => {
+                let mut changed = self.play.tick();
+                // a finished opponent quitting afterwards still counts as finished
+                if let Some(p) = self.race.as_ref().and_then(|link| link.poll())
+                    && !(p == race::Progress::Left
+                        && matches!(
+                            self.opponent,
+                            Some(race::Progress::Won(_) | race::Progress::Lost(_))
+                        ))
+                {
+                    self.opponent = Some(p);
+                    changed = true;
+                }
+                if changed { Step::Redraw } else { Step::Idle }
+            }
+            AppEvent::Resize => Step::Redraw,
+            // a race's clock is the opponent; it never stops
+            AppEvent::Focus(focused) if self.race.is_none() && self.play.focus(focused) => {
+                Step::Redraw
             }
-            // re-evaluate win/lose state after handling input
-            circles_flat_now = circles.iter().map(|&(r, c)| to_flat(r, c)).collect();
-            crosses_flat_now = crosses.iter().map(|&(r, c)| to_flat(r, c)).collect();
-            won = is_win_flat(&circles_flat_now, &board);
-            lost = check_lose_flat(&crosses_flat_now, &board);
+            AppEvent::Focus(_) | AppEvent::GenerationDone(_) | AppEvent::TopTimes(_) => Step::Idle,
         }
     }
 
-    Ok(())
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let play = &self.play;
+        let board = play.board;
+        let player_idx = play.player_idx;
+        let (circles, crosses) = (&play.circles, &play.crosses);
+        let lost = play.lost;
+
+        let debug = play
+            .show_debug
+            .then(|| debug_lines(board, circles, crosses, player_idx, &play.bound));
+        let frame = animation_frame(play.ended_at);
+
+        let size = f.size();
+        let scale = CellScale::fit(board, size, (2, 2));
+        let (full_w, full_h) = scale.grid_size(board);
+        let areas = layout::play(size, (full_w, full_h), false);
+        let mut area = areas.board;
+        if lost {
+            area.x = shake(area.x, frame);
+        }
+
+        let lines = grid_lines(play, frame, scale);
+
+        // large boards scroll to keep the player in view
+        let scroll = camera(
+            circles[player_idx],
+            (full_w, full_h),
+            (area.width, area.height),
+        );
+        let paragraph = Paragraph::new(lines).block(Block::default()).scroll(scroll);
+        f.render_widget(paragraph, area);
+        if play.show_minimap {
+            render_minimap(f, board, circles, crosses, player_idx);
+        }
+
+        // status under the board: move-limit countdown and race progress
+        let mut status: Vec<String> = Vec::new();
+        if let Some(b) = play.budget {
+            status.push(format!("moves left: {}", b.saturating_sub(play.moves_made)));
+        }
+        status.extend(play.undo_hint());
+        status.extend(play.bookmark_hint());
+        status.extend(play.powerup_hint());
+        status.extend(play.optimal_hint());
+        if let Some(n) = play.count {
+            status.push(format!("{} times: pick a direction", n));
+        }
+        if play.bookmark_armed {
+            status.push("bookmark: press 1, 2 or 3".to_string());
+        }
+        if play.remove_armed {
+            status.push("remove a cross: pick a direction".to_string());
+        }
+        if self.race.is_some() {
+            status.push(format!("you: {} moves", play.moves_made));
+            status.push(
+                self.opponent
+                    .map_or("opponent: 0 moves".to_string(), |p| p.describe()),
+            );
+        }
+        if !status.is_empty() {
+            let hud = Paragraph::new(Spans::from(Span::styled(
+                status.join("  -  "),
+                Style::default().fg(Color::White),
+            )))
+            .alignment(Alignment::Center);
+            f.render_widget(hud, areas.hud);
+        }
+
+        // a race can't be restarted
+        if self.race.is_none() {
+            render_soft_lock(f, play, areas.toast);
+        }
+        render_toast(f, play.toast, areas.toast);
+        render_paused(f, play, areas.toast);
+        render_announcement(f, play.announcement.as_deref());
+
+        if frame.is_none() {
+            render_end_overlay(f, play, &self.end_keys());
+        }
+
+        if let Some(lines) = &debug {
+            render_debug(f, lines);
+        }
+        if play.show_log {
+            render_log(f);
+        }
+    }
 }
 
+/// Play a puzzle file; finishes with how the player left and whether they
+/// solved it along the way.
 pub fn run_puzzle(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
     board: Board,
-    mut circles: Vec<(usize, usize)>,
-    mut crosses: Vec<(usize, usize)>,
+    circles: Vec<(usize, usize)>,
+    crosses: Vec<(usize, usize)>,
     mut player_idx: usize,
-) -> Result<(), Box<dyn Error>> {
-    // Create board and helpers
-    let rows = board.rows;
-    let cols = board.cols;
-    let row_widths = &board.row_widths;
-    let to_flat = |r: usize, c: usize| board.to_flat(r, c);
-    let from_flat = |idx: usize| board.from_flat(idx);
-    let default_grid_w = board.default_grid_w;
-    let default_grid_h = board.default_grid_h;
-
+    race: Option<&mut race::Link>,
+) -> Result<(GameExit, bool), Box<dyn Error>> {
     if circles.is_empty() {
         return Err("puzzle has no circles".into());
     }
     if player_idx >= circles.len() {
         player_idx = 0;
     }
+    let mut play = Play::new(&board, circles, crosses, player_idx);
+    // puzzle files carry no par, so the move-limit rule solves for one here
+    if board.rules.move_limit && !play.won && !play.lost {
+        play.budget = generator::solve_min_moves(
+            &board,
+            &play.circles,
+            &play.crosses,
+            player_idx,
+            PAR_MAX_NODES,
+        )
+        .map(move_budget);
+    }
+    if race.is_some() {
+        play.undos = Undos::Limit(0);
+        play.bookmarks = None;
+    }
+    let mut screen = PuzzleScreen {
+        play,
+        race,
+        opponent: None,
+        solved: false,
+    };
+    let exit = app::run(terminal, &mut screen)?;
+    Ok((exit, screen.solved))
+}
 
-    // initial win/lose checks
-    let mut circles_flat_now: Vec<usize> = circles.iter().map(|&(r, c)| to_flat(r, c)).collect();
-    let mut crosses_flat_now: Vec<usize> = crosses.iter().map(|&(r, c)| to_flat(r, c)).collect();
-    let mut won = is_win_flat(&circles_flat_now, &board);
-    let mut lost = check_lose_flat(&crosses_flat_now, &board);
+// Board the sandbox starts on, rows by columns, the player in the middle
+const SANDBOX_SIZE: (usize, usize) = (6, 6);
+// Background of the sandbox's edit cursor
+const CURSOR_BG: Color = Color::Gray;
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
+/// How the sandbox screen finished: left, or to add or delete the cell under
+/// the cursor, which takes a new board.
+enum SandboxExit {
+    Leave(GameExit),
+    ToggleCell,
+}
 
-            // ensure grid fits terminal
-            let grid_w = if default_grid_w + 2 > size.width {
-                size.width.saturating_sub(2)
-            } else {
-                default_grid_w
-            };
-            let grid_h = if default_grid_h + 2 > size.height {
-                size.height.saturating_sub(2)
-            } else {
-                default_grid_h
-            };
+/// Free play: the player moves as usual while pieces are placed and removed
+/// and cells deleted under an edit cursor. Lines are reported, not ended on.
+struct SandboxScreen<'a> {
+    play: Play<'a>,
+    cursor: (usize, usize),
+}
 
-            let x = (size.width.saturating_sub(grid_w)) / 2;
-            let y = (size.height.saturating_sub(grid_h)) / 2;
-            let area = Rect::new(x, y, grid_w, grid_h);
+impl SandboxScreen<'_> {
+    /// Start a new position after an edit: moves before it can't be taken
+    /// back, since they may concern pieces that are gone.
+    fn edited(&mut self) {
+        let play = &mut self.play;
+        play.history.clear();
+        play.removals.clear();
+        play.trail = None;
+        play.toast = None;
+        play.stalemate = false;
+        (play.won, play.lost) = play.outcome();
+    }
 
-            let mut lines: Vec<Spans> = Vec::new();
+    fn move_cursor(&mut self, (dr, dc): (isize, isize)) {
+        let board = self.play.board;
+        let (r, c) = self.cursor;
+        self.cursor = (
+            r.saturating_add_signed(dr).min(board.rows - 1),
+            c.saturating_add_signed(dc).min(board.cols - 1),
+        );
+    }
 
-            // Top border (aggressive removal): horizontal dashes only where top cell exists
-            let mut top = String::new();
-            if rows > 0 {
-                for col in 0..cols {
-                    let present = col < row_widths[0] && board.is_cell_present(0, col);
-                    if present {
-                        top.push_str("─── ");
-                    } else {
-                        top.push_str("    ");
-                    }
-                }
-            } else {
-                for _ in 0..cols {
-                    top.push_str("    ");
-                }
+    /// Remove the circle at `idx`, which is not the player.
+    fn remove_circle(&mut self, idx: usize) {
+        let play = &mut self.play;
+        play.circles.remove(idx);
+        if idx < play.player_idx {
+            play.player_idx -= 1;
+        }
+    }
+
+    /// `o`: a circle on an empty cell or over a cross; on a circle, that
+    /// circle becomes the player.
+    fn place_circle(&mut self) {
+        let at = self.cursor;
+        let play = &mut self.play;
+        if !play.board.is_cell_present(at.0, at.1) {
+            play.toast = Some(("no cell there", Instant::now()));
+            return;
+        }
+        if let Some(idx) = play.circles.iter().position(|&p| p == at) {
+            play.player_idx = idx;
+        } else if play.circles.len() >= MAX_CIRCLES {
+            play.toast = Some(("no more circles allowed", Instant::now()));
+            return;
+        } else {
+            play.crosses.retain(|&p| p != at);
+            play.circles.push(at);
+        }
+        self.edited();
+    }
+
+    /// `x`: a cross on an empty cell or over a circle other than the player.
+    fn place_cross(&mut self) {
+        let at = self.cursor;
+        let play = &self.play;
+        if !play.board.is_cell_present(at.0, at.1) {
+            self.play.toast = Some(("no cell there", Instant::now()));
+            return;
+        }
+        if play.crosses.contains(&at) {
+            return;
+        }
+        match play.circles.iter().position(|&p| p == at) {
+            Some(idx) if idx == play.player_idx => {
+                self.play.toast = Some(("the player can't be removed", Instant::now()));
+                return;
             }
-            lines.push(Spans::from(Span::raw(top)));
-
-            for row in 0..rows {
-                // Content line: draw only internal vertical separators between adjacent present cells
-                let mut span_line: Vec<Span> = Vec::new();
-                for col in 0..cols {
-                    let present = col < row_widths[row] && board.is_cell_present(row, col);
-                    if !present {
-                        // missing cell: reserve full cell width
-                        span_line.push(Span::raw("    "));
-                        continue;
-                    }
-                    let next_present =
-                        (col + 1) < row_widths[row] && board.is_cell_present(row, col + 1);
-
-                    if let Some(idx) = circles.iter().position(|&(rr, cc)| rr == row && cc == col) {
-                        let is_player = idx == player_idx;
-                        let symbol = "o";
-                        let style = if is_player {
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::BOLD)
-                        } else {
-                            Style::default().fg(Color::LightBlue)
-                        };
-                        span_line.push(Span::raw(" "));
-                        span_line.push(Span::styled(symbol.to_string(), style));
-                        span_line.push(Span::raw(if next_present { " │" } else { "  " }));
-                        continue;
-                    }
-                    if let Some(_) = crosses.iter().position(|&(rr, cc)| rr == row && cc == col) {
-                        let style = Style::default().fg(Color::Red);
-                        span_line.push(Span::raw(" "));
-                        span_line.push(Span::styled("x".to_string(), style));
-                        span_line.push(Span::raw(if next_present { " │" } else { "  " }));
-                        continue;
-                    }
+            Some(idx) => self.remove_circle(idx),
+            None => {}
+        }
+        self.play.crosses.push(at);
+        self.edited();
+    }
 
-                    // empty present cell
-                    span_line.push(Span::raw(if next_present { "   │" } else { "    " }));
-                }
-                lines.push(Spans::from(span_line));
-
-                // Middle border or bottom - draw horizontal only where both rows have present cell (more aggressive)
-                if row != rows - 1 {
-                    let mut mid = String::new();
-                    for col in 0..cols {
-                        let top_here = col < row_widths[row] && board.is_cell_present(row, col);
-                        let bottom_here = if row + 1 < rows {
-                            col < row_widths[row + 1] && board.is_cell_present(row + 1, col)
-                        } else {
-                            false
-                        };
-                        if top_here && bottom_here {
-                            mid.push_str("─── ");
-                        } else {
-                            mid.push_str("    ");
-                        }
-                    }
-                    lines.push(Spans::from(Span::raw(mid)));
-                } else {
-                    let mut bot = String::new();
-                    for col in 0..cols {
-                        let bot_seg = col < row_widths[row] && board.is_cell_present(row, col);
-                        if bot_seg {
-                            bot.push_str("─── ");
-                        } else {
-                            bot.push_str("    ");
-                        }
-                    }
-                    lines.push(Spans::from(Span::raw(bot)));
-                }
+    /// Backspace: the piece under the cursor goes, or else the cell itself
+    /// (or a deleted cell comes back).
+    fn clear(&mut self) -> Step<SandboxExit> {
+        let at = self.cursor;
+        let play = &self.play;
+        match play.circles.iter().position(|&p| p == at) {
+            Some(idx) if idx == play.player_idx => {
+                self.play.toast = Some(("the player can't be removed", Instant::now()));
+            }
+            Some(idx) => {
+                self.remove_circle(idx);
+                self.edited();
+            }
+            None if play.crosses.contains(&at) => {
+                self.play.crosses.retain(|&p| p != at);
+                self.edited();
+            }
+            None => return Step::Done(SandboxExit::ToggleCell),
+        }
+        Step::Redraw
+    }
+
+    fn status(&self) -> &'static str {
+        if self.play.stalemate {
+            return "the player is boxed in: stalemate";
+        }
+        match (self.play.won, self.play.lost) {
+            (true, true) => "circles and crosses both in a line",
+            (true, false) => "circles in a line: this would win",
+            (false, true) => "crosses in a line: this would lose",
+            (false, false) => "no line yet",
+        }
+    }
+}
+
+impl Screen for SandboxScreen<'_> {
+    type Output = SandboxExit;
+
+    fn update(&mut self, event: AppEvent) -> Step<SandboxExit> {
+        let key = match event {
+            AppEvent::KeyPressed(key) => key,
+            AppEvent::Resize => return Step::Redraw,
+            AppEvent::Tick if self.play.tick() => return Step::Redraw,
+            AppEvent::Tick
+            | AppEvent::GenerationDone(_)
+            | AppEvent::TopTimes(_)
+            | AppEvent::Focus(_) => return Step::Idle,
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                return Step::Done(SandboxExit::Leave(GameExit::Menu));
+            }
+            KeyCode::Char('h') => self.move_cursor((0, -1)),
+            KeyCode::Char('j') => self.move_cursor((1, 0)),
+            KeyCode::Char('k') => self.move_cursor((-1, 0)),
+            KeyCode::Char('l') => self.move_cursor((0, 1)),
+            KeyCode::Char('o') => self.place_circle(),
+            KeyCode::Char('x') => self.place_cross(),
+            KeyCode::Backspace => return self.clear(),
+            _ => {
+                // a line only ends a real game; here the player keeps moving
+                let play = &mut self.play;
+                (play.won, play.lost) = (false, false);
+                play.key(key);
+                play.ended_at = None;
             }
+        }
+        Step::Redraw
+    }
 
-            let paragraph = Paragraph::new(lines).block(Block::default());
-            f.render_widget(paragraph, area);
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let play = &self.play;
+        let board = play.board;
+        let (circles, crosses) = (&play.circles, &play.crosses);
+
+        let size = f.size();
+        let scale = CellScale::fit(board, size, (2, 3));
+        let (full_w, full_h) = scale.grid_size(board);
+        let areas = layout::play(size, (full_w, full_h), true);
+        let area = areas.board;
+        let scroll = camera(
+            circles[play.player_idx],
+            (full_w, full_h),
+            (area.width, area.height),
+        );
+        let paragraph = Paragraph::new(grid_lines(play, None, scale))
+            .block(Block::default())
+            .scroll(scroll);
+        f.render_widget(paragraph, area);
+
+        // the cursor's cell, where the grid puts it, if it is in view
+        let (cell_w, cell_h) = scale.cell_size();
+        let left = crate::config::get().grid_style.left_edge();
+        let x = (left + self.cursor.1 * (cell_w + 1)) as u16;
+        let y = (1 + self.cursor.0 * (cell_h + 1)) as u16;
+        if let (Some(x), Some(y)) = (x.checked_sub(scroll.1), y.checked_sub(scroll.0))
+            && x + cell_w as u16 <= area.width
+            && y + cell_h as u16 <= area.height
+        {
+            let cell = Rect::new(area.x + x, area.y + y, cell_w as u16, cell_h as u16);
+            f.render_widget(Block::default().style(Style::default().bg(CURSOR_BG)), cell);
+        }
+        if play.show_minimap {
+            render_minimap(f, board, circles, crosses, play.player_idx);
+        }
 
-            // If won, render an overlay message centered on screen
-            if won {
-                let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
-                let overlay_h = 5u16;
-                let ox = (size.width.saturating_sub(overlay_w)) / 2;
-                let oy = (size.height.saturating_sub(overlay_h)) / 2;
-                let o_area = Rect::new(ox, oy, overlay_w, overlay_h);
+        let hud = Paragraph::new(Spans::from(Span::styled(
+            "h/j/k/l: cursor   o: circle   x: cross   Backspace: clear   Esc: back",
+            Style::default().fg(Color::White),
+        )))
+        .alignment(Alignment::Center);
+        f.render_widget(hud, areas.hud);
 
-                let mut msg_lines: Vec<Spans> = Vec::new();
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    " YOU WON! ",
-                    Style::default()
-                        .fg(Color::Black)
-                        .bg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                )));
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    "press q to quit",
-                    Style::default().fg(Color::White).bg(Color::Black),
-                )));
+        render_toast(f, play.toast, areas.toast);
+        render_announcement(f, play.announcement.as_deref());
 
-                let overlay = Paragraph::new(msg_lines)
-                    .alignment(Alignment::Center)
-                    .style(Style::default().bg(Color::Black))
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title("Victory")
-                            .style(Style::default().bg(Color::Black)),
-                    );
-                f.render_widget(Clear, o_area);
-                f.render_widget(
-                    Block::default().style(Style::default().bg(Color::Black)),
-                    o_area,
-                );
-                f.render_widget(overlay, o_area);
+        let mut status = format!(" Sandbox  |  {}", self.status());
+        if let Some(optimal) = play.optimal_hint() {
+            status.push_str(&format!("  |  {}", optimal));
+        }
+        for name in board.rules.names() {
+            status.push_str(&format!("  |  {}", name));
+        }
+        let status_bar = Paragraph::new(Spans::from(Span::raw(status)))
+            .style(Style::default().fg(Color::Black).bg(Color::Gray));
+        f.render_widget(status_bar, areas.status);
+
+        if play.show_debug {
+            render_debug(
+                f,
+                &debug_lines(board, circles, crosses, play.player_idx, &play.bound),
+            );
+        }
+        if play.show_log {
+            render_log(f);
+        }
+    }
+}
+
+/// Free play on an empty board with the difficulty menu's last rules,
+/// editing the position while playing it.
+fn run_sandbox(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+) -> Result<GameExit, Box<dyn Error>> {
+    let (rows, cols) = SANDBOX_SIZE;
+    let mut board = Board::from_dims(rows, cols, &[])?;
+    board.rules = Prefs::load().rules.unwrap_or(crate::config::get().rules);
+    let mut position: Position = (vec![(rows / 2, cols / 2)], Vec::new());
+    let mut player_idx = 0;
+    let mut cursor = (rows / 2, cols / 2);
+    // the screen borrows the board, so deleting a cell starts it again
+    loop {
+        let (circles, crosses) = position;
+        let mut play = Play::new(&board, circles, crosses, player_idx);
+        // a bookmark could bring back circles an edit removed, and losing
+        // is part of the experiment
+        play.bookmarks = None;
+        play.warn_losing = false;
+        let mut screen = SandboxScreen { play, cursor };
+        let exit = app::run(terminal, &mut screen)?;
+        let play = screen.play;
+        position = (play.circles, play.crosses);
+        player_idx = play.player_idx;
+        cursor = screen.cursor;
+        match exit {
+            SandboxExit::Leave(exit) => return Ok(exit),
+            SandboxExit::ToggleCell => {
+                let idx = board.to_flat(cursor.0, cursor.1);
+                board.cells[idx] = !board.cells[idx];
+                let what = if board.cells[idx] {
+                    "restored"
+                } else {
+                    "deleted"
+                };
+                log::info!("sandbox: {} cell {:?}", what, cursor);
             }
+        }
+    }
+}
 
-            // If lost, render an overlay message centered on screen
-            if lost {
-                let overlay_w = std::cmp::min(36, size.width.saturating_sub(4));
-                let overlay_h = 5u16;
-                let ox = (size.width.saturating_sub(overlay_w)) / 2;
-                let oy = (size.height.saturating_sub(overlay_h)) / 2;
-                let o_area = Rect::new(ox, oy, overlay_w, overlay_h);
+// Time between moves while a replay plays by itself
+const REPLAY_STEP: Duration = Duration::from_millis(600);
+
+/// A finished game from the journal, shown one position at a time.
+struct ReplayScreen<'a> {
+    play: Play<'a>,
+    entry: &'a Entry,
+    step: usize,
+    // playing by itself, and when the last step was shown
+    playing: bool,
+    stepped_at: Instant,
+}
 
-                let mut msg_lines: Vec<Spans> = Vec::new();
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    " YOU LOST! three crosses aligned ",
-                    Style::default()
-                        .fg(Color::White)
-                        .bg(Color::Red)
-                        .add_modifier(Modifier::BOLD),
-                )));
-                msg_lines.push(Spans::from(Span::raw("")));
-                msg_lines.push(Spans::from(Span::styled(
-                    "press q to quit",
-                    Style::default().fg(Color::White).bg(Color::Black),
-                )));
+impl ReplayScreen<'_> {
+    fn show(&mut self, step: usize) {
+        self.step = step.min(self.entry.positions.len() - 1);
+        (self.play.circles, self.play.crosses) = self.entry.positions[self.step].clone();
+        (self.play.won, self.play.lost) = self.play.outcome();
+        self.play.moves_made = self.step;
+        self.stepped_at = Instant::now();
+    }
+}
 
-                let overlay = Paragraph::new(msg_lines)
-                    .alignment(Alignment::Center)
-                    .style(Style::default().bg(Color::Black))
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title("Defeat")
-                            .style(Style::default().bg(Color::Black)),
-                    );
-                f.render_widget(Clear, o_area);
-                f.render_widget(
-                    Block::default().style(Style::default().bg(Color::Black)),
-                    o_area,
-                );
-                f.render_widget(overlay, o_area);
-            }
-        })?;
-
-        // Input handling: arrows and WASD. movement blocked by walls and other objects
-        if event::poll(Duration::from_millis(150))? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char(c) => match c.to_ascii_lowercase() {
-                        'q' => break,
-                        'w' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    -1,
-                                    0,
-                                    &board,
-                                )
-                            }
-                        }
-                        'a' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    0,
-                                    -1,
-                                    &board,
-                                )
-                            }
-                        }
-                        's' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    1,
-                                    0,
-                                    &board,
-                                )
-                            }
-                        }
-                        'd' => {
-                            if !won && !lost {
-                                movement::attempt_move_runtime(
-                                    &mut circles,
-                                    &mut crosses,
-                                    player_idx,
-                                    0,
-                                    1,
-                                    &board,
-                                )
-                            }
-                        }
-                        _ => {}
-                    },
-                    KeyCode::Up => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                -1,
-                                0,
-                                &board,
-                            )
-                        }
-                    }
-                    KeyCode::Left => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                0,
-                                -1,
-                                &board,
-                            )
-                        }
-                    }
-                    KeyCode::Down => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                1,
-                                0,
-                                &board,
-                            )
-                        }
+impl Screen for ReplayScreen<'_> {
+    type Output = ();
+
+    fn update(&mut self, event: AppEvent) -> Step<()> {
+        let last = self.entry.positions.len() - 1;
+        match event {
+            AppEvent::KeyPressed(key) => match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Step::Done(()),
+                KeyCode::Left | KeyCode::Char('a') => self.show(self.step.saturating_sub(1)),
+                KeyCode::Right | KeyCode::Char('d') => self.show(self.step + 1),
+                KeyCode::Home => self.show(0),
+                KeyCode::End => self.show(last),
+                KeyCode::Char(' ') => {
+                    if self.step == last {
+                        self.show(0);
                     }
-                    KeyCode::Right => {
-                        if !won && !lost {
-                            movement::attempt_move_runtime(
-                                &mut circles,
-                                &mut crosses,
-                                player_idx,
-                                0,
-                                1,
-                                &board,
-                            )
-                        }
-                    }
-                    KeyCode::Esc => break,
-                    _ => {}
+                    self.playing = !self.playing;
+                    self.stepped_at = Instant::now();
                 }
+                _ => return Step::Idle,
+            },
+            AppEvent::Tick if self.playing && self.stepped_at.elapsed() >= REPLAY_STEP => {
+                self.show(self.step + 1);
+                self.playing = self.step < last;
+            }
+            AppEvent::Resize => {}
+            AppEvent::Tick
+            | AppEvent::GenerationDone(_)
+            | AppEvent::TopTimes(_)
+            | AppEvent::Focus(_) => {
+                return Step::Idle;
             }
-            // re-evaluate win/lose state after handling input
-            circles_flat_now = circles.iter().map(|&(r, c)| to_flat(r, c)).collect();
-            crosses_flat_now = crosses.iter().map(|&(r, c)| to_flat(r, c)).collect();
-            won = is_win_flat(&circles_flat_now, &board);
-            lost = check_lose_flat(&crosses_flat_now, &board);
         }
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let play = &self.play;
+        let board = play.board;
+        let entry = self.entry;
+
+        let size = f.size();
+        let scale = CellScale::fit(board, size, (2, 3));
+        let (full_w, full_h) = scale.grid_size(board);
+        let areas = layout::play(size, (full_w, full_h), true);
+        let scroll = camera(
+            play.circles[play.player_idx],
+            (full_w, full_h),
+            (areas.board.width, areas.board.height),
+        );
+        let paragraph = Paragraph::new(grid_lines(play, None, scale))
+            .block(Block::default())
+            .scroll(scroll);
+        f.render_widget(paragraph, areas.board);
+
+        let hud = format!(
+            "move {} of {}  -  ←/→: step   space: {}   Esc: back",
+            self.step,
+            entry.positions.len() - 1,
+            if self.playing { "pause" } else { "play" }
+        );
+        let hud = Paragraph::new(Spans::from(Span::styled(
+            hud,
+            Style::default().fg(Color::White),
+        )))
+        .alignment(Alignment::Center);
+        f.render_widget(hud, areas.hud);
+
+        let status = format!(
+            " Replay  |  {}  |  seed {}  |  {}  |  {}",
+            entry.label(),
+            entry.seed,
+            journal::date(entry.finished_at),
+            if entry.won { "won" } else { "lost" }
+        );
+        let status_bar = Paragraph::new(Spans::from(Span::raw(status)))
+            .style(Style::default().fg(Color::Black).bg(Color::Gray));
+        f.render_widget(status_bar, areas.status);
     }
+}
 
-    Ok(())
+/// Watch the game of a journal entry move by move.
+pub fn replay(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    entry: &Entry,
+) -> Result<(), Box<dyn Error>> {
+    if entry.positions.is_empty() {
+        return Err("journal entry has no positions".into());
+    }
+    let board = entry.puzzle.board()?;
+    let (circles, crosses) = entry.positions[0].clone();
+    let play = Play::new(&board, circles, crosses, entry.puzzle.player_idx());
+    let mut screen = ReplayScreen {
+        play,
+        entry,
+        step: 0,
+        playing: true,
+        stepped_at: Instant::now(),
+    };
+    app::run(terminal, &mut screen)
 }
@@ -0,0 +1,247 @@
+// `tic-tac-go generate`: write a batch of seeded puzzles to a directory.
+//
+// Puzzles equal to one already in the batch or in the output directory, up to
+// mirroring and rotation, are dropped (see `PuzzleFile::canonical_hash`).
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::{Rng, thread_rng};
+
+use crate::ai::Strength;
+use crate::config::{self, Config};
+use crate::generator::{Difficulty, generate_from_seed};
+use crate::puzzle::PuzzleFile;
+use crate::rules::{MAX_CIRCLES, MIN_CIRCLES, Variant};
+
+const DEFAULT_COUNT: usize = 10;
+// Give up after this many seeds per requested puzzle, so a difficulty that
+// rarely succeeds on small boards cannot loop forever.
+const ATTEMPTS_PER_PUZZLE: usize = 20;
+
+fn print_usage() {
+    eprintln!(
+        "usage: tic-tac-go generate [--count N] [--difficulty LEVEL] [--seed S] [--out DIR] [--variant NAME] [--diagonals] [--chain-push] [--pull] [--adversarial] [--ai-strength LEVEL] [--move-limit] [--wrap] [--sinkholes] [--unison]"
+    );
+    eprintln!();
+    eprintln!(
+        "Writes N puzzles (default {}) to DIR (default: puzzles), skipping",
+        DEFAULT_COUNT
+    );
+    eprintln!("duplicates and mirrored copies.");
+    eprintln!("LEVEL is easy, medium, hard, expert or insane.");
+    eprintln!("Consecutive seeds starting at S are used, so a batch is reproducible.");
+    eprintln!("--variant picks the rule set: classic, four_in_a_row or goal_cells.");
+    eprintln!("--diagonals makes diagonal lines count for wins and losses.");
+    eprintln!("--chain-push lets a push move a whole row of adjacent pieces.");
+    eprintln!("--pull lets the player pull the piece behind it.");
+    eprintln!("--adversarial marks puzzles as played against moving crosses;");
+    eprintln!("--ai-strength (easy, normal or hard) sets how far ahead they look.");
+    eprintln!("--move-limit makes running out of moves lose (the budget follows par).");
+    eprintln!("--wrap makes the board toroidal: pieces leaving one edge enter the opposite one.");
+    eprintln!("--sinkholes makes crosses pushed into a hole fall in for good.");
+    eprintln!("--unison makes every move step all circles at once.");
+    eprintln!();
+    eprintln!("Difficulty overrides (on top of the config file):");
+    eprintln!("  --crosses MIN-MAX       number of crosses to place");
+    eprintln!("  --circles N             number of circles (3 to 5)");
+    eprintln!("  --scramble-budget N     reverse search states per attempt");
+    eprintln!("  --attempts N            configurations tried per puzzle");
+    eprintln!("  --min-score X           lowest composite score to aim for");
+    eprintln!("  --max-score X           highest composite score to aim for");
+    eprintln!("  --min-moves N           shortest optimal solution to accept");
+    eprintln!("  --max-moves N           longest optimal solution to accept");
+    eprintln!("  --moves MIN-MAX         both at once, e.g. 25-30");
+    eprintln!("  --ice PERCENT           share of cells turned into ice");
+    eprintln!("  --heavy N               crosses that can't be moved, at most");
+    eprintln!("  --powerups N            power-up tiles that each remove a cross");
+    eprintln!("  --hardest-of N          generate N puzzles in parallel, keep the hardest");
+}
+
+fn parse_value<T: std::str::FromStr>(
+    it: &mut std::slice::Iter<String>,
+    flag: &str,
+) -> Result<T, Box<dyn Error>> {
+    it.next()
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("{} expects a number", flag).into())
+}
+
+fn parse_range(s: &str) -> Option<(usize, usize)> {
+    let (lo, hi) = s.split_once('-').unwrap_or((s, s));
+    let (lo, hi) = (lo.trim().parse().ok()?, hi.trim().parse().ok()?);
+    (lo <= hi).then_some((lo, hi))
+}
+
+pub fn parse_difficulty(s: &str) -> Option<Difficulty> {
+    match s.to_ascii_lowercase().as_str() {
+        "easy" => Some(Difficulty::Easy),
+        "medium" => Some(Difficulty::Medium),
+        "hard" => Some(Difficulty::Hard),
+        "expert" => Some(Difficulty::Expert),
+        "insane" => Some(Difficulty::Insane),
+        _ => None,
+    }
+}
+
+pub fn run(args: &[String], mut config: Config) -> Result<(), Box<dyn Error>> {
+    let mut count = DEFAULT_COUNT;
+    let mut difficulty = Difficulty::Medium;
+    let mut seed: u64 = thread_rng().r#gen();
+    let mut out = PathBuf::from("puzzles");
+    let mut overrides = config::DifficultyOverrides::default();
+    let mut rules = config.rules;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--count" | "-n" => {
+                count = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("--count expects a positive number")?;
+            }
+            "--difficulty" | "-d" => {
+                difficulty = it
+                    .next()
+                    .and_then(|v| parse_difficulty(v))
+                    .ok_or("--difficulty expects easy, medium, hard, expert or insane")?;
+            }
+            "--seed" => {
+                seed = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--seed expects a number")?;
+            }
+            "--out" | "-o" => {
+                out = PathBuf::from(it.next().ok_or("--out expects a directory")?);
+            }
+            "--variant" => {
+                rules.variant = it
+                    .next()
+                    .and_then(|v| Variant::parse(v))
+                    .ok_or("--variant expects classic, four_in_a_row or goal_cells")?;
+            }
+            "--diagonals" => rules.diagonals = true,
+            "--chain-push" => rules.chain_push = true,
+            "--pull" => rules.pull = true,
+            "--adversarial" => rules.adversarial = true,
+            "--ai-strength" => {
+                rules.ai_strength = it
+                    .next()
+                    .and_then(|v| Strength::parse(v))
+                    .ok_or("--ai-strength expects easy, normal or hard")?;
+            }
+            "--move-limit" => rules.move_limit = true,
+            "--wrap" => rules.wrap = true,
+            "--sinkholes" => rules.sinkholes = true,
+            "--unison" => rules.unison = true,
+            "--crosses" => {
+                overrides.crosses = Some(
+                    it.next()
+                        .and_then(|v| parse_range(v))
+                        .ok_or("--crosses expects MIN-MAX")?,
+                );
+            }
+            "--circles" => {
+                let n: usize = parse_value(&mut it, arg)?;
+                if !(MIN_CIRCLES..=MAX_CIRCLES).contains(&n) {
+                    return Err(
+                        format!("--circles expects {} to {}", MIN_CIRCLES, MAX_CIRCLES).into(),
+                    );
+                }
+                overrides.circles = Some(n);
+            }
+            "--scramble-budget" => overrides.scramble_budget = Some(parse_value(&mut it, arg)?),
+            "--attempts" => overrides.attempts = Some(parse_value(&mut it, arg)?),
+            "--min-score" => overrides.min_score = Some(parse_value(&mut it, arg)?),
+            "--max-score" => overrides.max_score = Some(parse_value(&mut it, arg)?),
+            "--min-moves" => overrides.min_moves = Some(parse_value(&mut it, arg)?),
+            "--max-moves" => overrides.max_moves = Some(parse_value(&mut it, arg)?),
+            "--moves" => {
+                let (min, max) = it
+                    .next()
+                    .and_then(|v| parse_range(v))
+                    .ok_or("--moves expects MIN-MAX")?;
+                overrides.min_moves = Some(min);
+                overrides.max_moves = Some(max);
+            }
+            "--hardest-of" => overrides.hardest_of = Some(parse_value(&mut it, arg)?),
+            "--ice" => overrides.ice_percent = Some(parse_value(&mut it, arg)?),
+            "--heavy" => overrides.heavy_crosses = Some(parse_value(&mut it, arg)?),
+            "--powerups" => overrides.powerups = Some(parse_value(&mut it, arg)?),
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(());
+            }
+            other => {
+                print_usage();
+                return Err(format!("unknown option {}", other).into());
+            }
+        }
+    }
+
+    config.overrides_mut(difficulty).merge(&overrides);
+    config::init(config);
+
+    fs::create_dir_all(&out)?;
+    let mut seen: HashSet<u64> = HashSet::new();
+    for entry in fs::read_dir(&out)?.flatten() {
+        if let Ok(p) = PuzzleFile::load(&entry.path())
+            && p.board().is_ok_and(|b| p.check(&b).is_ok())
+        {
+            seen.insert(p.canonical_hash());
+        }
+    }
+
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .ok();
+    let mut written = 0usize;
+    let mut duplicates = 0usize;
+    let mut failed = 0usize;
+    for attempt in 0..count * ATTEMPTS_PER_PUZZLE {
+        if written == count {
+            break;
+        }
+        let s = seed.wrapping_add(attempt as u64);
+        let puzzle = generate_from_seed(s, difficulty, rules);
+        if puzzle.circles.is_empty() {
+            failed += 1;
+            continue;
+        }
+        let board = &puzzle.board;
+        let circles: Vec<(usize, usize)> =
+            puzzle.circles.iter().map(|&f| board.from_flat(f)).collect();
+        let crosses: Vec<(usize, usize)> =
+            puzzle.crosses.iter().map(|&f| board.from_flat(f)).collect();
+        let file = PuzzleFile::from_parts(board, &circles, &crosses, puzzle.player_idx, created_at);
+        if !seen.insert(file.canonical_hash()) {
+            duplicates += 1;
+            continue;
+        }
+        // the same seed with other settings gives another puzzle; keep both
+        let mut path = out.join(format!("generated-{}.json", s));
+        let mut copy = 1;
+        while path.exists() {
+            copy += 1;
+            path = out.join(format!("generated-{}-{}.json", s, copy));
+        }
+        fs::write(&path, serde_json::to_string(&file)?)?;
+        println!("{}", path.display());
+        written += 1;
+    }
+
+    eprintln!(
+        "wrote {} puzzles ({} duplicates skipped, {} seeds failed to generate)",
+        written, duplicates, failed
+    );
+    if written < count {
+        return Err(format!("only {} of {} puzzles could be generated", written, count).into());
+    }
+    Ok(())
+}
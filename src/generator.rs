@@ -1,7 +1,7 @@
 // Constructive puzzle generation using reverse-play BFS (sokoban-style).
 //
 // Algorithm (based on proven Sokoban level generators like miki151/sokoban):
-// 1. Start from a solved state (circles in a winning triple)
+// 1. Start from a solved state (circles on a winning placement of the rule set)
 // 2. Place crosses strategically, avoiding deadlocks
 // 3. Explore reverse moves via BFS to build a tree of reachable states
 // 4. Pick the state with maximum distance from the solution as the puzzle
@@ -10,74 +10,229 @@
 // This guarantees every generated puzzle is solvable by construction since
 // the initial state was reached by reversing valid moves from a solution.
 
-use crate::board::Board;
-use crate::rules::{check_cross_deadlock, check_lose_flat, is_win_flat};
+use crate::board::{Board, Carving};
+use crate::movement::{MAX_POWERUPS, legal_moves};
+use crate::rating::{Rating, rate};
+use crate::rules::{
+    MIN_CIRCLES, Rules, check_cross_deadlock, check_lose_flat, in_play, is_win_flat,
+};
+use crate::solver::Step;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::{Rng, thread_rng};
+use rand::{Rng, SeedableRng, thread_rng};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashSet, VecDeque};
+use std::ops::RangeInclusive;
+use std::thread;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Difficulty {
     Easy,
     Medium,
     Hard,
+    Expert,
+    Insane,
 }
 
+// Solver budget for scoring a candidate; candidates that need more are dropped.
+const RATING_MAX_NODES: usize = 200_000;
+// States the forced-loss search may visit per candidate; what lies beyond
+// counts as safe.
+const SAFETY_MAX_NODES: usize = 20_000;
+// Scrambles per attempt while homing in on a maximum solution length
+const DEPTH_ROUNDS: usize = 4;
+
+/// Knobs that define a difficulty. The built-in values can be overridden per
+/// field from the config file or the `generate` command line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyParams {
+    /// Range of crosses to place
+    pub crosses: (usize, usize),
+    /// Number of circles (3 to 5); any three aligned win
+    pub circles: usize,
+    /// Reverse BFS exploration budget used to scramble each attempt
+    pub scramble_budget: usize,
+    /// How many random configurations to try per puzzle
+    pub attempts: usize,
+    /// Composite score range (see `rating`) the generator aims for
+    pub min_score: f64,
+    pub max_score: Option<f64>,
+    /// Candidates with a shorter optimal solution are never accepted
+    pub min_moves: usize,
+    /// Nor with a longer one, when set
+    pub max_moves: Option<usize>,
+    /// Reject candidates the player wins by walking: without moving any
+    /// other circle, or with a single push (see `Rating::is_walk`)
+    pub reject_walks: bool,
+    /// Puzzles generated side by side per request; the highest scoring one
+    /// is served
+    pub hardest_of: usize,
+    /// Share of the board's cells turned into ice
+    pub ice_percent: usize,
+    /// Crosses that can't be moved (see `place_heavy`), counted among
+    /// `crosses`
+    pub heavy_crosses: usize,
+    /// Power-up tiles put on empty cells of the finished puzzle
+    pub powerups: usize,
+    /// Moves the player may take back per puzzle
+    pub undos: Undos,
+}
+
+/// An undo allowance: `"unlimited"` or a number in the config file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Undos {
+    Unlimited,
+    #[serde(untagged)]
+    Limit(usize),
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Insane,
+    ];
+
+    pub fn default_params(self) -> DifficultyParams {
+        let (crosses, scramble_budget, attempts, min_score, max_score) = match self {
+            Difficulty::Easy => ((3, 5), 5_000, 30, 4.0, Some(12.0)),
+            Difficulty::Medium => ((4, 8), 20_000, 25, 12.0, Some(22.0)),
+            Difficulty::Hard => ((5, 10), 50_000, 20, 22.0, None),
+            Difficulty::Expert => ((5, 10), 100_000, 16, 26.0, None),
+            Difficulty::Insane => ((5, 10), 100_000, 12, 30.0, None),
+        };
+        let undos = match self {
+            Difficulty::Easy => Undos::Unlimited,
+            Difficulty::Medium => Undos::Limit(3),
+            _ => Undos::Limit(0),
+        };
+        // the tiers above Hard only take long solutions, and generate several
+        // puzzles side by side to find one
+        let (min_moves, max_moves, hardest_of) = match self {
+            Difficulty::Expert => (14, Some(30), 8),
+            Difficulty::Insane => (18, Some(30), 12),
+            _ => (0, None, 1),
+        };
+        DifficultyParams {
+            crosses,
+            circles: MIN_CIRCLES,
+            scramble_budget,
+            attempts,
+            min_score,
+            max_score,
+            min_moves,
+            max_moves,
+            reject_walks: true,
+            hardest_of,
+            ice_percent: 0,
+            heavy_crosses: 0,
+            powerups: 0,
+            undos,
+        }
+    }
+
+    /// Parameters in effect, including any configured overrides.
+    pub fn params(self) -> DifficultyParams {
+        crate::config::get().difficulty_params(self)
+    }
+
+    /// Rows and columns of the tiers played on boards bigger than the usual
+    /// random ones (see `Board::random_from`).
+    fn board_size(self) -> Option<(RangeInclusive<usize>, RangeInclusive<usize>)> {
+        match self {
+            Difficulty::Expert => Some((4..=8, 6..=10)),
+            Difficulty::Insane => Some((5..=9, 7..=12)),
+            _ => None,
+        }
+    }
+}
+
+/// A generated puzzle (flat indices) with how far its score lies outside the
+/// difficulty's range.
+struct Candidate {
+    circles: Vec<usize>,
+    crosses: Vec<usize>,
+    player_idx: usize,
+    distance: f64,
+    score: f64,
+    rating: Rating,
+}
+
+/// Circles, crosses (both in (row, col) form) and a search depth.
+type Placement = (Vec<(usize, usize)>, Vec<(usize, usize)>, usize);
+
+/// Circles and crosses of a scrambled position with its rating.
+type Scrambled = (Vec<(usize, usize)>, Vec<(usize, usize)>, Rating);
+
 #[derive(Clone, Hash, PartialEq, Eq)]
-struct ReverseState {
+struct SearchState {
     circles: Vec<usize>,
     crosses: Vec<usize>,
 }
 
-impl ReverseState {
+impl SearchState {
     fn new(circles: &[(usize, usize)], crosses: &[(usize, usize)], board: &Board) -> Self {
         let circles: Vec<usize> = circles.iter().map(|&(r, c)| board.to_flat(r, c)).collect();
         let mut crosses: Vec<usize> = crosses.iter().map(|&(r, c)| board.to_flat(r, c)).collect();
         crosses.sort_unstable();
-        ReverseState { circles, crosses }
+        SearchState { circles, crosses }
     }
 }
 
-/// Enumerate all winning triples (3 consecutive present cells, horizontal and vertical).
-fn enumerate_triples(board: &Board) -> Vec<Vec<(usize, usize)>> {
-    let mut triples: Vec<Vec<(usize, usize)>> = Vec::new();
-    for r in 0..board.rows {
-        if board.row_widths[r] < 3 {
-            continue;
+/// Mark up to `count` plain cells, off ice and portals, as holding heavy
+/// crosses, never three of them in a line. Every puzzle on the board then has
+/// a cross on each (see `place_crosses`).
+fn place_heavy(board: &mut Board, count: usize, rng: &mut impl Rng) {
+    let mut cells: Vec<usize> = (0..board.total_cells)
+        .filter(|&i| board.cells[i] && !board.ice[i] && board.portals[i].is_none())
+        .collect();
+    cells.shuffle(rng);
+    let mut heavy: Vec<usize> = Vec::new();
+    for i in cells {
+        if heavy.len() == count {
+            break;
         }
-        for c in 0..=board.row_widths[r].saturating_sub(3) {
-            if board.is_cell_present(r, c)
-                && board.is_cell_present(r, c + 1)
-                && board.is_cell_present(r, c + 2)
-            {
-                triples.push(vec![(r, c), (r, c + 1), (r, c + 2)]);
-            }
+        heavy.push(i);
+        if check_lose_flat(&heavy, board) {
+            heavy.pop();
         }
     }
-    if board.rows >= 3 {
-        for r in 0..=board.rows - 3 {
-            let min_w = board.row_widths[r..r + 3]
-                .iter()
-                .cloned()
-                .min()
-                .unwrap_or(0);
-            if min_w == 0 {
-                continue;
-            }
-            for c in 0..min_w {
-                if board.is_cell_present(r, c)
-                    && board.is_cell_present(r + 1, c)
-                    && board.is_cell_present(r + 2, c)
-                {
-                    triples.push(vec![(r, c), (r + 1, c), (r + 2, c)]);
-                }
-            }
-        }
+    for i in heavy {
+        board.heavy[i] = true;
+    }
+}
+
+/// Put `count` power-up tiles on plain cells free of pieces, in place of any
+/// the board had. The puzzle was solved without them, so they only help.
+fn place_powerups(
+    board: &mut Board,
+    circles: &[usize],
+    crosses: &[usize],
+    count: usize,
+    rng: &mut impl Rng,
+) {
+    board.powerups.fill(false);
+    let mut cells: Vec<usize> = (0..board.total_cells)
+        .filter(|&i| {
+            board.cells[i]
+                && !board.ice[i]
+                && !board.goals[i]
+                && board.portals[i].is_none()
+                && !circles.contains(&i)
+                && !crosses.contains(&i)
+        })
+        .collect();
+    cells.shuffle(rng);
+    for i in cells.into_iter().take(count.min(MAX_POWERUPS)) {
+        board.powerups[i] = true;
     }
-    triples
 }
 
 /// Place crosses on the board, avoiding deadlocks and the lose condition.
+/// The board's heavy crosses come first and count towards `count`.
 /// Uses a heuristic: prefer cells at moderate distance from circles to create
 /// interesting obstacles without trivial deadlocks.
 fn place_crosses(
@@ -88,7 +243,7 @@ fn place_crosses(
 ) -> Option<Vec<(usize, usize)>> {
     let occupied: HashSet<usize> = circles.iter().map(|&(r, c)| board.to_flat(r, c)).collect();
     let mut available: Vec<usize> = (0..board.total_cells)
-        .filter(|&i| board.cells[i] && !occupied.contains(&i))
+        .filter(|&i| board.cells[i] && !board.heavy[i] && !occupied.contains(&i))
         .collect();
     if available.len() < count {
         return None;
@@ -116,7 +271,7 @@ fn place_crosses(
         }
     });
 
-    let mut crosses: Vec<(usize, usize)> = Vec::new();
+    let mut crosses = board.heavy_cells();
     for &f in &available {
         if crosses.len() >= count {
             break;
@@ -137,48 +292,59 @@ fn place_crosses(
 
 /// Perform BFS over reverse moves from a solved state.
 /// Returns the farthest reachable state (circles, crosses in rc form) and its depth,
-/// along with the total number of unique states explored.
+/// along with the total number of unique states explored. States `max_depth`
+/// moves away are not expanded further.
 ///
 /// The key insight: every state found this way is guaranteed solvable because
 /// we reached it by undoing valid forward moves from a known solution.
+/// (On ice boards with chain push or pulls the reverse steps are not exact
+/// inverses, but every candidate is re-checked by the solver when rated.)
 fn reverse_bfs(
     board: &Board,
     init_circles: &[(usize, usize)],
     init_crosses: &[(usize, usize)],
     player_idx: usize,
-    max_nodes: usize,
+    params: &DifficultyParams,
+    max_depth: usize,
     rng: &mut impl Rng,
-) -> (Vec<(usize, usize)>, Vec<(usize, usize)>, usize) {
+) -> Placement {
     let dirs: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 
-    let init_state = ReverseState::new(init_circles, init_crosses, board);
-    let mut visited: HashSet<ReverseState> = HashSet::new();
+    let init_state = SearchState::new(init_circles, init_crosses, board);
+    let mut visited: HashSet<SearchState> = HashSet::new();
     visited.insert(init_state);
 
     // BFS queue: (circles_rc, crosses_rc, depth)
-    let mut queue: VecDeque<(Vec<(usize, usize)>, Vec<(usize, usize)>, usize)> = VecDeque::new();
+    let mut queue: VecDeque<Placement> = VecDeque::new();
     queue.push_back((init_circles.to_vec(), init_crosses.to_vec(), 0));
 
     let mut best_circles = init_circles.to_vec();
     let mut best_crosses = init_crosses.to_vec();
     let mut best_depth = 0usize;
     // Collect multiple candidates at high depth for random selection
-    let mut best_candidates: Vec<(Vec<(usize, usize)>, Vec<(usize, usize)>, usize)> = Vec::new();
+    let mut best_candidates: Vec<Placement> = Vec::new();
 
     let mut nodes = 0usize;
 
     while let Some((circles, crosses, depth)) = queue.pop_front() {
         nodes += 1;
-        if nodes > max_nodes {
+        if nodes > params.scramble_budget {
             break;
         }
 
-        // Track the best (deepest) state found
-        if depth > best_depth {
+        // Track the best (deepest) state found; when the other circles must
+        // matter, only among states where one of them has left its place
+        let candidate = !params.reject_walks
+            || circles
+                .iter()
+                .zip(init_circles)
+                .enumerate()
+                .any(|(i, (a, b))| i != player_idx && a != b);
+        if candidate && depth > best_depth {
             best_depth = depth;
             best_candidates.clear();
         }
-        if depth == best_depth {
+        if candidate && depth == best_depth {
             best_candidates.push((circles.clone(), crosses.clone(), depth));
             // Cap stored candidates to avoid memory bloat
             if best_candidates.len() > 50 {
@@ -187,19 +353,41 @@ fn reverse_bfs(
             }
         }
 
-        // Try all 4 reverse-move directions
-        for &(dr, dc) in &dirs {
+        if depth == max_depth {
+            continue;
+        }
+
+        // Try all 4 reverse-move directions. With the pull rule a forward pull
+        // is undone by a push, so pushes are tried as well.
+        let undo_pulls = board.rules.pull_choices();
+        for (dr, dc, undo_pull) in dirs
+            .iter()
+            .flat_map(|&(dr, dc)| undo_pulls.iter().map(move |&u| (dr, dc, u)))
+        {
             let mut new_circles = circles.clone();
             let mut new_crosses = crosses.clone();
 
-            crate::movement::attempt_move_reverse(
-                &mut new_circles,
-                &mut new_crosses,
-                player_idx,
-                dr,
-                dc,
-                board,
-            );
+            if undo_pull {
+                crate::movement::attempt_move_runtime(
+                    &mut new_circles,
+                    &mut new_crosses,
+                    player_idx,
+                    dr,
+                    dc,
+                    board,
+                );
+            } else if board.rules.unison {
+                crate::unison::reverse(&mut new_circles, &mut new_crosses, dr, dc, board);
+            } else {
+                crate::movement::attempt_move_reverse(
+                    &mut new_circles,
+                    &mut new_crosses,
+                    player_idx,
+                    dr,
+                    dc,
+                    board,
+                );
+            }
 
             // Check if the state actually changed
             let cir_flat_before: Vec<usize> =
@@ -218,6 +406,10 @@ fn reverse_bfs(
             if cir_flat_before == cir_flat_after && crs_flat_before == crs_flat_after {
                 continue;
             }
+            // a cross dropped into a hole can't come back out going forward
+            if crs_flat_after.iter().any(|&p| !in_play(p, board)) {
+                continue;
+            }
 
             // Reject states that cause losing or deadlock conditions
             if check_lose_flat(&crs_flat_after, board) {
@@ -232,7 +424,7 @@ fn reverse_bfs(
                 continue;
             }
 
-            let new_state = ReverseState::new(&new_circles, &new_crosses, board);
+            let new_state = SearchState::new(&new_circles, &new_crosses, board);
             if visited.insert(new_state) {
                 queue.push_back((new_circles, new_crosses, depth + 1));
             }
@@ -270,12 +462,13 @@ fn has_safe_move(
             .map(|&(r, c)| board.to_flat(r, c))
             .collect();
 
-        crate::movement::attempt_move_runtime(
+        crate::movement::apply_move(
             &mut test_circles,
             &mut test_crosses,
             player_idx,
             dr,
             dc,
+            false,
             board,
         );
 
@@ -302,62 +495,640 @@ fn has_safe_move(
     false
 }
 
+/// Whether a position in which the player can move but every move loses is
+/// reachable within `max_depth` moves. Breadth-first and bounded by
+/// `SAFETY_MAX_NODES`; won and lost positions are not explored further.
+fn reaches_forced_loss(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    max_depth: usize,
+) -> bool {
+    let flat = |pieces: &[(usize, usize)]| -> Vec<usize> {
+        pieces.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+    };
+    // the player's cell, then the other pieces in order (pieces of a kind
+    // are interchangeable)
+    let key = |cir: &[(usize, usize)], crs: &[(usize, usize)]| -> Vec<usize> {
+        let mut others = flat(cir);
+        let player = others.remove(player_idx);
+        others.sort_unstable();
+        let mut crosses = flat(crs);
+        crosses.sort_unstable();
+        let mut key = vec![player];
+        key.extend(others);
+        key.push(usize::MAX);
+        key.extend(crosses);
+        key
+    };
+
+    let mut seen: HashSet<Vec<usize>> = HashSet::from([key(circles, crosses)]);
+    let mut queue = VecDeque::from([(circles.to_vec(), crosses.to_vec(), 0)]);
+    while let Some((cir, crs, depth)) = queue.pop_front() {
+        let (mut legal, mut losing) = (0, 0);
+        let mut next = Vec::new();
+        for (_, m) in legal_moves(&cir, &crs, player_idx, 0, board) {
+            legal += 1;
+            if check_lose_flat(&flat(&m.crosses), board) {
+                losing += 1;
+            } else if !is_win_flat(&flat(&m.circles), board) {
+                next.push((m.circles, m.crosses));
+            }
+        }
+        if legal > 0 && losing == legal {
+            log::debug!("forced loss {} moves in", depth);
+            return true;
+        }
+        if depth == max_depth || seen.len() >= SAFETY_MAX_NODES {
+            continue;
+        }
+        for (new_cir, new_crs) in next {
+            if seen.insert(key(&new_cir, &new_crs)) {
+                queue.push_back((new_cir, new_crs, depth + 1));
+            }
+        }
+    }
+    false
+}
+
+/// Player-chosen generation settings, from the Custom difficulty form.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomSettings {
+    pub rows: usize,
+    pub cols: usize,
+    pub hole_percent: usize,
+    /// Blobs the holes grow from, and whether cut-off cells are reconnected
+    /// (see `Carving`)
+    pub blobs: usize,
+    pub corridors: bool,
+    pub ice_percent: usize,
+    pub circles: usize,
+    pub crosses: usize,
+    pub min_moves: usize,
+    /// 0 for no maximum
+    #[serde(default)]
+    pub max_moves: usize,
+}
+
+impl CustomSettings {
+    fn carving(&self) -> Carving {
+        Carving {
+            hole_percent: (self.hole_percent, self.hole_percent),
+            blobs: (self.blobs, self.blobs),
+            corridors: self.corridors,
+        }
+    }
+
+    fn params(&self) -> DifficultyParams {
+        DifficultyParams {
+            crosses: (self.crosses, self.crosses),
+            circles: self.circles,
+            min_score: 0.0,
+            max_score: None,
+            min_moves: self.min_moves,
+            max_moves: (self.max_moves > 0).then_some(self.max_moves),
+            ice_percent: self.ice_percent,
+            ..Difficulty::Hard.params()
+        }
+    }
+}
+
+/// Fixed arenas for players who want a known board. Only the pieces are
+/// generated; the shape never changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Preset {
+    /// The 3x3 grid of the pen-and-paper game
+    Classic,
+    /// 5x5 without holes
+    Open,
+    /// 7x7 ring around a 3x3 hole
+    Donut,
+    /// Three rows by ten columns
+    Corridor,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [
+        Preset::Classic,
+        Preset::Open,
+        Preset::Donut,
+        Preset::Corridor,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Preset::Classic => "Classic 3x3",
+            Preset::Open => "Open 5x5",
+            Preset::Donut => "Donut",
+            Preset::Corridor => "Corridor",
+        }
+    }
+
+    pub fn board(self) -> Board {
+        let (rows, cols, removed) = match self {
+            Preset::Classic => (3, 3, Vec::new()),
+            Preset::Open => (5, 5, Vec::new()),
+            Preset::Donut => {
+                let hole = (2..5).flat_map(|r| (2..5).map(move |c| (r, c))).collect();
+                (7, 7, hole)
+            }
+            Preset::Corridor => (3, 10, Vec::new()),
+        };
+        Board::from_dims(rows, cols, &removed).expect("preset dimensions are valid")
+    }
+
+    // On 3x3 only the centre cell lets a cross move at all, so the classic
+    // grid gets a single one. Any score is accepted: the board can't be
+    // rerolled toward a target.
+    fn params(self) -> DifficultyParams {
+        let crosses = match self {
+            Preset::Classic => (1, 1),
+            Preset::Open => (3, 5),
+            Preset::Donut | Preset::Corridor => (4, 6),
+        };
+        DifficultyParams {
+            crosses,
+            min_score: 0.0,
+            max_score: None,
+            ..Difficulty::Medium.params()
+        }
+    }
+}
+
+/// What the game generates: one of the tiers, a custom board or a preset
+/// arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Setup {
+    Tier(Difficulty),
+    Custom(CustomSettings),
+    Preset(Preset),
+}
+
+impl Setup {
+    pub fn label(&self) -> String {
+        match self {
+            Setup::Tier(d) => format!("{:?}", d),
+            Setup::Custom(c) => format!("Custom {}x{}", c.rows, c.cols),
+            Setup::Preset(p) => p.label().to_string(),
+        }
+    }
+
+    /// Parameters puzzles of this setup are generated and played with.
+    pub fn params(&self) -> DifficultyParams {
+        match self {
+            Setup::Tier(d) => d.params(),
+            Setup::Custom(c) => c.params(),
+            Setup::Preset(p) => p.params(),
+        }
+    }
+}
+
+/// A generated board and puzzle, reproducible from `seed` and the difficulty.
+pub struct GeneratedPuzzle {
+    pub seed: u64,
+    pub board: Board,
+    pub circles: Vec<usize>,
+    pub crosses: Vec<usize>,
+    pub player_idx: usize,
+    /// Optimal solution length, when the solver proved one
+    pub par: Option<usize>,
+    /// What `generate_verified` had to give up to find a puzzle, if anything
+    pub relaxed: Option<&'static str>,
+    /// The seed the board came from, when `seed` only placed the pieces on it
+    /// (see `reroll`)
+    pub board_seed: Option<u64>,
+    /// Metrics of the optimal solution (see `rating`), when the puzzle was
+    /// rated
+    pub rating: Option<Rating>,
+}
+
+/// Generate both the board and the puzzle from a single seeded RNG, so the same
+/// seed, difficulty and rules always give the same puzzle. `circles` is empty
+/// when generation failed.
+pub fn generate_from_seed(seed: u64, difficulty: Difficulty, rules: Rules) -> GeneratedPuzzle {
+    generate_setup_from_seed(seed, Setup::Tier(difficulty), rules)
+}
+
+/// Like `generate_from_seed`, for any `Setup`. With `hardest_of` above one,
+/// that many puzzles are generated on separate threads, from `seed` and
+/// seeds derived from it, and the highest scoring one is returned under
+/// `seed`.
+pub fn generate_setup_from_seed(seed: u64, setup: Setup, rules: Rules) -> GeneratedPuzzle {
+    let carving = &crate::config::get().board;
+    generate_with(seed, setup, &setup.params(), carving, rules)
+}
+
+/// `generate_setup_from_seed` with the parameters and, for the tiers, the
+/// board carving given rather than read from the config.
+fn generate_with(
+    seed: u64,
+    setup: Setup,
+    params: &DifficultyParams,
+    carving: &Carving,
+    rules: Rules,
+) -> GeneratedPuzzle {
+    let n = params.hardest_of;
+    if n <= 1 {
+        return generate_single(seed, setup, params, carving, rules);
+    }
+    let puzzles: Vec<GeneratedPuzzle> = thread::scope(|s| {
+        let handles: Vec<_> = (0..n as u64)
+            .map(|i| {
+                let candidate_seed = seed.wrapping_add(i.wrapping_mul(HARDEST_OF_SEED_STEP));
+                s.spawn(move || generate_single(candidate_seed, setup, params, carving, rules))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap_or_else(|e| std::panic::resume_unwind(e)))
+            .collect()
+    });
+    // the first of the highest scores, so the choice doesn't depend on
+    // thread timing; a failed puzzle only when all failed
+    let mut best: Option<GeneratedPuzzle> = None;
+    for puzzle in puzzles {
+        let better = best.as_ref().is_none_or(|b| {
+            b.circles.is_empty() || (!puzzle.circles.is_empty() && score(&puzzle) > score(b))
+        });
+        if better {
+            best = Some(puzzle);
+        }
+    }
+    let mut best = best.expect("hardest_of is above one");
+    log::info!("seed {}: hardest of {} scores {:.1}", seed, n, score(&best));
+    best.seed = seed;
+    best
+}
+
+/// The puzzle's composite score, 0 when unrated.
+fn score(puzzle: &GeneratedPuzzle) -> f64 {
+    puzzle.rating.map_or(0.0, |r| r.score())
+}
+
+fn generate_single(
+    seed: u64,
+    setup: Setup,
+    params: &DifficultyParams,
+    carving: &Carving,
+    rules: Rules,
+) -> GeneratedPuzzle {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut board = match setup {
+        Setup::Tier(difficulty) => match difficulty.board_size() {
+            Some((rows, cols)) => {
+                let (rows, cols) = (rng.gen_range(rows), rng.gen_range(cols));
+                Board::random_sized_from(rows, cols, carving, &mut rng)
+            }
+            None => Board::random_from(carving, &mut rng),
+        },
+        Setup::Custom(custom) => {
+            Board::random_with_holes_from(custom.rows, custom.cols, &custom.carving(), &mut rng)
+        }
+        Setup::Preset(preset) => preset.board(),
+    };
+    board.rules = rules;
+    // no extra draws without ice or heavy crosses, so those seeds keep their
+    // puzzles
+    if params.ice_percent > 0 {
+        board.scatter_ice_from(params.ice_percent as f64 / 100.0, &mut rng);
+    }
+    if params.heavy_crosses > 0 {
+        place_heavy(&mut board, params.heavy_crosses, &mut rng);
+    }
+    generate_on(seed, board, params, &mut rng)
+}
+
+/// The best puzzle `params` give on `board`; `circles` is empty if none.
+fn generate_on(
+    seed: u64,
+    mut board: Board,
+    params: &DifficultyParams,
+    rng: &mut impl Rng,
+) -> GeneratedPuzzle {
+    // none of the fresh crosses is a neutral block
+    board.blocks = 0;
+    let best = match board.validate() {
+        Ok(()) => {
+            board.rules.rule_set().prepare(&mut board, rng);
+            best_candidate(&board, params, rng)
+        }
+        Err(e) => {
+            log::warn!("seed {}: generated an unusable board: {}", seed, e);
+            None
+        }
+    };
+    let rating = best.as_ref().map(|b| b.rating);
+    let par = rating.map(|r| r.moves);
+    match &best {
+        Some(b) => log::info!(
+            "seed {}: {}x{} board, score {:.1}, par {}, {} pushes, {} cross moves, {} pieces touched",
+            seed,
+            board.rows,
+            board.cols,
+            b.score,
+            b.rating.moves,
+            b.rating.pushes,
+            b.rating.cross_moves,
+            b.rating.pieces_touched
+        ),
+        None => log::info!("seed {}: no puzzle found", seed),
+    }
+    let (circles, crosses, player_idx) = match best {
+        Some(best) => (best.circles, best.crosses, best.player_idx),
+        None => (Vec::new(), Vec::new(), 0),
+    };
+    if params.powerups > 0 && !circles.is_empty() {
+        place_powerups(&mut board, &circles, &crosses, params.powerups, rng);
+    }
+    GeneratedPuzzle {
+        seed,
+        board,
+        circles,
+        crosses,
+        player_idx,
+        par,
+        relaxed: None,
+        board_seed: None,
+        rating,
+    }
+}
+
+// Distance between the seeds of a hardest-of batch
+const HARDEST_OF_SEED_STEP: u64 = 0x2545_f491_4f6c_dd1d;
+// Mixed into the seed for the draws of the relaxed retries, so they don't
+// repeat the attempts that just failed
+const RELAXED_SEED_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+// Retries on the small open board before giving up for good
+const OPEN_BOARD_RETRIES: usize = 20;
+
+/// `params` loosened until nearly any board yields a puzzle: half the
+/// crosses, any solution length and any score, and the other circles may sit
+/// still.
+fn relaxed_params(params: &DifficultyParams) -> DifficultyParams {
+    let (lo, hi) = params.crosses;
+    DifficultyParams {
+        crosses: ((lo / 2).max(1), (hi / 2).max(1)),
+        min_score: 0.0,
+        max_score: None,
+        min_moves: 0,
+        max_moves: None,
+        reject_walks: false,
+        ..*params
+    }
+}
+
+/// Like `generate_setup_from_seed`, but only gives up in the most unlikely
+/// case. If the setup yields no puzzle, the same board is tried again with
+/// relaxed parameters, then a small open board is; whatever comes out was
+/// solved by the solver, and `relaxed` says what had to give. Still
+/// reproducible from the seed.
+pub fn generate_verified(seed: u64, setup: Setup, rules: Rules) -> GeneratedPuzzle {
+    let carving = &crate::config::get().board;
+    verified_with(seed, setup, &setup.params(), carving, rules)
+}
+
+/// The daily puzzle for `day`: Medium, from the built-in parameters and
+/// board carving, so the config can't make it differ between players.
+pub fn generate_daily(day: u64, rules: Rules) -> GeneratedPuzzle {
+    let difficulty = Difficulty::Medium;
+    let params = difficulty.default_params();
+    verified_with(
+        day,
+        Setup::Tier(difficulty),
+        &params,
+        &Carving::default(),
+        rules,
+    )
+}
+
+/// `generate_verified` with the parameters and carving given.
+fn verified_with(
+    seed: u64,
+    setup: Setup,
+    params: &DifficultyParams,
+    carving: &Carving,
+    rules: Rules,
+) -> GeneratedPuzzle {
+    let puzzle = generate_with(seed, setup, params, carving, rules);
+    if !puzzle.circles.is_empty() {
+        return puzzle;
+    }
+    let mut rng = StdRng::seed_from_u64(seed ^ RELAXED_SEED_SALT);
+    let params = relaxed_params(params);
+    log::warn!("seed {}: retrying with relaxed parameters", seed);
+    let mut puzzle = generate_on(seed, puzzle.board, &params, &mut rng);
+    if !puzzle.circles.is_empty() {
+        puzzle.relaxed = Some("fewer crosses, any length");
+        return puzzle;
+    }
+    let mut board = Preset::Open.board();
+    board.rules = rules;
+    log::warn!("seed {}: retrying on a small open board", seed);
+    for _ in 0..OPEN_BOARD_RETRIES {
+        let mut puzzle = generate_on(seed, board.clone(), &params, &mut rng);
+        if !puzzle.circles.is_empty() {
+            puzzle.relaxed = Some("small open board");
+            return puzzle;
+        }
+    }
+    puzzle
+}
+
+/// A new puzzle on `board`, which came from `board_seed`: holes, ice,
+/// portals and goals stay, only the pieces are drawn afresh from `seed`, with
+/// the setup's parameters if they give a puzzle and relaxed ones otherwise.
+/// `circles` is empty when neither does.
+pub fn reroll(seed: u64, board: Board, board_seed: u64, setup: Setup) -> GeneratedPuzzle {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let params = setup.params();
+    let mut puzzle = generate_on(seed, board, &params, &mut rng);
+    if puzzle.circles.is_empty() {
+        log::warn!("seed {}: retrying the reroll with relaxed parameters", seed);
+        puzzle = generate_on(seed, puzzle.board, &relaxed_params(&params), &mut rng);
+        puzzle.relaxed = Some("fewer crosses, any length");
+    }
+    puzzle.board_seed = Some(board_seed);
+    puzzle
+}
+
+/// Circles, crosses (flat indices) and the player's circle for `board`. The
+/// number of crosses is drawn from the difficulty's whole `crosses` range,
+/// not just its lower end; lines and deadlocks are still avoided.
 pub fn generate_puzzle_constructive(
     board: &Board,
     difficulty: Difficulty,
 ) -> (Vec<usize>, Vec<usize>, usize) {
-    let mut rng = thread_rng();
-
-    let triples = enumerate_triples(board);
-    if triples.is_empty() {
-        return (Vec::new(), Vec::new(), 0);
-    }
-
-    // Difficulty parameters:
-    //   cross_range: how many crosses to place
-    //   min_depth: minimum solution depth (reverse BFS depth) to accept
-    //   max_depth: don't accept puzzles deeper than this (keeps difficulty bounded)
-    //   node_budget: BFS exploration budget per iteration
-    //   iterations: how many random configurations to try
-    let (cross_range, min_depth, max_depth, node_budget, iterations) = match difficulty {
-        Difficulty::Easy => ((3usize, 5usize), 3usize, 10usize, 5_000usize, 30usize),
-        Difficulty::Medium => ((4usize, 8usize), 6usize, 25usize, 20_000usize, 25usize),
-        Difficulty::Hard => ((5usize, 10usize), 10usize, 80usize, 50_000usize, 20usize),
-    };
+    generate_puzzle_with_rng(board, difficulty, &mut thread_rng())
+}
+
+pub fn generate_puzzle_with_rng(
+    board: &Board,
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+) -> (Vec<usize>, Vec<usize>, usize) {
+    generate_with_params(board, &difficulty.params(), rng)
+}
+
+pub fn generate_with_params(
+    board: &Board,
+    params: &DifficultyParams,
+    rng: &mut impl Rng,
+) -> (Vec<usize>, Vec<usize>, usize) {
+    match best_candidate(board, params, rng) {
+        Some(best) => (best.circles, best.crosses, best.player_idx),
+        None => (Vec::new(), Vec::new(), 0),
+    }
+}
+
+/// Scramble the winning position `circles`/`crosses` with the reverse search,
+/// at most `max_depth` moves deep, and rate the result. `None` when the
+/// result is unusable: won, lost, deadlocked, without a safe first move,
+/// unsolved by the rater, won without moving another circle (unless allowed)
+/// or able to reach a forced loss.
+fn scramble_and_rate(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    params: &DifficultyParams,
+    max_depth: usize,
+    rng: &mut impl Rng,
+) -> Option<Scrambled> {
+    let (result_circles, result_crosses, _depth) =
+        reverse_bfs(board, circles, crosses, player_idx, params, max_depth, rng);
+
+    let result_cir_flat: Vec<usize> = result_circles
+        .iter()
+        .map(|&(r, c)| board.to_flat(r, c))
+        .collect();
+    let result_crs_flat: Vec<usize> = result_crosses
+        .iter()
+        .map(|&(r, c)| board.to_flat(r, c))
+        .collect();
+    if is_win_flat(&result_cir_flat, board)
+        || check_lose_flat(&result_crs_flat, board)
+        || check_cross_deadlock(&result_crs_flat, board)
+    {
+        return None;
+    }
+
+    // Ensure player has at least one safe move
+    if !has_safe_move(board, &result_circles, &result_crosses, player_idx) {
+        return None;
+    }
+
+    let rating = rate(
+        board,
+        &result_circles,
+        &result_crosses,
+        player_idx,
+        RATING_MAX_NODES,
+    )?;
+    if params.reject_walks && rating.is_walk() {
+        return None;
+    }
+    // No dead ends along the way: within the solution's length, the player
+    // can never get stuck with nothing but losing moves.
+    if reaches_forced_loss(
+        board,
+        &result_circles,
+        &result_crosses,
+        player_idx,
+        rating.moves,
+    ) {
+        return None;
+    }
+    Some((result_circles, result_crosses, rating))
+}
+
+/// The candidate closest to the difficulty's score range, if any attempt
+/// produced a solvable puzzle. Its rating's `moves` come from a complete optimal
+/// search, so a move budget derived from them can always be met.
+fn best_candidate(
+    board: &Board,
+    params: &DifficultyParams,
+    rng: &mut impl Rng,
+) -> Option<Candidate> {
+    // circles can't win on a heavy cross
+    let targets: Vec<Vec<(usize, usize)>> = board
+        .rules
+        .rule_set()
+        .targets(board)
+        .into_iter()
+        .filter(|t| !t.iter().any(|&(r, c)| board.is_heavy(r, c)))
+        .collect();
+    if targets.is_empty() {
+        return None;
+    }
+
+    let (min_score, max_score) = (params.min_score, params.max_score.unwrap_or(f64::INFINITY));
 
-    let mut best_result: Option<(Vec<usize>, Vec<usize>, usize, usize)> = None; // (circles, crosses, player_idx, depth)
+    let mut best_result: Option<Candidate> = None;
+    // attempts that made it to the rating step, for the log
+    let mut rated = 0usize;
 
-    for _ in 0..iterations {
-        // Pick a random winning triple
-        let triple = triples.choose(&mut rng).unwrap();
-        let circles: Vec<(usize, usize)> = triple.clone();
+    for _ in 0..params.attempts {
+        // Pick a random winning placement
+        let target = targets.choose(rng).unwrap();
+        let mut circles: Vec<(usize, usize)> = target.clone();
+
+        // Extra circles start on random free cells; any of them may end up
+        // in the win
+        if params.circles > circles.len() {
+            let free: Vec<(usize, usize)> = (0..board.total_cells)
+                .filter(|&i| board.cells[i] && !board.heavy[i])
+                .map(|i| board.from_flat(i))
+                .filter(|p| !circles.contains(p))
+                .collect();
+            circles.extend(free.choose_multiple(rng, params.circles - circles.len()));
+        }
 
         // Pick a random player index (which circle is the player)
-        let player_idx = rng.gen_range(0..3);
+        let player_idx = rng.gen_range(0..circles.len());
 
         // Pick a random cross count within the range
         let cross_count = rng
-            .gen_range(cross_range.0..=cross_range.1)
-            .min(board.total_cells.saturating_sub(3));
+            .gen_range(params.crosses.0..=params.crosses.1.max(params.crosses.0))
+            .min(board.total_cells.saturating_sub(circles.len()));
 
         // Place crosses
-        let crosses = match place_crosses(board, &circles, cross_count, &mut rng) {
+        let crosses = match place_crosses(board, &circles, cross_count, rng) {
             Some(c) => c,
             None => continue,
         };
 
-        // The circles are currently in the winning position.
-        // Run reverse BFS to find the farthest reachable state.
-        let (result_circles, result_crosses, depth) =
-            reverse_bfs(board, &circles, &crosses, player_idx, node_budget, &mut rng);
-
-        // Filter by difficulty depth range
-        if depth < min_depth {
-            continue;
+        // The circles are currently in the winning position. Scramble them
+        // away and keep the result only if its solution length is wanted.
+        // With a maximum set the scramble goes no deeper than that, and the
+        // depth is corrected by how far the solver's answer was off until
+        // it lands in range.
+        let mut max_depth = params.max_moves.unwrap_or(usize::MAX);
+        let mut scrambled = None;
+        for _ in 0..DEPTH_ROUNDS {
+            let Some((cir, crs, rating)) = scramble_and_rate(
+                board, &circles, &crosses, player_idx, params, max_depth, rng,
+            ) else {
+                break;
+            };
+            let next_depth = match params.max_moves {
+                _ if rating.moves < params.min_moves => {
+                    max_depth.saturating_add(params.min_moves - rating.moves)
+                }
+                Some(max) if rating.moves > max => max_depth - (rating.moves - max).min(max_depth),
+                _ => {
+                    scrambled = Some((cir, crs, rating));
+                    break;
+                }
+            };
+            if params.max_moves.is_none() || next_depth == max_depth {
+                break;
+            }
+            max_depth = next_depth;
         }
-        let effective_depth = depth.min(max_depth);
-
-        // Skip if already won or lost or deadlocked
+        let Some((result_circles, result_crosses, rating)) = scrambled else {
+            continue;
+        };
         let result_cir_flat: Vec<usize> = result_circles
             .iter()
             .map(|&(r, c)| board.to_flat(r, c))
@@ -366,35 +1137,143 @@ pub fn generate_puzzle_constructive(
             .iter()
             .map(|&(r, c)| board.to_flat(r, c))
             .collect();
-        if is_win_flat(&result_cir_flat, board) {
-            continue;
-        }
-        if check_lose_flat(&result_crs_flat, board) {
-            continue;
-        }
-        if check_cross_deadlock(&result_crs_flat, board) {
-            continue;
-        }
-
-        // Ensure player has at least one safe move
-        if !has_safe_move(board, &result_circles, &result_crosses, player_idx) {
-            continue;
-        }
+        // Scores outside the difficulty's range are only kept until
+        // something closer turns up, so small boards that cannot reach the
+        // range still get a puzzle.
+        rated += 1;
+        let score = rating.score();
+        log::debug!(
+            "attempt: {} moves, score {:.1} (target {:.1} to {:.1})",
+            rating.moves,
+            score,
+            min_score,
+            max_score
+        );
+        let distance = if score < min_score {
+            min_score - score
+        } else {
+            (score - max_score).max(0.0)
+        };
 
-        // Keep the best puzzle found so far (highest depth within range)
-        let dominated = match &best_result {
-            Some((_, _, _, best_d)) => effective_depth > *best_d,
+        // Keep the best puzzle so far: closest to the range, then highest score
+        let better = match &best_result {
+            Some(best) => {
+                distance < best.distance || (distance == best.distance && score > best.score)
+            }
             None => true,
         };
-        if dominated {
+        if better {
             let mut crs_sorted = result_crs_flat;
             crs_sorted.sort_unstable();
-            best_result = Some((result_cir_flat, crs_sorted, player_idx, effective_depth));
+            best_result = Some(Candidate {
+                circles: result_cir_flat,
+                crosses: crs_sorted,
+                player_idx,
+                distance,
+                score,
+                rating,
+            });
         }
     }
 
-    match best_result {
-        Some((circles, crosses, player_idx, _)) => (circles, crosses, player_idx),
-        None => (Vec::new(), Vec::new(), 0),
+    log::debug!(
+        "{} of {} attempts gave a usable puzzle",
+        rated,
+        params.attempts
+    );
+    best_result
+}
+
+/// Minimum number of player moves needed to align any three circles, or `None` if
+/// the position is unsolvable or no win was found within `max_nodes` expanded
+/// states. Moves that complete a line of crosses are never part of a solution.
+/// The search itself is IDA* (see `solver`), which keeps memory bounded even
+/// for long Hard solutions.
+pub fn solve_min_moves(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    max_nodes: usize,
+) -> Option<usize> {
+    crate::solver::solve(board, circles, crosses, player_idx, max_nodes).map(|steps| steps.len())
+}
+
+// Solver budget of `verify_puzzle`, plenty for hand-made puzzles
+pub const VERIFY_MAX_NODES: usize = 500_000;
+
+/// What `verify_puzzle` found out about a starting position.
+#[derive(Clone, Debug)]
+pub struct VerifyReport {
+    /// Three crosses are in a line already
+    pub lost: bool,
+    /// Three circles are in a line already
+    pub won: bool,
+    /// Some crosses can never be pushed again (see `check_cross_deadlock`);
+    /// still playable, but those crosses are no more than walls
+    pub deadlocked: bool,
+    /// An optimal solution, when the solver found one
+    pub solution: Option<Vec<Step>>,
+}
+
+impl VerifyReport {
+    pub fn solvable(&self) -> bool {
+        self.solution.is_some()
+    }
+
+    /// Length of the optimal solution.
+    pub fn optimal_moves(&self) -> Option<usize> {
+        self.solution.as_ref().map(Vec::len)
+    }
+
+    /// Why the puzzle can't be played to a win, if it can't.
+    pub fn problem(&self) -> Option<&'static str> {
+        if self.lost {
+            Some("the crosses are already lined up")
+        } else if self.won {
+            Some("the circles have already won")
+        } else if !self.solvable() {
+            Some("the solver found no solution")
+        } else {
+            None
+        }
+    }
+}
+
+/// Check a starting position the way generated puzzles are checked: whether
+/// it is lost or won already, whether crosses are stuck for good, and an
+/// optimal solution. The editor, `validate` and the puzzle browser all go
+/// through this, so a puzzle accepted by one is accepted by all.
+pub fn verify_puzzle(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+) -> VerifyReport {
+    verify_puzzle_within(board, circles, crosses, player_idx, VERIFY_MAX_NODES)
+}
+
+/// `verify_puzzle` with the solver limited to `max_nodes` states.
+pub fn verify_puzzle_within(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    max_nodes: usize,
+) -> VerifyReport {
+    let flat = |pieces: &[(usize, usize)]| -> Vec<usize> {
+        pieces.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+    };
+    let crosses_flat = flat(crosses);
+    let lost = check_lose_flat(&crosses_flat, board);
+    VerifyReport {
+        lost,
+        won: is_win_flat(&flat(circles), board),
+        deadlocked: check_cross_deadlock(&crosses_flat, board),
+        solution: if lost {
+            None
+        } else {
+            crate::solver::solve(board, circles, crosses, player_idx, max_nodes)
+        },
     }
 }
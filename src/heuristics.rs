@@ -0,0 +1,146 @@
+// Admissible lower bounds on the number of moves left to win a position.
+//
+// Every move shifts the player circle by exactly one cell and pushes (or, with
+// the pull rule, pulls) at most one other piece by one cell. So for any target
+// of the rule set (a line, or the goal cells) the player needs at least its
+// own distance in moves (if it is one of the circles filling it), and at least
+// the summed distance of the other circles (each push moves one of them one
+// step). The bound is the larger of the two, minimised over targets and over
+// which circles fill which cell. With chain push one move can shift a
+// whole row of circles, so only the farthest of the other circles counts, and
+// under the unison rule every circle steps at once, so only the farthest of
+// all of them. On
+// boards with ice or portals a single push can carry a circle any distance, so
+// each other circle that still has to move counts as one move instead.
+//
+// Assignments are skipped when they can provably never be completed:
+// - a cell holds a cross that can never be pushed (`rules::frozen_crosses`),
+//   unless the board has power-ups, which may still remove it
+// - a frozen non-player circle would have to move, or sits in the way
+use crate::board::Board;
+use crate::rules::{frozen_crosses, is_immovable};
+
+/// Every ordered choice of `k` distinct circles out of `n`, one per cell of a
+/// target, as consecutive runs of `k` indices.
+fn assignments(n: usize, k: usize) -> Vec<usize> {
+    fn extend(n: usize, k: usize, chosen: &mut Vec<usize>, out: &mut Vec<usize>) {
+        if chosen.len() == k {
+            out.extend_from_slice(chosen);
+            return;
+        }
+        for i in 0..n {
+            if !chosen.contains(&i) {
+                chosen.push(i);
+                extend(n, k, chosen, out);
+                chosen.pop();
+            }
+        }
+    }
+    let mut out = Vec::new();
+    extend(n, k, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Lower bound estimator for one board; caches the board's winning targets.
+pub struct LowerBound<'a> {
+    board: &'a Board,
+    targets: Vec<Vec<(usize, usize)>>,
+    // ice or portals: one push may carry a circle arbitrarily far
+    long_pushes: bool,
+    // power-up tiles: no cross is there for good
+    removals: bool,
+}
+
+impl<'a> LowerBound<'a> {
+    pub fn new(board: &'a Board) -> Self {
+        LowerBound {
+            board,
+            targets: board.rules.rule_set().targets(board),
+            long_pushes: board.has_ice() || board.has_portals(),
+            removals: board.powerups.contains(&true),
+        }
+    }
+
+    /// Minimum number of moves still needed to align the circles, or `None`
+    /// when no winning target can ever be reached from this position.
+    pub fn estimate(
+        &self,
+        circles: &[(usize, usize)],
+        crosses: &[(usize, usize)],
+        player_idx: usize,
+    ) -> Option<usize> {
+        // every target of a rule set has the same size
+        let k = self.targets.first()?.len();
+        if circles.len() < k || player_idx >= circles.len() {
+            return None;
+        }
+        let stuck: Vec<(usize, usize)> = if self.removals {
+            Vec::new()
+        } else {
+            let crosses_flat: Vec<usize> = crosses
+                .iter()
+                .map(|&(r, c)| self.board.to_flat(r, c))
+                .collect();
+            crosses
+                .iter()
+                .zip(frozen_crosses(&crosses_flat, self.board))
+                .filter(|&(_, frozen)| frozen)
+                .map(|(&p, _)| p)
+                .collect()
+        };
+        let frozen_circles: Vec<bool> = circles
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                // a circle that walks by itself is never frozen in place
+                i != player_idx && !self.board.rules.unison && is_immovable(p, self.board)
+            })
+            .collect();
+
+        let choices = assignments(circles.len(), k);
+
+        let mut best: Option<usize> = None;
+        for target in &self.targets {
+            if target.iter().any(|cell| stuck.contains(cell)) {
+                continue;
+            }
+            for chosen in choices.chunks(k) {
+                let mut player_dist = 0usize;
+                let mut others_dist = 0usize;
+                let mut feasible = true;
+                for (j, &i) in chosen.iter().enumerate() {
+                    let d = self.board.distance(circles[i], target[j]);
+                    if i == player_idx {
+                        player_dist = d;
+                    } else if frozen_circles[i] && d > 0 {
+                        feasible = false;
+                        break;
+                    } else {
+                        // what this circle's trip costs at the very least
+                        let d = if self.long_pushes { d.min(1) } else { d };
+                        if self.board.rules.chain_push || self.board.rules.unison {
+                            others_dist = others_dist.max(d);
+                        } else {
+                            others_dist += d;
+                        }
+                    }
+                }
+                // a frozen circle left out of the target still blocks its cell
+                let blocked = target.iter().enumerate().any(|(j, cell)| {
+                    circles
+                        .iter()
+                        .enumerate()
+                        .any(|(i, p)| frozen_circles[i] && p == cell && chosen[j] != i)
+                });
+                if !feasible || blocked {
+                    continue;
+                }
+                let cost = player_dist.max(others_dist);
+                if best.is_none_or(|b| cost < b) {
+                    best = Some(cost);
+                }
+            }
+        }
+        best
+    }
+}
@@ -0,0 +1,168 @@
+// The History screen: every finished puzzle from the journal, newest first,
+// with keys to play one again or watch how it went.
+use std::error::Error;
+
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::app::{self, AppEvent, Screen, Step};
+use crate::cast;
+use crate::game::{self, GameExit};
+use crate::journal::{self, Entry};
+use crate::layout;
+use crate::leaderboard::format_time;
+use crate::term::Tui;
+
+// Rows of the box besides the entries: title, blank lines and the key help
+const CHROME_ROWS: u16 = 6;
+
+enum Choice {
+    Retry(usize),
+    Replay(usize),
+}
+
+struct HistoryScreen {
+    /// Newest first
+    entries: Vec<Entry>,
+    selection: usize,
+    // outcome of the last export, shown above the keys
+    message: Option<String>,
+}
+
+impl HistoryScreen {
+    fn load(&mut self) {
+        self.entries = journal::load();
+        self.entries.reverse();
+        self.selection = self.selection.min(self.entries.len().saturating_sub(1));
+    }
+}
+
+/// One line about `entry`: when, what, how it went.
+pub fn entry_line(entry: &Entry) -> String {
+    let result = match entry.score {
+        Some(score) => format!("won   score {}", score),
+        None if entry.won => "won".to_string(),
+        None => "lost".to_string(),
+    };
+    format!(
+        "{}  {:<16} {:>3} moves  {:>5}  {}",
+        journal::date(entry.finished_at),
+        entry.label(),
+        entry.moves,
+        format_time(entry.seconds),
+        result
+    )
+}
+
+impl Screen for HistoryScreen {
+    type Output = Option<Choice>;
+
+    fn update(&mut self, event: AppEvent) -> Step<Option<Choice>> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Step::Done(None),
+            KeyCode::Up | KeyCode::Char('w') => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('s') => {
+                self.selection = (self.selection + 1).min(self.entries.len().saturating_sub(1))
+            }
+            KeyCode::Enter | KeyCode::Char('r') if !self.entries.is_empty() => {
+                return Step::Done(Some(Choice::Retry(self.selection)));
+            }
+            KeyCode::Char('p') if !self.entries.is_empty() => {
+                return Step::Done(Some(Choice::Replay(self.selection)));
+            }
+            KeyCode::Char('e') if !self.entries.is_empty() => {
+                self.message = Some(match cast::export(&self.entries[self.selection]) {
+                    Ok(path) => format!("saved {}", path.display()),
+                    Err(e) => format!("export failed: {}", e),
+                });
+            }
+            _ => {}
+        }
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let overlay_w = std::cmp::min(76, size.width.saturating_sub(4));
+        let overlay_h =
+            (self.entries.len().max(1) as u16 + CHROME_ROWS).min(size.height.saturating_sub(2));
+        let area = layout::centered(size, overlay_w, overlay_h);
+
+        let mut lines: Vec<Spans> = vec![
+            Spans::from(Span::styled(
+                " History ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::raw("")),
+        ];
+        if self.entries.is_empty() {
+            lines.push(Spans::from(Span::raw("No finished puzzles yet.")));
+        }
+        // keep the selection in view when the list is longer than the box
+        let visible = overlay_h.saturating_sub(CHROME_ROWS).max(1) as usize;
+        let first = self.selection.saturating_sub(visible - 1);
+        for (i, entry) in self.entries.iter().enumerate().skip(first).take(visible) {
+            if i == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {}", entry_line(entry)),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}", entry_line(entry)))));
+            }
+        }
+        lines.push(Spans::from(Span::raw(
+            self.message.clone().unwrap_or_default(),
+        )));
+        lines.push(Spans::from(Span::raw(
+            "↑/↓ select   Enter/r: retry   p: replay   e: export   Esc: back",
+        )));
+
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// Show the History screen until the player goes back to the menu or quits
+/// from a retried puzzle.
+pub fn show_history(terminal: &mut Tui) -> Result<GameExit, Box<dyn Error>> {
+    let mut screen = HistoryScreen {
+        entries: Vec::new(),
+        selection: 0,
+        message: None,
+    };
+    screen.load();
+    loop {
+        match app::run(terminal, &mut screen)? {
+            None => return Ok(GameExit::Menu),
+            Some(Choice::Retry(i)) => {
+                if let GameExit::Quit = game::retry_entry(terminal, &screen.entries[i])? {
+                    return Ok(GameExit::Quit);
+                }
+                // the retry may have added entries; the same one stays selected
+                let before = screen.entries.len();
+                screen.load();
+                screen.selection += screen.entries.len().saturating_sub(before);
+            }
+            Some(Choice::Replay(i)) => game::replay(terminal, &screen.entries[i])?,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// Terminal input, read off the terminal as it arrives.
+//
+// The first call to `next` starts a thread that awaits crossterm's
+// `EventStream` on a single-threaded tokio runtime and forwards every event
+// into a channel. Input is taken off the terminal while a frame is drawn or a
+// puzzle generated, and whoever waits for it (the frame clock in `tick`, the
+// browser and editor loops) wakes up the moment it arrives or its deadline
+// passes, instead of sleeping through a fixed poll interval.
+//
+// Everything in the game reads input through here; reading the terminal
+// directly as well would race the thread for events.
+//
+// Terminals that speak the kitty keyboard protocol also say when a key is let
+// go (`term::enter` asks them to). There a held arrow key repeats at the rate
+// in the config's `key_repeat` rather than the keyboard's own, and stops the
+// moment it is released; the terminal's repeats and releases are not passed
+// on. Elsewhere held keys repeat as the terminal sends them.
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+
+static INPUT: OnceLock<Mutex<Input>> = OnceLock::new();
+static RELEASES: AtomicBool = AtomicBool::new(false);
+
+/// How a held arrow key repeats where releases are reported.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyRepeat {
+    /// Time from the press to the first repeat
+    pub delay_ms: u64,
+    /// Repeats per second after that
+    pub per_second: u32,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        KeyRepeat {
+            delay_ms: 250,
+            per_second: 12,
+        }
+    }
+}
+
+impl KeyRepeat {
+    fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms)
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs(1) / self.per_second.max(1)
+    }
+}
+
+struct Input {
+    rx: Receiver<io::Result<Event>>,
+    /// The arrow key held down and when it repeats next
+    held: Option<(KeyEvent, Instant)>,
+}
+
+/// Say whether the terminal reports key releases, which held keys repeating
+/// at our own rate relies on.
+pub fn set_releases(on: bool) {
+    RELEASES.store(on, Ordering::Relaxed);
+}
+
+pub fn releases() -> bool {
+    RELEASES.load(Ordering::Relaxed)
+}
+
+/// Forward terminal events into `tx` until the terminal or the channel closes.
+fn read_events(tx: mpsc::Sender<io::Result<Event>>) {
+    let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            let _ = tx.send(Err(e));
+            return;
+        }
+    };
+    runtime.block_on(async {
+        let mut stream = EventStream::new();
+        while let Some(event) = stream.next().await {
+            let failed = event.is_err();
+            if tx.send(event).is_err() || failed {
+                return;
+            }
+        }
+    });
+}
+
+fn input() -> &'static Mutex<Input> {
+    INPUT.get_or_init(|| {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || read_events(tx));
+        Mutex::new(Input { rx, held: None })
+    })
+}
+
+fn is_arrow(code: KeyCode) -> bool {
+    matches!(
+        code,
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+    )
+}
+
+impl Input {
+    /// The event to hand on for `event` from the terminal, if any, keeping
+    /// track of the held arrow key.
+    fn filter(&mut self, event: Event, now: Instant) -> Option<Event> {
+        let key = match event {
+            Event::Key(key) if releases() => key,
+            Event::FocusLost => {
+                // the release would go to another window
+                self.held = None;
+                return Some(event);
+            }
+            _ => return Some(event),
+        };
+        let repeat = &crate::config::get().key_repeat;
+        let held = self.held.map(|(held, _)| held.code);
+        match key.kind {
+            KeyEventKind::Press => {
+                self.held = is_arrow(key.code).then(|| (key, now + repeat.delay()));
+                Some(Event::Key(key))
+            }
+            KeyEventKind::Repeat if held == Some(key.code) => None,
+            KeyEventKind::Repeat => Some(Event::Key(KeyEvent {
+                kind: KeyEventKind::Press,
+                ..key
+            })),
+            KeyEventKind::Release => {
+                if held == Some(key.code) {
+                    self.held = None;
+                }
+                None
+            }
+        }
+    }
+}
+
+/// The next terminal event, or `None` when none arrives within `timeout`.
+pub fn next(timeout: Duration) -> io::Result<Option<Event>> {
+    let mut input = input().lock().unwrap_or_else(|e| e.into_inner());
+    let deadline = Instant::now() + timeout;
+    loop {
+        let now = Instant::now();
+        if let Some((key, due)) = input.held
+            && due <= now
+        {
+            let interval = crate::config::get().key_repeat.interval();
+            input.held = Some((key, (due + interval).max(now)));
+            return Ok(Some(Event::Key(key)));
+        }
+        let wake = match input.held {
+            Some((_, due)) => due.min(deadline),
+            None => deadline,
+        };
+        match input.rx.recv_timeout(wake.saturating_duration_since(now)) {
+            Ok(event) => {
+                if let Some(event) = input.filter(event?, now) {
+                    return Ok(Some(event));
+                }
+            }
+            Err(RecvTimeoutError::Timeout) if wake >= deadline => return Ok(None),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "terminal input closed",
+                ));
+            }
+        }
+    }
+}
+
+/// Every event waiting to be handled, after waiting up to `timeout` for the
+/// first one. Loops that redraw after each batch stay in step with fast typing
+/// and key repeat instead of drawing a frame per key.
+pub fn drain(timeout: Duration) -> io::Result<Vec<Event>> {
+    let mut events: Vec<Event> = next(timeout)?.into_iter().collect();
+    while !events.is_empty()
+        && let Some(event) = next(Duration::ZERO)?
+    {
+        events.push(event);
+    }
+    Ok(events)
+}
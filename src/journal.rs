@@ -0,0 +1,103 @@
+// A record of every finished generated puzzle, for the History screen.
+//
+// Each won or lost puzzle is appended to `tic-tac-go-journal.jsonl` in the
+// working directory as one JSON line: when it finished, its setup and seed,
+// the result, and the board with every position the game went through, so
+// any entry can be retried or replayed later without regenerating it.
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Position;
+use crate::generator::Setup;
+use crate::puzzle::PuzzleFile;
+
+pub const JOURNAL_FILE: &str = "tic-tac-go-journal.jsonl";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    /// Seconds since the epoch
+    pub finished_at: u64,
+    pub setup: Setup,
+    /// The day, for a daily puzzle
+    pub daily: Option<u64>,
+    pub seed: u64,
+    pub par: Option<usize>,
+    pub won: bool,
+    pub moves: usize,
+    pub seconds: f64,
+    pub score: Option<u32>,
+    /// Board, rules and pieces as the puzzle started
+    pub puzzle: PuzzleFile,
+    /// The starting position, then the position after each move that was
+    /// kept (undone moves are gone)
+    pub positions: Vec<Position>,
+}
+
+impl Entry {
+    /// What kind of puzzle this was, as listed on the History screen.
+    pub fn label(&self) -> String {
+        match self.daily {
+            Some(_) => "Daily puzzle".to_string(),
+            None => self.setup.label(),
+        }
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// The UTC date of `secs` since the epoch, as YYYY-MM-DD.
+pub fn date(secs: u64) -> String {
+    // days to a civil date, after Howard Hinnant's `civil_from_days`
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn try_append(entry: &Entry) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(JOURNAL_FILE)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Add a finished puzzle to the journal.
+pub fn append(entry: &Entry) {
+    if let Err(e) = try_append(entry) {
+        log::warn!("could not write {}: {}", JOURNAL_FILE, e);
+    }
+}
+
+/// Every entry of the journal, oldest first. Lines that don't parse are
+/// logged and skipped.
+pub fn load() -> Vec<Entry> {
+    let Ok(contents) = fs::read_to_string(JOURNAL_FILE) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| log::warn!("{} line {}: {}", JOURNAL_FILE, i + 1, e))
+                .ok()
+        })
+        .collect()
+}
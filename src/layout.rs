@@ -0,0 +1,150 @@
+// Where things go on screen, worked out with ratatui's `Layout` so every
+// screen splits the terminal the same way and picks up a resize on its next
+// frame. Sizes larger than the area are cut down to fit it.
+use std::rc::Rc;
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+fn split(area: Rect, direction: Direction, constraints: [Constraint; 3]) -> Rc<[Rect]> {
+    Layout::default()
+        .direction(direction)
+        .constraints(constraints)
+        .split(area)
+}
+
+/// A `width` x `height` box in the middle of `area`, e.g. for a menu or
+/// the end-of-game message.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let (width, height) = (width.min(area.width), height.min(area.height));
+    let rows = split(
+        area,
+        Direction::Vertical,
+        [
+            Constraint::Length((area.height - height) / 2),
+            Constraint::Length(height),
+            Constraint::Min(0),
+        ],
+    );
+    split(
+        rows[1],
+        Direction::Horizontal,
+        [
+            Constraint::Length((area.width - width) / 2),
+            Constraint::Length(width),
+            Constraint::Min(0),
+        ],
+    )[1]
+}
+
+/// The top `height` rows of `area`.
+pub fn top(area: Rect, height: u16) -> Rect {
+    let height = height.min(area.height);
+    split(
+        area,
+        Direction::Vertical,
+        [
+            Constraint::Length(height),
+            Constraint::Min(0),
+            Constraint::Length(0),
+        ],
+    )[0]
+}
+
+/// The bottom `height` rows of `area`.
+pub fn bottom(area: Rect, height: u16) -> Rect {
+    let height = height.min(area.height);
+    split(
+        area,
+        Direction::Vertical,
+        [
+            Constraint::Length(0),
+            Constraint::Min(0),
+            Constraint::Length(height),
+        ],
+    )[2]
+}
+
+/// The leftmost `width` columns of `area`.
+pub fn left(area: Rect, width: u16) -> Rect {
+    let width = width.min(area.width);
+    split(
+        area,
+        Direction::Horizontal,
+        [
+            Constraint::Length(width),
+            Constraint::Min(0),
+            Constraint::Length(0),
+        ],
+    )[0]
+}
+
+/// The rightmost `width` columns of `area`.
+pub fn right(area: Rect, width: u16) -> Rect {
+    let width = width.min(area.width);
+    split(
+        area,
+        Direction::Horizontal,
+        [
+            Constraint::Length(0),
+            Constraint::Min(0),
+            Constraint::Length(width),
+        ],
+    )[2]
+}
+
+/// The parts of a play screen.
+pub struct PlayAreas {
+    /// Row just above the board, for "blocked" toasts
+    pub toast: Rect,
+    /// The visible part of the board's grid
+    pub board: Rect,
+    /// Row just below the board: difficulty, moves left, race progress
+    pub hud: Rect,
+    /// Bottom row, empty when the screen has no status bar
+    pub status: Rect,
+}
+
+/// Split `area` for a board whose full grid is `grid` (columns, rows): the
+/// board is centered with a row for the toast above it and one for the HUD
+/// below it, and shrinks to what is left of the terminal otherwise (it
+/// scrolls then, see `game::camera`).
+pub fn play(area: Rect, grid: (u16, u16), status_bar: bool) -> PlayAreas {
+    let rows = split(
+        area,
+        Direction::Vertical,
+        [
+            Constraint::Min(0),
+            Constraint::Length(u16::from(status_bar).min(area.height)),
+            Constraint::Length(0),
+        ],
+    );
+    let (body, status) = (rows[0], rows[1]);
+    // one column stays free on either side of the board
+    let grid_w = grid.0.min(body.width.saturating_sub(2));
+    let grid_h = grid.1.min(body.height.saturating_sub(2));
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(body.height.saturating_sub(grid_h + 2) / 2),
+            Constraint::Length(1),
+            Constraint::Length(grid_h),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(body);
+    let columns = split(
+        rows[2],
+        Direction::Horizontal,
+        [
+            Constraint::Length((body.width - grid_w) / 2),
+            Constraint::Length(grid_w),
+            Constraint::Min(0),
+        ],
+    );
+    PlayAreas {
+        toast: rows[1],
+        board: columns[1],
+        hud: rows[3],
+        status,
+    }
+}
@@ -0,0 +1,154 @@
+// Daily puzzle results shared online, for players who opt in.
+//
+// With `online.enabled` set in the config, a solved daily puzzle is sent to
+// `online.url` and the day's best times are fetched back for the results
+// screen. The server is anything that speaks this small protocol:
+//
+//   POST {url}/daily/{day}   {"name", "seed", "hash", "moves", "seconds", "score"}
+//   GET  {url}/daily/{day}   [{"name", "moves", "seconds"}, ...]
+//
+// where `day` counts days since 1970-01-01 (UTC) and is also the seed of the
+// day's puzzle, and `hash` is the puzzle's canonical hash, so the server can
+// turn away results for a different board. Nothing is sent unless the player turned it on.
+use std::error::Error;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::blocking::Client;
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::{Deserialize, Serialize};
+
+// Requests give up after this long, so a dead server only delays the list
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Entries shown on the results screen
+const TOP_TIMES: usize = 5;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The `online` section of the config.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OnlineScores {
+    /// Send daily puzzle results and fetch the day's top times
+    pub enabled: bool,
+    /// Base URL of the score server
+    pub url: String,
+    /// Name shown next to your times; "anonymous" when empty
+    pub name: String,
+}
+
+/// A solved daily puzzle.
+#[derive(Clone, Debug, Serialize)]
+pub struct Submission {
+    pub name: String,
+    pub seed: u64,
+    /// Canonical hash of the puzzle played (see `PuzzleFile::canonical_hash`)
+    pub hash: u64,
+    pub moves: usize,
+    pub seconds: f64,
+    pub score: u32,
+}
+
+/// One line of the day's top times.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub moves: usize,
+    pub seconds: f64,
+}
+
+/// What became of the submission: the day's top times, or why there are none.
+pub type TopTimes = Result<Vec<Entry>, String>;
+
+/// Today's date as days since the epoch, which is also the daily seed.
+pub fn today() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    now.as_secs() / SECONDS_PER_DAY
+}
+
+/// `seconds` as m:ss.
+pub fn format_time(seconds: f64) -> String {
+    let s = seconds.round() as u64;
+    format!("{}:{:02}", s / 60, s % 60)
+}
+
+fn daily_url(online: &OnlineScores, day: u64) -> Result<String, Box<dyn Error>> {
+    if online.url.is_empty() {
+        return Err("online.url is not set".into());
+    }
+    Ok(format!(
+        "{}/daily/{}",
+        online.url.trim_end_matches('/'),
+        day
+    ))
+}
+
+fn submit(client: &Client, url: &str, result: &Submission) -> Result<(), Box<dyn Error>> {
+    let resp = client
+        .post(url)
+        .header(USER_AGENT, "tic-tac-go")
+        .json(result)
+        .send()?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().unwrap_or_default();
+        return Err(format!("score submission failed: {} - {}", status, text).into());
+    }
+    Ok(())
+}
+
+fn fetch(client: &Client, url: &str) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let resp = client
+        .get(url)
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, "tic-tac-go")
+        .send()?;
+    let status = resp.status();
+    if !status.is_success() {
+        let text = resp.text().unwrap_or_default();
+        return Err(format!("top times failed: {} - {}", status, text).into());
+    }
+    let mut entries: Vec<Entry> = resp.json()?;
+    entries.sort_by(|a, b| a.seconds.total_cmp(&b.seconds));
+    entries.truncate(TOP_TIMES);
+    Ok(entries)
+}
+
+fn submit_and_fetch(
+    online: &OnlineScores,
+    day: u64,
+    result: Option<&Submission>,
+) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let url = daily_url(online, day)?;
+    let client = Client::builder().timeout(TIMEOUT).build()?;
+    if let Some(result) = result {
+        submit(&client, &url, result)?;
+    }
+    fetch(&client, &url)
+}
+
+/// Send `result` for `day`, if there is one, and fetch the day's top times
+/// on a background thread, so the results screen stays responsive.
+pub fn spawn_submit(
+    online: &OnlineScores,
+    day: u64,
+    mut result: Option<Submission>,
+) -> mpsc::Receiver<TopTimes> {
+    let online = online.clone();
+    if let Some(result) = result.as_mut()
+        && result.name.is_empty()
+    {
+        result.name = "anonymous".to_string();
+    }
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let top = submit_and_fetch(&online, day, result.as_ref()).map_err(|e| {
+            log::warn!("daily puzzle {}: {}", day, e);
+            e.to_string()
+        });
+        let _ = tx.send(top);
+    });
+    rx
+}
@@ -0,0 +1,88 @@
+// Debug log for the interactive game, written to `tic-tac-go.log` in the
+// working directory.
+//
+// Modules log through the `log` macros: generation attempts, solver searches
+// and the player's moves. Besides the file, the newest lines are kept in
+// memory for the overlay that F12 toggles in the game. Nothing is recorded
+// unless `init` ran, so the command line tools stay quiet.
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+pub const LOG_FILE: &str = "tic-tac-go.log";
+// Lines kept for the in-game overlay
+const RECENT_LINES: usize = 64;
+
+static LOGGER: OnceLock<FileLogger> = OnceLock::new();
+
+struct FileLogger {
+    file: Mutex<File>,
+    recent: Mutex<VecDeque<String>>,
+    start: Instant,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let target = record.target();
+        let module = target.strip_prefix("tic_tac_go::").unwrap_or(target);
+        let line = format!(
+            "{:>8.3} {:<5} {}: {}",
+            self.start.elapsed().as_secs_f64(),
+            record.level(),
+            module,
+            record.args()
+        );
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{}", line);
+        }
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_LINES {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Start logging to `LOG_FILE`, replacing the previous session's log.
+pub fn init() -> Result<(), Box<dyn Error>> {
+    let logger = FileLogger {
+        file: Mutex::new(File::create(LOG_FILE)?),
+        recent: Mutex::new(VecDeque::new()),
+        start: Instant::now(),
+    };
+    let logger = LOGGER.get_or_init(|| logger);
+    log::set_logger(logger).map_err(|e| e.to_string())?;
+    log::set_max_level(LevelFilter::Debug);
+    Ok(())
+}
+
+/// The newest `n` log lines, oldest first.
+pub fn recent(n: usize) -> Vec<String> {
+    let Some(logger) = LOGGER.get() else {
+        return Vec::new();
+    };
+    let Ok(recent) = logger.recent.lock() else {
+        return Vec::new();
+    };
+    recent
+        .iter()
+        .skip(recent.len().saturating_sub(n))
+        .cloned()
+        .collect()
+}
@@ -1,75 +1,137 @@
 use std::error::Error;
-use std::io::{self};
-
-use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
-use crossterm::execute;
-use crossterm::terminal::{
-    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
-};
-
-use ratatui::Terminal;
-use ratatui::backend::CrosstermBackend;
 
+mod ai;
+mod announce;
+mod app;
+mod autosave;
+mod bench;
 mod board;
 mod browser;
+mod bundled;
+mod cast;
+mod clipboard;
+mod config;
 mod database;
 mod game;
+mod generate;
 mod generator;
+mod heuristics;
+mod history;
+mod input;
+mod journal;
+mod layout;
+mod leaderboard;
+mod logging;
 mod movement;
+mod pack;
+mod plain;
+mod prefs;
+mod puzzle;
 mod puzzle_editor;
+mod race;
+mod rate;
+mod rating;
 mod rules;
+mod score;
+mod settings;
+mod sim;
 mod solver;
+mod sound;
+mod state;
+mod term;
+mod tick;
+mod title;
+mod unison;
+mod validate;
+mod weekly;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Setup terminal
-
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+fn print_usage() {
+    eprintln!("usage: tic-tac-go [--config PATH] [COMMAND]");
+    eprintln!();
+    eprintln!("Without a command the interactive game is started. --config reads");
+    eprintln!("difficulty settings from PATH instead of tic-tac-go.json.");
+    eprintln!();
+    eprintln!("Commands:");
+    eprintln!("  validate [DIR] [--json]   check a directory of puzzle files");
+    eprintln!("  generate [--count N]      write a batch of unique puzzles");
+    eprintln!("  rate FILE... [--json]     score puzzle difficulty");
+    eprintln!("  bench [--iterations N]    time board generation and the solver");
+    eprintln!("  bundle [--count N]        rebuild the puzzle database built into the game");
+    eprintln!("  race host|join            race another player over the network");
+    eprintln!("  sim FILE...               play ASCII test cases and check their outcome");
+    eprintln!("  --script [MOVES]          play a move string headlessly and print the result");
+    eprintln!("  --plain                   play in line mode, without the full-screen interface");
+    eprintln!("  --quick [LEVEL]           start a puzzle right away, skipping the menus");
+    eprintln!("  config                    print the effective difficulty settings");
+    eprintln!("  stats                     print your puzzles played, solved and total score");
+}
 
-    // Show main menu to select mode (play or create). If user quits, exit gracefully.
-    let res = match game::select_mode(&mut terminal) {
-        Ok(game::StartupMode::Play(d)) => game::run_app(&mut terminal, d),
-        Ok(game::StartupMode::Create) => {
-            // show placeholder for create puzzle, then restore and exit
-            puzzle_editor::show_create_placeholder(&mut terminal)?;
-            disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
-            terminal.show_cursor()?;
-            Ok(())
+fn main() -> Result<(), Box<dyn Error>> {
+    // Command line subcommands run without touching the terminal
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut config_path = None;
+    if args.first().map(String::as_str) == Some("--config") {
+        if args.len() < 2 {
+            print_usage();
+            std::process::exit(2);
         }
-        Ok(game::StartupMode::Browse) => {
-            browser::show_browser(&mut terminal)?;
-            Ok(())
+        config_path = Some(std::path::PathBuf::from(args.remove(1)));
+        args.remove(0);
+    }
+    let config = config::load(config_path.as_deref())?;
+    sound::set_enabled(config.sound);
+
+    // `--quick` goes on to the game below, with a puzzle instead of the menus
+    let mut quick = None;
+    if let Some(cmd) = args.first() {
+        // `generate` layers its own overrides on top before installing it,
+        // and `bundle` uses the built-in settings
+        if cmd != "generate" && cmd != "bundle" {
+            config::init(config.clone());
         }
-        Err(_) => {
-            // Restore terminal and exit without running the game
-            disable_raw_mode()?;
-            execute!(
-                terminal.backend_mut(),
-                LeaveAlternateScreen,
-                DisableMouseCapture
-            )?;
-            terminal.show_cursor()?;
-            return Ok(());
+        match cmd.as_str() {
+            "--quick" => quick = Some(game::quick_setup(&args[1..])?),
+            "validate" => {
+                let all_ok = validate::run(&args[1..])?;
+                std::process::exit(if all_ok { 0 } else { 1 });
+            }
+            "--script" => return sim::script(&args[1..]),
+            "--plain" => return plain::run(&args[1..]),
+            "sim" => {
+                let all_ok = sim::run(&args[1..])?;
+                std::process::exit(if all_ok { 0 } else { 1 });
+            }
+            "generate" => return generate::run(&args[1..], config),
+            "rate" => return rate::run(&args[1..]),
+            "bench" => return bench::run(&args[1..]),
+            "bundle" => return bundled::run(&args[1..]),
+            "race" => return race::run(&args[1..]),
+            "config" => return config::run(&args[1..]),
+            "stats" => return score::run(&args[1..]),
+            "-h" | "--help" | "help" => {
+                print_usage();
+                return Ok(());
+            }
+            other => {
+                eprintln!("unknown command: {}", other);
+                print_usage();
+                std::process::exit(2);
+            }
         }
-    };
+    }
+
+    config::init(config);
+
+    // the game works the same without a log, e.g. in a read-only directory
+    let _ = logging::init();
+
+    // Setup terminal; the guard restores it however we leave, panics included
+    let (mut terminal, guard) = term::enter()?;
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    let res = game::run_screens(&mut terminal, quick);
 
+    // Restore terminal before reporting errors
+    drop(guard);
     if let Err(err) = res {
         eprintln!("Error: {}", err);
     }
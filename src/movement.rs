@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::board::Board;
 
 // Helper query functions
@@ -19,7 +21,8 @@ fn occupied_any(
 }
 
 /// Attempt to move the player at `player_idx` by (dr, dc) in the runtime (forward) direction.
-/// If the destination contains a movable object (circle or cross), attempt to push it one cell.
+/// If the destination contains a movable object (circle or cross), attempt to push it one cell;
+/// a piece pushed onto ice keeps sliding (see `slide`).
 pub fn attempt_move_runtime(
     circles: &mut [(usize, usize)],
     crosses: &mut [(usize, usize)],
@@ -29,8 +32,10 @@ pub fn attempt_move_runtime(
     board: &Board,
 ) {
     let (player_row, player_column) = circles[player_idx];
-    let destination_row_i = player_row as isize + direction_row;
-    let destination_column_i = player_column as isize + direction_column;
+    let (destination_row_i, destination_column_i) = board.wrap(
+        player_row as isize + direction_row,
+        player_column as isize + direction_column,
+    );
 
     // destination must be within board bounds and present
     if destination_row_i < 0 || destination_column_i < 0 {
@@ -42,11 +47,27 @@ pub fn attempt_move_runtime(
         return;
     }
 
+    // Chain-push variant: a whole line of pieces moves together
+    if board.rules.chain_push && occupied_any(circles, crosses, destination_row, destination_column)
+    {
+        push_line(
+            circles,
+            crosses,
+            player_idx,
+            direction_row,
+            direction_column,
+            board,
+        );
+        return;
+    }
+
     // If destination occupied by another circle, try to push that circle one step further
     if let Some(other_circle_idx) = find_circle_index(circles, destination_row, destination_column)
     {
-        let push_row_i = destination_row_i + direction_row;
-        let push_column_i = destination_column_i + direction_column;
+        let (push_row_i, push_column_i) = board.wrap(
+            destination_row_i + direction_row,
+            destination_column_i + direction_column,
+        );
         if push_row_i < 0 || push_column_i < 0 {
             return;
         }
@@ -62,13 +83,31 @@ pub fn attempt_move_runtime(
         // perform push
         circles[other_circle_idx] = (push_row, push_column);
         circles[player_idx] = (destination_row, destination_column);
+        slide(
+            circles,
+            crosses,
+            (push_row, push_column),
+            direction_row,
+            direction_column,
+            board,
+        );
         return;
     }
 
     // If destination occupied by a cross, try to push the cross one step further
     if let Some(cross_idx) = find_cross_index(crosses, destination_row, destination_column) {
-        let push_row_i = destination_row_i + direction_row;
-        let push_column_i = destination_column_i + direction_column;
+        if board.is_heavy(destination_row, destination_column) {
+            return;
+        }
+        let (push_row_i, push_column_i) = board.wrap(
+            destination_row_i + direction_row,
+            destination_column_i + direction_column,
+        );
+        if swallows(push_row_i, push_column_i, board) {
+            crosses[cross_idx] = (push_row_i as usize, push_column_i as usize);
+            circles[player_idx] = (destination_row, destination_column);
+            return;
+        }
         if push_row_i < 0 || push_column_i < 0 {
             return;
         }
@@ -84,6 +123,14 @@ pub fn attempt_move_runtime(
         // perform push
         crosses[cross_idx] = (push_row, push_column);
         circles[player_idx] = (destination_row, destination_column);
+        slide(
+            circles,
+            crosses,
+            (push_row, push_column),
+            direction_row,
+            direction_column,
+            board,
+        );
         return;
     }
 
@@ -91,8 +138,347 @@ pub fn attempt_move_runtime(
     circles[player_idx] = (destination_row, destination_column);
 }
 
+/// The power-up's move: take the cross next to the player in direction
+/// (dr, dc) off the board. Heavy crosses and neutral blocks stay where they
+/// are. Whether a cross was removed.
+pub fn remove_cross(
+    circles: &[(usize, usize)],
+    crosses: &mut Vec<(usize, usize)>,
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) -> bool {
+    let (r, c) = step(circles[player_idx], dr, dc, board);
+    if !board.has_cell(r, c) || board.is_heavy(r as usize, c as usize) {
+        return false;
+    }
+    let line_crosses = board.line_crosses(crosses).len();
+    match find_cross_index(&crosses[..line_crosses], r as usize, c as usize) {
+        Some(idx) => {
+            crosses.remove(idx);
+            true
+        }
+        None => false,
+    }
+}
+
+// Power-up tiles a game keeps track of (see `PowerUps::taken`)
+pub const MAX_POWERUPS: usize = 64;
+
+/// The power-ups of a game in progress: the tiles picked up so far, one bit
+/// per tile in `Board::powerup_cells` order, and how many were spent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PowerUps {
+    pub taken: u64,
+    pub used: usize,
+}
+
+impl PowerUps {
+    /// The power-ups at the start of a game: the tile under the player, if
+    /// any, is taken from the first move on.
+    pub fn start(board: &Board, player: (usize, usize)) -> Self {
+        let mut powerups = PowerUps::default();
+        powerups.pick_up(board, player);
+        powerups
+    }
+
+    fn tile(board: &Board, (r, c): (usize, usize)) -> Option<usize> {
+        if !board.powerups[board.to_flat(r, c)] {
+            return None;
+        }
+        let i = board.powerup_cells().iter().position(|&p| p == (r, c))?;
+        (i < MAX_POWERUPS).then_some(i)
+    }
+
+    /// Take the tile at `cell`, if there is one.
+    pub fn pick_up(&mut self, board: &Board, cell: (usize, usize)) {
+        if let Some(i) = Self::tile(board, cell) {
+            self.taken |= 1 << i;
+        }
+    }
+
+    pub fn is_taken(&self, board: &Board, cell: (usize, usize)) -> bool {
+        Self::tile(board, cell).is_some_and(|i| self.taken & (1 << i) != 0)
+    }
+
+    /// Power-ups in hand.
+    pub fn held(&self) -> usize {
+        (self.taken.count_ones() as usize).saturating_sub(self.used)
+    }
+}
+
+/// Why a forward move would leave the board unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blocked {
+    /// The player would walk off the board, into a hole or into a wall, or
+    /// push a piece off the board or into a wall
+    Obstacle,
+    /// The pushed piece would land in a hole
+    PushIntoHole,
+    /// The pushed piece has another piece right behind it
+    TwoPieces,
+    /// The pushed piece is, or the pushed row holds, a heavy cross
+    Heavy,
+}
+
+impl Blocked {
+    /// Short message for the game's toast.
+    pub fn message(self) -> &'static str {
+        match self {
+            Blocked::Obstacle => "blocked",
+            Blocked::PushIntoHole => "can't push into a hole",
+            Blocked::TwoPieces => "two pieces in the way",
+            Blocked::Heavy => "heavy crosses don't budge",
+        }
+    }
+}
+
+/// What stops a piece from entering (r, c), which is not a board cell.
+fn missing_cell(r: isize, c: isize, board: &Board) -> Blocked {
+    if board.is_hole(r, c) {
+        Blocked::PushIntoHole
+    } else {
+        Blocked::Obstacle
+    }
+}
+
+/// Whether a cross pushed onto (r, c) falls in and leaves play there, under
+/// the sinkhole rule. It stays in the crosses on that cell (see
+/// `Rules::sinkholes`).
+fn swallows(r: isize, c: isize, board: &Board) -> bool {
+    board.rules.sinkholes && board.is_hole(r, c)
+}
+
+/// Why `attempt_move_runtime` with the same arguments would not move
+/// anything, or `None` when it would.
+pub fn blocked_reason(
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) -> Option<Blocked> {
+    let (pr, pc) = circles[player_idx];
+    let (r, c) = board.wrap(pr as isize + dr, pc as isize + dc);
+    if !board.has_cell(r, c) {
+        return Some(Blocked::Obstacle);
+    }
+    if !occupied_any(circles, crosses, r as usize, c as usize) {
+        return None;
+    }
+    if board.rules.chain_push {
+        let line = line_of_pieces(circles, crosses, (r, c), dr, dc, board);
+        if line.iter().any(|&(r, c)| board.is_heavy(r, c)) {
+            return Some(Blocked::Heavy);
+        }
+        let (er, ec) = beyond(&line, dr, dc, board);
+        if swallows(er, ec, board) && crosses.contains(&line[line.len() - 1]) {
+            return None;
+        }
+        if !board.has_cell(er, ec) {
+            return Some(missing_cell(er, ec, board));
+        }
+        // the row closes a ring around a toroidal board
+        return occupied_any(circles, crosses, er as usize, ec as usize)
+            .then_some(Blocked::TwoPieces);
+    }
+    if board.is_heavy(r as usize, c as usize) {
+        return Some(Blocked::Heavy);
+    }
+    let (er, ec) = board.wrap(r + dr, c + dc);
+    if swallows(er, ec, board) && crosses.contains(&(r as usize, c as usize)) {
+        return None;
+    }
+    if !board.has_cell(er, ec) {
+        return Some(missing_cell(er, ec, board));
+    }
+    if occupied_any(circles, crosses, er as usize, ec as usize) {
+        return Some(Blocked::TwoPieces);
+    }
+    None
+}
+
+/// Settle the piece at `pos`, just pushed by (dr, dc): a piece on a portal whose
+/// partner is free comes out of the partner (once per push), and it keeps
+/// sliding while it rests on ice and the next cell is present and free. The
+/// player never slides or teleports; a cross sliding into a hole falls in
+/// under the sinkhole rule.
+fn slide(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    mut pos: (usize, usize),
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) {
+    let mut teleported = false;
+    // where the current straight run began; around a ring of ice on a toroidal
+    // board the piece stops just short of it instead of circling forever
+    let mut run_start = pos;
+    loop {
+        let next = match board.portal_exit(pos.0, pos.1) {
+            Some(exit) if !teleported && !occupied_any(circles, crosses, exit.0, exit.1) => {
+                teleported = true;
+                run_start = exit;
+                exit
+            }
+            _ => {
+                if !board.is_ice(pos.0 as isize, pos.1 as isize) {
+                    return;
+                }
+                let (nr, nc) = board.wrap(pos.0 as isize + dr, pos.1 as isize + dc);
+                let next = (nr as usize, nc as usize);
+                if swallows(nr, nc, board) && crosses.contains(&pos) {
+                    for p in crosses.iter_mut().filter(|p| **p == pos) {
+                        *p = next;
+                    }
+                    return;
+                }
+                if !board.has_cell(nr, nc)
+                    || next == run_start
+                    || occupied_any(circles, crosses, next.0, next.1)
+                {
+                    return;
+                }
+                next
+            }
+        };
+        for p in circles.iter_mut().chain(crosses.iter_mut()) {
+            if *p == pos {
+                *p = next;
+            }
+        }
+        pos = next;
+    }
+}
+
+/// Where the piece a reverse step would pull back toward the player sits:
+/// the nearest piece behind the player across free ice, provided a push from
+/// the player's cell would have stopped it exactly there. Without ice this is
+/// simply the piece right behind the player.
+fn pull_source(
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    (r, c): (isize, isize),
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) -> Option<(usize, usize)> {
+    let player = (r, c);
+    let (mut r, mut c) = board.wrap(r - dr, c - dc);
+    // around a toroidal board the walk ends back at the player
+    while board.has_cell(r, c) && (r, c) != player {
+        let (br, bc) = board.wrap(r - dr, c - dc);
+        if occupied_any(circles, crosses, r as usize, c as usize) {
+            if board.is_heavy(r as usize, c as usize) {
+                return None;
+            }
+            let stops = !board.is_ice(r, c)
+                || !board.has_cell(br, bc)
+                || occupied_any(circles, crosses, br as usize, bc as usize);
+            return stops.then_some((r as usize, c as usize));
+        }
+        if !board.is_ice(r, c) {
+            return None;
+        }
+        (r, c) = (br, bc);
+    }
+    None
+}
+
+/// Occupied cells in a straight run starting at (r, c) and stepping by (dr, dc).
+/// A run that goes all the way around a toroidal board stops before repeating.
+fn line_of_pieces(
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    (r, c): (isize, isize),
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) -> Vec<(usize, usize)> {
+    let mut line = Vec::new();
+    let (mut r, mut c) = (r, c);
+    while board.has_cell(r, c)
+        && occupied_any(circles, crosses, r as usize, c as usize)
+        && !line.contains(&(r as usize, c as usize))
+    {
+        line.push((r as usize, c as usize));
+        (r, c) = board.wrap(r + dr, c + dc);
+    }
+    line
+}
+
+/// The cell one step by (dr, dc) past a cell of the board.
+fn step((r, c): (usize, usize), dr: isize, dc: isize, board: &Board) -> (isize, isize) {
+    board.wrap(r as isize + dr, c as isize + dc)
+}
+
+/// The cell one step past the far end of a non-empty run of pieces.
+fn beyond(line: &[(usize, usize)], dr: isize, dc: isize, board: &Board) -> (isize, isize) {
+    step(line[line.len() - 1], dr, dc, board)
+}
+
+/// Move every piece at one of `cells` one step by (dr, dc).
+fn shift_pieces(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    cells: &[(usize, usize)],
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) {
+    for p in circles.iter_mut().chain(crosses.iter_mut()) {
+        if cells.contains(p) {
+            let (r, c) = step(*p, dr, dc, board);
+            *p = (r as usize, c as usize);
+        }
+    }
+}
+
+/// Chain push: the player moves into an occupied cell and the whole run of
+/// adjacent pieces in front of it shifts one cell, if the cell past the far end
+/// exists and is free, or is a hole the front cross drops into.
+fn push_line(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) {
+    let line = line_of_pieces(
+        circles,
+        crosses,
+        step(circles[player_idx], dr, dc, board),
+        dr,
+        dc,
+        board,
+    );
+    if line.iter().any(|&(r, c)| board.is_heavy(r, c)) {
+        return;
+    }
+    let (er, ec) = beyond(&line, dr, dc, board);
+    let front_falls = swallows(er, ec, board) && crosses.contains(&line[line.len() - 1]);
+    if !front_falls
+        && (!board.has_cell(er, ec) || occupied_any(circles, crosses, er as usize, ec as usize))
+    {
+        return;
+    }
+    shift_pieces(circles, crosses, &line, dr, dc, board);
+    circles[player_idx] = line[0];
+    // the front piece slides first so the ones behind can follow it
+    for &cell in line.iter().rev() {
+        let (r, c) = step(cell, dr, dc, board);
+        slide(circles, crosses, (r as usize, c as usize), dr, dc, board);
+    }
+}
+
 /// Reverse-move used for scrambling: attempt to "pull" an object from behind the player into
-/// the player's current cell and move the player forward. This is the inverse of a forward push.
+/// the player's current cell and move the player forward. This is the inverse of a forward push,
+/// including one that sent the piece sliding across ice (but not through a portal; generated
+/// boards have none).
 pub fn attempt_move_reverse(
     circles: &mut [(usize, usize)],
     crosses: &mut [(usize, usize)],
@@ -100,16 +486,39 @@ pub fn attempt_move_reverse(
     dr: isize,
     dc: isize,
     board: &Board,
+) {
+    pull_move(circles, crosses, player_idx, dr, dc, board, true);
+}
+
+/// The player's pull move when the pull rule is on: like `attempt_move_reverse`, but only the
+/// piece right behind the player comes along.
+pub fn attempt_pull(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) {
+    pull_move(circles, crosses, player_idx, dr, dc, board, false);
+}
+
+fn pull_move(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    board: &Board,
+    undo_slides: bool,
 ) {
     let (player_row, player_column) = circles[player_idx];
 
     // source cell (one step behind the player in the given direction)
-    let source_row_i = player_row as isize - dr;
-    let source_column_i = player_column as isize - dc;
+    let (source_row_i, source_column_i) = step((player_row, player_column), -dr, -dc, board);
 
     // forward cell where the player would step into after pulling
-    let forward_row_i = player_row as isize + dr;
-    let forward_column_i = player_column as isize + dc;
+    let (forward_row_i, forward_column_i) = step((player_row, player_column), dr, dc, board);
 
     // forward must be valid and present
     if forward_row_i < 0 || forward_column_i < 0 {
@@ -121,31 +530,282 @@ pub fn attempt_move_reverse(
         return;
     }
 
-    // If there's an object one step behind the player and the forward cell is free, pull it into player's cell
-    if source_row_i >= 0 && source_column_i >= 0 {
-        let source_row = source_row_i as usize;
-        let source_column = source_column_i as usize;
-        if source_row < board.rows && source_column < board.row_widths[source_row] && board.is_cell_present(source_row, source_column) {
-            // forward cell must be free to pull
-            if occupied_any(circles, crosses, forward_row, forward_column) {
-                return;
+    // Chain-push variant: pull the whole line of pieces behind the player, the
+    // inverse of pushing that line
+    if board.rules.chain_push {
+        if occupied_any(circles, crosses, forward_row, forward_column) {
+            return;
+        }
+        let mut behind = line_of_pieces(
+            circles,
+            crosses,
+            (source_row_i, source_column_i),
+            -dr,
+            -dc,
+            board,
+        );
+        // a heavy cross stays, and so does the rest of the line behind it
+        if let Some(heavy) = behind.iter().position(|&(r, c)| board.is_heavy(r, c)) {
+            behind.truncate(heavy);
+        }
+        shift_pieces(circles, crosses, &behind, dr, dc, board);
+        circles[player_idx] = (forward_row, forward_column);
+        return;
+    }
+
+    // forward cell must be free, both to pull and to just step forward
+    if occupied_any(circles, crosses, forward_row, forward_column) {
+        return;
+    }
+
+    // If there's an object behind the player, pull it into player's cell
+    let source = if undo_slides {
+        pull_source(
+            circles,
+            crosses,
+            (player_row as isize, player_column as isize),
+            dr,
+            dc,
+            board,
+        )
+    } else {
+        (board.has_cell(source_row_i, source_column_i)
+            && occupied_any(
+                circles,
+                crosses,
+                source_row_i as usize,
+                source_column_i as usize,
+            )
+            && !board.is_heavy(source_row_i as usize, source_column_i as usize))
+        .then_some((source_row_i as usize, source_column_i as usize))
+    };
+    if let Some(source) = source {
+        for p in circles.iter_mut().chain(crosses.iter_mut()) {
+            if *p == source {
+                *p = (player_row, player_column);
             }
+        }
+    }
+    circles[player_idx] = (forward_row, forward_column);
+}
+
+/// Move the pieces for one player move: a pull when `pull` is set, otherwise
+/// a walk or push (of every circle at once under the unison rule).
+fn move_pieces(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    pull: bool,
+    board: &Board,
+) {
+    if pull {
+        attempt_pull(circles, crosses, player_idx, dr, dc, board);
+    } else if board.rules.unison {
+        crate::unison::step_all(circles, crosses, dr, dc, board);
+    } else {
+        attempt_move_runtime(circles, crosses, player_idx, dr, dc, board);
+    }
+}
 
-            if let Some(circle_idx) = find_circle_index(circles, source_row, source_column) {
-                circles[circle_idx] = (player_row, player_column);
-                circles[player_idx] = (forward_row, forward_column);
-                return;
+/// Set off every bomb a piece other than the player was moved onto, going
+/// from the pieces before the move to the pieces now: each clears the crosses
+/// next to it, except heavy crosses and neutral blocks.
+fn detonate(
+    circles_before: &[(usize, usize)],
+    crosses_before: &[(usize, usize)],
+    circles: &[(usize, usize)],
+    crosses: &mut Vec<(usize, usize)>,
+    player_idx: usize,
+    board: &Board,
+) {
+    let moved = |old: &[(usize, usize)], new: &[(usize, usize)], skip: Option<usize>| {
+        old.iter()
+            .zip(new)
+            .enumerate()
+            .filter(|&(i, (o, n))| Some(i) != skip && o != n && board.is_bomb(n.0, n.1))
+            .map(|(_, (_, &n))| n)
+            .collect::<Vec<_>>()
+    };
+    let mut blasts = moved(circles_before, circles, Some(player_idx));
+    blasts.extend(moved(crosses_before, crosses, None));
+    let cleared: Vec<(usize, usize)> = blasts
+        .iter()
+        .flat_map(|&(r, c)| board.blast_cells(r, c))
+        .collect();
+    let mut i = 0;
+    while i < board.line_crosses(crosses).len() {
+        let (r, c) = crosses[i];
+        if cleared.contains(&(r, c)) && !board.is_heavy(r, c) {
+            crosses.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Apply one player move: a pull when `pull` is set, otherwise a walk or push
+/// (of every circle at once under the unison rule). A piece moved onto a bomb
+/// sets it off, which can leave fewer crosses.
+pub fn apply_move(
+    circles: &mut [(usize, usize)],
+    crosses: &mut Vec<(usize, usize)>,
+    player_idx: usize,
+    dr: isize,
+    dc: isize,
+    pull: bool,
+    board: &Board,
+) {
+    let before = board
+        .bombs
+        .contains(&true)
+        .then(|| (circles.to_vec(), crosses.clone()));
+    move_pieces(circles, crosses, player_idx, dr, dc, pull, board);
+    if let Some((old_circles, old_crosses)) = before {
+        detonate(
+            &old_circles,
+            &old_crosses,
+            circles,
+            crosses,
+            player_idx,
+            board,
+        );
+    }
+}
+
+/// A direction the player can move in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// In the order the searches try them.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// Row and column offset of one step.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+}
+
+/// The piece a move pushed or pulled, by index into the circles or crosses
+/// slice. For a chain push this is the piece next to the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Push {
+    Circle(usize),
+    Cross(usize),
+}
+
+/// What a legal move does: whether it is a pull or a power-up's removal, the
+/// piece it moves (or removes) besides the player, and the position it leads
+/// to.
+#[derive(Clone, Debug)]
+pub struct MoveResult {
+    pub pull: bool,
+    pub remove: bool,
+    pub push: Option<Push>,
+    pub circles: Vec<(usize, usize)>,
+    pub crosses: Vec<(usize, usize)>,
+}
+
+/// Every move that changes the position, in `Direction::ALL` order with the
+/// plain move before the pull of each direction. A pull with nothing behind
+/// the player is left out, being the plain move again. With power-ups
+/// `held`, the removal of each cross next to the player follows. Moves that
+/// lose are included.
+pub fn legal_moves(
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    held: usize,
+    board: &Board,
+) -> Vec<(Direction, MoveResult)> {
+    let mut moves = Vec::new();
+    for dir in Direction::ALL {
+        let (dr, dc) = dir.delta();
+        for &pull in board.rules.pull_choices() {
+            let mut new_circles = circles.to_vec();
+            let mut new_crosses = crosses.to_vec();
+            move_pieces(
+                &mut new_circles,
+                &mut new_crosses,
+                player_idx,
+                dr,
+                dc,
+                pull,
+                board,
+            );
+            if new_circles == circles && new_crosses == crosses {
+                continue;
             }
-            if let Some(cross_idx) = find_cross_index(crosses, source_row, source_column) {
-                crosses[cross_idx] = (player_row, player_column);
-                circles[player_idx] = (forward_row, forward_column);
-                return;
+            // a pushed piece stood where the player now is, a pulled one now
+            // stands where the player was
+            let (from, to) = (circles[player_idx], new_circles[player_idx]);
+            let moved = |old: (usize, usize), new: (usize, usize)| {
+                if pull { new == from } else { old == to }
+            };
+            let push = if let Some(i) =
+                (0..circles.len()).find(|&i| i != player_idx && moved(circles[i], new_circles[i]))
+            {
+                Some(Push::Circle(i))
+            } else {
+                (0..crosses.len())
+                    .find(|&i| moved(crosses[i], new_crosses[i]))
+                    .map(Push::Cross)
+            };
+            if pull && push.is_none() {
+                continue;
             }
+            detonate(
+                circles,
+                crosses,
+                &new_circles,
+                &mut new_crosses,
+                player_idx,
+                board,
+            );
+            let result = MoveResult {
+                pull,
+                remove: false,
+                push,
+                circles: new_circles,
+                crosses: new_crosses,
+            };
+            moves.push((dir, result));
         }
     }
-
-    // Otherwise, if forward is empty, just move the player forward
-    if !occupied_any(circles, crosses, forward_row, forward_column) {
-        circles[player_idx] = (forward_row, forward_column);
+    if held > 0 {
+        for dir in Direction::ALL {
+            let (dr, dc) = dir.delta();
+            let mut new_crosses = crosses.to_vec();
+            if !remove_cross(circles, &mut new_crosses, player_idx, dr, dc, board) {
+                continue;
+            }
+            let removed = (0..crosses.len()).find(|&i| new_crosses.get(i) != Some(&crosses[i]));
+            let result = MoveResult {
+                pull: false,
+                remove: true,
+                push: removed.map(Push::Cross),
+                circles: circles.to_vec(),
+                crosses: new_crosses,
+            };
+            moves.push((dir, result));
+        }
     }
+    moves
 }
@@ -0,0 +1,190 @@
+// Puzzle packs: a directory of puzzle files with an optional `pack.toml`
+// manifest saying in which order they are played, what they are called and
+// which puzzles have to be solved before another one opens up.
+//
+//     title = "First steps"
+//
+//     [[puzzle]]
+//     file = "corner.json"
+//     title = "Into the corner"
+//
+//     [[puzzle]]
+//     file = "two-crosses.json"
+//     requires = ["corner.json"]
+//
+// Only this much TOML is understood: `#` comments, string values, arrays of
+// strings and `[[puzzle]]` tables. Puzzle files the manifest leaves out come
+// after the listed ones and are always open.
+//
+// Solved puzzle files are remembered in `tic-tac-go-solved.json` in the
+// working directory by their canonical hash (see `PuzzleFile::canonical_hash`),
+// so renaming or copying a file keeps its progress.
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE: &str = "pack.toml";
+pub const SOLVED_FILE: &str = "tic-tac-go-solved.json";
+
+#[derive(Clone, Debug, Default)]
+pub struct Manifest {
+    pub title: Option<String>,
+    /// In play order
+    pub puzzles: Vec<PackPuzzle>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PackPuzzle {
+    /// File name within the pack's directory
+    pub file: String,
+    pub title: Option<String>,
+    /// Files that must be solved first
+    pub requires: Vec<String>,
+}
+
+impl Manifest {
+    /// The manifest's entry for `file`, if it lists it.
+    pub fn entry(&self, file: &str) -> Option<&PackPuzzle> {
+        self.puzzles.iter().find(|p| p.file == file)
+    }
+}
+
+enum Value {
+    Str(String),
+    List(Vec<String>),
+}
+
+/// A quoted string at the start of `text` and the rest of the text after it.
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let body = text.strip_prefix('"').ok_or("expected a quoted string")?;
+    let mut out = String::new();
+    let mut chars = body.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '"' => return Ok((out, &body[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => out.push('"'),
+                Some((_, '\\')) => out.push('\\'),
+                Some((_, 'n')) => out.push('\n'),
+                Some((_, 't')) => out.push('\t'),
+                _ => return Err("unsupported escape in string".to_string()),
+            },
+            _ => out.push(ch),
+        }
+    }
+    Err("unterminated string".to_string())
+}
+
+/// The value of a `key = value` line; anything after it must be a comment.
+fn parse_value(text: &str) -> Result<Value, String> {
+    let text = text.trim_start();
+    let (value, rest) = if let Some(mut list) = text.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            list = list.trim_start();
+            if let Some(rest) = list.strip_prefix(']') {
+                break (Value::List(items), rest);
+            }
+            let (item, rest) = parse_string(list)?;
+            items.push(item);
+            list = rest.trim_start();
+            if let Some(rest) = list.strip_prefix(',') {
+                list = rest;
+            } else if !list.starts_with(']') {
+                return Err("expected , or ] in list".to_string());
+            }
+        }
+    } else {
+        let (s, rest) = parse_string(text)?;
+        (Value::Str(s), rest)
+    };
+    let rest = rest.trim_start();
+    if !rest.is_empty() && !rest.starts_with('#') {
+        return Err(format!("unexpected {}", rest));
+    }
+    Ok(value)
+}
+
+/// Parse the text of a `pack.toml`.
+pub fn parse(text: &str) -> Result<Manifest, Box<dyn Error>> {
+    let mut manifest = Manifest::default();
+    for (i, line) in text.lines().enumerate() {
+        let at = |e: String| format!("line {}: {}", i + 1, e);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            let header = line.split('#').next().unwrap_or("").trim();
+            if header != "[[puzzle]]" {
+                return Err(at(format!("unknown table {}", header)).into());
+            }
+            manifest.puzzles.push(PackPuzzle::default());
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| at("expected key = value".to_string()))?;
+        let value = parse_value(value).map_err(at)?;
+        match (manifest.puzzles.last_mut(), key.trim(), value) {
+            (None, "title", Value::Str(s)) => manifest.title = Some(s),
+            (Some(puzzle), "file", Value::Str(s)) => puzzle.file = s,
+            (Some(puzzle), "title", Value::Str(s)) => puzzle.title = Some(s),
+            (Some(puzzle), "requires", Value::List(files)) => puzzle.requires = files,
+            (_, key, _) => return Err(at(format!("unexpected {}", key)).into()),
+        }
+    }
+    if let Some(i) = manifest.puzzles.iter().position(|p| p.file.is_empty()) {
+        return Err(format!("puzzle {} has no file", i + 1).into());
+    }
+    Ok(manifest)
+}
+
+/// The manifest of the pack in `dir`, or `None` when it has none.
+pub fn load(dir: &Path) -> Result<Option<Manifest>, Box<dyn Error>> {
+    match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(text) => Ok(Some(parse(&text)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Solved {
+    hashes: HashSet<u64>,
+}
+
+fn load_solved() -> Solved {
+    let Ok(contents) = fs::read_to_string(SOLVED_FILE) else {
+        return Solved::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        log::warn!("{}: {}; starting with nothing solved", SOLVED_FILE, e);
+        Solved::default()
+    })
+}
+
+fn save_solved(solved: &Solved) -> Result<(), Box<dyn Error>> {
+    fs::write(Path::new(SOLVED_FILE), serde_json::to_string(solved)?)?;
+    Ok(())
+}
+
+/// Canonical hashes of every puzzle file solved so far.
+pub fn solved() -> HashSet<u64> {
+    load_solved().hashes
+}
+
+/// Remember that the puzzle with canonical hash `hash` was solved.
+pub fn mark_solved(hash: u64) {
+    let mut solved = load_solved();
+    if !solved.hashes.insert(hash) {
+        return;
+    }
+    if let Err(e) = save_solved(&solved) {
+        log::warn!("could not save {}: {}", SOLVED_FILE, e);
+    }
+}
@@ -0,0 +1,213 @@
+// `tic-tac-go --plain`: the game as a plain line dialogue, for dumb
+// terminals, editor shells and anything else without raw mode or cursor
+// control. The board is printed as text (the ASCII format of `sim`) after
+// every line of input, and each line is read as a series of moves.
+use std::error::Error;
+use std::io::{self, BufRead, Write};
+
+use rand::{Rng, thread_rng};
+
+use crate::announce;
+use crate::generate::parse_difficulty;
+use crate::generator::{self, Difficulty};
+use crate::movement;
+use crate::rules::move_budget;
+use crate::sim::{self, Action, Status};
+use crate::state::GameState;
+
+// Seeds tried in a row before giving up on a difficulty that yields nothing
+const MAX_FAILED_SEEDS: u64 = 20;
+
+fn print_usage() {
+    eprintln!("usage: tic-tac-go --plain [--difficulty LEVEL] [--seed S]");
+    eprintln!();
+    eprintln!("Plays in line mode: type moves (w/a/s/d, p before a direction to pull)");
+    eprintln!("and press Enter. n starts the next puzzle, q quits.");
+}
+
+fn print_help() {
+    println!("@ you   o circle   x cross   # wall   ~ ice   A-H portals");
+    println!("moves: w a s d (several per line); with pulls on, p then a direction pulls");
+    println!("n: next puzzle   q: quit");
+}
+
+fn direction(c: char) -> Option<(isize, isize)> {
+    match c {
+        'w' => Some((-1, 0)),
+        's' => Some((1, 0)),
+        'a' => Some((0, -1)),
+        'd' => Some((0, 1)),
+        _ => None,
+    }
+}
+
+/// Generate the puzzle for `seed`, moving on to the following seeds when one
+/// gives nothing. Also returns the move budget under the move-limit rule.
+fn next_puzzle(
+    seed: &mut u64,
+    difficulty: Difficulty,
+) -> Result<(GameState, Option<usize>), Box<dyn Error>> {
+    let rules = crate::config::get().rules;
+    for _ in 0..MAX_FAILED_SEEDS {
+        let puzzle = generator::generate_from_seed(*seed, difficulty, rules);
+        if !puzzle.circles.is_empty() {
+            let budget = puzzle.par.filter(|_| rules.move_limit).map(move_budget);
+            return Ok((GameState::from_generated(puzzle), budget));
+        }
+        *seed = seed.wrapping_add(1);
+    }
+    Err(format!("no puzzle in {} seeds from {}", MAX_FAILED_SEEDS, seed).into())
+}
+
+/// Print the screen-reader line for a move from `before` that left the
+/// crosses at `pushed` before the crosses' reply.
+fn announce(
+    state: &GameState,
+    (dr, dc, action): sim::Move,
+    before: announce::Pieces,
+    pushed: &[(usize, usize)],
+) {
+    let mut text = announce::describe_move(
+        state.player_idx,
+        (dr, dc),
+        action == Action::Pull,
+        before,
+        (&state.circles, pushed),
+    );
+    if let Some(reply) = announce::describe_reply(pushed, &state.crosses) {
+        text.push_str("; ");
+        text.push_str(&reply);
+    }
+    if sim::status(state) == Status::Playing {
+        text = announce::with_warnings(text, &state.board, (&state.circles, &state.crosses));
+    }
+    println!("{}", text);
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut difficulty = Difficulty::Medium;
+    let mut seed: u64 = thread_rng().r#gen();
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--difficulty" | "-d" => {
+                difficulty = it
+                    .next()
+                    .and_then(|v| parse_difficulty(v))
+                    .ok_or("--difficulty expects easy, medium, hard, expert or insane")?;
+            }
+            "--seed" => {
+                seed = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--seed expects a number")?;
+            }
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(());
+            }
+            other => {
+                print_usage();
+                return Err(format!("unknown option: {}", other).into());
+            }
+        }
+    }
+
+    print_help();
+    let stdin = io::stdin();
+    let mut input = stdin.lock().lines();
+    'puzzle: loop {
+        let (mut state, budget) = next_puzzle(&mut seed, difficulty)?;
+        println!();
+        println!("{:?} puzzle, seed {}", difficulty, seed);
+        loop {
+            let mut status = sim::status(&state);
+            let out_of_moves =
+                status == Status::Playing && budget.is_some_and(|b| state.moves >= b);
+            if out_of_moves {
+                status = Status::Lost;
+            }
+            println!();
+            print!("{}", sim::to_ascii(&state));
+            match (status, budget) {
+                (Status::Won, _) => {
+                    println!("You won in {} moves. n: next puzzle, q: quit", state.moves)
+                }
+                (Status::Lost, _) if out_of_moves => {
+                    println!("Out of moves. n: next puzzle, q: quit")
+                }
+                (Status::Lost, _) => println!("Three crosses aligned. n: next puzzle, q: quit"),
+                (Status::Stalemate, _) => {
+                    println!("Stalemate: no move changes the board. n: next puzzle, q: quit")
+                }
+                (Status::Playing, Some(b)) => {
+                    println!("moves {}, {} left", state.moves, b - state.moves)
+                }
+                (Status::Playing, None) => println!("moves {}", state.moves),
+            }
+            print!("> ");
+            io::stdout().flush()?;
+
+            let Some(line) = input.next() else {
+                return Ok(());
+            };
+            let mut action = Action::Step;
+            for c in line?.chars().map(|c| c.to_ascii_lowercase()) {
+                match c {
+                    'q' => return Ok(()),
+                    'n' => {
+                        seed = seed.wrapping_add(1);
+                        continue 'puzzle;
+                    }
+                    '?' | 'h' => print_help(),
+                    'p' if state.board.rules.pull => action = Action::Pull,
+                    c => {
+                        let Some((dr, dc)) = direction(c) else {
+                            if !c.is_whitespace() {
+                                println!("unknown key '{}'", c);
+                            }
+                            continue;
+                        };
+                        if status != Status::Playing || budget.is_some_and(|b| state.moves >= b) {
+                            break;
+                        }
+                        let before = (state.circles.clone(), state.crosses.clone());
+                        let pushed = sim::step(&mut state, (dr, dc, action));
+                        status = sim::status(&state);
+                        if let Some(pushed) = pushed {
+                            if crate::config::get().screen_reader {
+                                announce(&state, (dr, dc, action), (&before.0, &before.1), &pushed);
+                            }
+                        } else {
+                            let reason = if action == Action::Pull {
+                                Some(movement::Blocked::Obstacle)
+                            } else {
+                                movement::blocked_reason(
+                                    &state.circles,
+                                    &state.crosses,
+                                    state.player_idx,
+                                    dr,
+                                    dc,
+                                    &state.board,
+                                )
+                            };
+                            if let Some(reason) = reason
+                                && crate::config::get().screen_reader
+                            {
+                                println!(
+                                    "player could not move {}: {}",
+                                    announce::direction_name(dr, dc),
+                                    reason.message()
+                                );
+                            } else if let Some(reason) = reason {
+                                println!("{}", reason.message());
+                            }
+                        }
+                        action = Action::Step;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// The difficulty menu's choices from the last game, so the next one opens on
+// them.
+//
+// Starting a game from the menu writes `tic-tac-go-prefs.json` in the working
+// directory with the highlighted entry, the rule toggles and the settings of
+// the Custom form. Until the file exists the menu starts on Medium with the
+// rules from the config.
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::generator::CustomSettings;
+use crate::rules::Rules;
+
+pub const PREFS_FILE: &str = "tic-tac-go-prefs.json";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Prefs {
+    /// Index of the difficulty menu entry last started
+    pub selection: Option<usize>,
+    pub rules: Option<Rules>,
+    /// The Custom form as last submitted
+    pub custom: Option<CustomSettings>,
+}
+
+impl Prefs {
+    /// The saved choices; a missing or unreadable file remembers nothing.
+    pub fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(PREFS_FILE) else {
+            return Prefs::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("{}: {}; ignoring it", PREFS_FILE, e);
+            Prefs::default()
+        })
+    }
+
+    fn try_save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(Path::new(PREFS_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = self.try_save() {
+            log::warn!("could not save {}: {}", PREFS_FILE, e);
+        }
+    }
+}
@@ -0,0 +1,400 @@
+// Puzzle file format shared by the editor, browser and command line tools
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::{Board, BoardShape, PortalPair};
+use crate::movement::MAX_POWERUPS;
+use crate::rules::{MAX_CIRCLES, MIN_CIRCLES};
+
+// The eight symmetries of the grid: bit 0 flips rows, bit 1 flips columns and
+// bit 2 transposes. Lines stay lines under all of them, so a mirrored or
+// rotated puzzle plays exactly like the original.
+const SYMMETRIES: usize = 8;
+
+type CanonicalForm = (
+    usize,
+    usize,
+    Vec<(usize, usize)>,
+    (usize, usize),
+    Vec<(usize, usize)>,
+    Vec<(usize, usize)>,
+    Vec<(usize, usize)>,
+    Vec<(usize, usize)>,
+    Vec<PortalPair>,
+    Vec<(usize, usize)>,
+    // heavy crosses, neutral blocks, power-ups and bombs; tuples stop being
+    // `Ord` past twelve elements
+    (
+        Vec<(usize, usize)>,
+        Vec<(usize, usize)>,
+        Vec<(usize, usize)>,
+        Vec<(usize, usize)>,
+    ),
+);
+
+fn transform(t: usize, rows: usize, cols: usize, (r, c): (usize, usize)) -> (usize, usize) {
+    let r = if t & 1 != 0 { rows - 1 - r } else { r };
+    let c = if t & 2 != 0 { cols - 1 - c } else { c };
+    if t & 4 != 0 { (c, r) } else { (r, c) }
+}
+
+/// On-disk representation of a puzzle, as written by the puzzle editor. The
+/// board's fields sit at the top level (see `BoardShape`).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PuzzleFile {
+    #[serde(flatten)]
+    pub shape: BoardShape,
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    pub circles: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub crosses: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub player: Option<(usize, usize)>,
+}
+
+impl PuzzleFile {
+    /// Puzzle file for a board and pieces, e.g. from the generator.
+    pub fn from_parts(
+        board: &Board,
+        circles: &[(usize, usize)],
+        crosses: &[(usize, usize)],
+        player_idx: usize,
+        created_at: Option<u64>,
+    ) -> Self {
+        PuzzleFile {
+            shape: BoardShape::from(board),
+            created_at,
+            circles: circles.to_vec(),
+            crosses: crosses.to_vec(),
+            player: circles.get(player_idx).copied(),
+        }
+    }
+
+    pub fn parse(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents)
+    }
+
+    /// The file's board, rejected if it breaks `Board::validate`.
+    pub fn board(&self) -> Result<Board, Box<dyn Error>> {
+        let board = self.shape.build()?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    /// Index of the player among `circles`; defaults to the first circle when
+    /// no player was marked in the editor.
+    pub fn player_idx(&self) -> usize {
+        self.player
+            .and_then(|p| self.circles.iter().position(|&c| c == p))
+            .unwrap_or(0)
+    }
+
+    /// Structural checks that the lenient browser loader skips: every piece must
+    /// sit on a distinct, present cell and the puzzle needs 3 to 5 circles.
+    pub fn check(&self, board: &Board) -> Result<(), String> {
+        if !(MIN_CIRCLES..=MAX_CIRCLES).contains(&self.circles.len()) {
+            return Err(format!(
+                "expected {} to {} circles, found {}",
+                MIN_CIRCLES,
+                MAX_CIRCLES,
+                self.circles.len()
+            ));
+        }
+        let mut seen: Vec<(usize, usize)> = Vec::new();
+        for &(r, c) in self.circles.iter().chain(self.crosses.iter()) {
+            if r >= board.rows || c >= board.row_widths[r] {
+                return Err(format!("piece at ({}, {}) is outside the board", r, c));
+            }
+            if !board.is_cell_present(r, c) {
+                return Err(format!("piece at ({}, {}) is on a removed cell", r, c));
+            }
+            if seen.contains(&(r, c)) {
+                return Err(format!("more than one piece at ({}, {})", r, c));
+            }
+            seen.push((r, c));
+        }
+        if let Some(&(r, c)) = self
+            .shape
+            .walls
+            .iter()
+            .find(|&&(r, c)| r >= board.rows || c >= board.row_widths[r])
+        {
+            return Err(format!("wall at ({}, {}) is outside the board", r, c));
+        }
+        if let Some(&(r, c)) = self
+            .shape
+            .ice
+            .iter()
+            .find(|&&(r, c)| !board.has_cell(r as isize, c as isize))
+        {
+            return Err(format!("ice at ({}, {}) is not on a board cell", r, c));
+        }
+        if let Some(&(r, c)) = self
+            .shape
+            .goals
+            .iter()
+            .find(|&&(r, c)| !board.has_cell(r as isize, c as isize))
+        {
+            return Err(format!("goal at ({}, {}) is not on a board cell", r, c));
+        }
+        if let Some(&(r, c)) = self
+            .shape
+            .powerups
+            .iter()
+            .find(|&&(r, c)| !board.has_cell(r as isize, c as isize))
+        {
+            return Err(format!("power-up at ({}, {}) is not on a board cell", r, c));
+        }
+        if self.shape.powerups.len() > MAX_POWERUPS {
+            return Err(format!(
+                "{} power-ups, at most {} are allowed",
+                self.shape.powerups.len(),
+                MAX_POWERUPS
+            ));
+        }
+        if let Some(&(r, c)) = self
+            .shape
+            .bombs
+            .iter()
+            .find(|&&(r, c)| !board.has_cell(r as isize, c as isize))
+        {
+            return Err(format!("bomb at ({}, {}) is not on a board cell", r, c));
+        }
+        if let Some(&(r, c)) = self.shape.heavy.iter().find(|p| !self.crosses.contains(p)) {
+            return Err(format!("heavy cross at ({}, {}) is not on a cross", r, c));
+        }
+        if self.shape.blocks > self.crosses.len() {
+            return Err(format!(
+                "{} blocks but only {} crosses to take them from",
+                self.shape.blocks,
+                self.crosses.len()
+            ));
+        }
+        let mut ends: Vec<(usize, usize)> = Vec::new();
+        for &(a, b) in &self.shape.portals {
+            for (r, c) in [a, b] {
+                if !board.has_cell(r as isize, c as isize) {
+                    return Err(format!("portal at ({}, {}) is not on a board cell", r, c));
+                }
+                if ends.contains(&(r, c)) {
+                    return Err(format!("more than one portal at ({}, {})", r, c));
+                }
+                ends.push((r, c));
+            }
+        }
+        if let Some(p) = self.player
+            && !self.circles.contains(&p)
+        {
+            return Err(format!("player ({}, {}) is not on a circle", p.0, p.1));
+        }
+        Ok(())
+    }
+
+    /// Smallest of the puzzle's eight mirrored/rotated forms, with pieces
+    /// sorted, so equivalent puzzles compare equal regardless of orientation,
+    /// piece order or metadata. Assumes `check` passed.
+    fn canonical_form(&self) -> CanonicalForm {
+        let player_idx = self.player_idx();
+        let shape = &self.shape;
+        (0..SYMMETRIES)
+            .map(|t| {
+                let map = |v: &[(usize, usize)]| {
+                    let mut v: Vec<(usize, usize)> = v
+                        .iter()
+                        .filter(|&&(r, c)| r < shape.rows && c < shape.cols)
+                        .map(|&p| transform(t, shape.rows, shape.cols, p))
+                        .collect();
+                    v.sort_unstable();
+                    v.dedup();
+                    v
+                };
+                let (rows, cols) = if t & 4 != 0 {
+                    (shape.cols, shape.rows)
+                } else {
+                    (shape.rows, shape.cols)
+                };
+                let player = transform(t, shape.rows, shape.cols, self.circles[player_idx]);
+                let others: Vec<(usize, usize)> = self
+                    .circles
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != player_idx)
+                    .map(|(_, &p)| p)
+                    .collect();
+                let mut portals: Vec<PortalPair> = shape
+                    .portals
+                    .iter()
+                    .map(|&(a, b)| {
+                        let a = transform(t, shape.rows, shape.cols, a);
+                        let b = transform(t, shape.rows, shape.cols, b);
+                        (a.min(b), a.max(b))
+                    })
+                    .collect();
+                portals.sort_unstable();
+                let line_crosses = self.crosses.len().saturating_sub(shape.blocks);
+                let (crosses, blocks) = self.crosses.split_at(line_crosses);
+                (
+                    rows,
+                    cols,
+                    map(&shape.removed),
+                    player,
+                    map(&others),
+                    map(crosses),
+                    map(&shape.ice),
+                    map(&shape.walls),
+                    portals,
+                    map(&shape.goals),
+                    (
+                        map(&shape.heavy),
+                        map(blocks),
+                        map(&shape.powerups),
+                        map(&shape.bombs),
+                    ),
+                )
+            })
+            .min()
+            .expect("at least one symmetry")
+    }
+
+    /// Text form of `canonical_form`, usable as a map key.
+    pub fn canonical_key(&self) -> String {
+        let (rows, cols, removed, player, others, crosses, ice, walls, portals, goals, extra) =
+            self.canonical_form();
+        let (heavy, blocks, powerups, bombs) = extra;
+        let mut key = format!(
+            "{}x{}|{:?}|{:?}|{:?}|{:?}",
+            rows, cols, removed, player, others, crosses
+        );
+        if !ice.is_empty() {
+            key.push_str(&format!("|ice{:?}", ice));
+        }
+        if !walls.is_empty() {
+            key.push_str(&format!("|walls{:?}", walls));
+        }
+        if !portals.is_empty() {
+            key.push_str(&format!("|portals{:?}", portals));
+        }
+        if !goals.is_empty() {
+            key.push_str(&format!("|goals{:?}", goals));
+        }
+        if !heavy.is_empty() {
+            key.push_str(&format!("|heavy{:?}", heavy));
+        }
+        if !blocks.is_empty() {
+            key.push_str(&format!("|blocks{:?}", blocks));
+        }
+        if !powerups.is_empty() {
+            key.push_str(&format!("|powerups{:?}", powerups));
+        }
+        if !bombs.is_empty() {
+            key.push_str(&format!("|bombs{:?}", bombs));
+        }
+        // Classic puzzles keep the hashes they had before ice, walls, portals and rule
+        // variants existed
+        if !self.shape.rules.is_classic() {
+            key.push_str(&format!("|{:?}", self.shape.rules));
+        }
+        key
+    }
+
+    /// Stable 64-bit FNV-1a hash of `canonical_key`. Unlike `DefaultHasher`
+    /// it does not change between Rust releases, so it can be stored in packs.
+    pub fn canonical_hash(&self) -> u64 {
+        self.canonical_key()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325, |h, b| {
+                (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3x4 with a hole, a heavy cross, a block, a power-up, a bomb and a
+    // portal pair, so every field goes through the symmetries
+    const VARIANTS: &str = r#"{"rows":3,"cols":4,"removed":[[0,3]],"circles":[[0,0],[1,1],[2,3]],
+        "crosses":[[1,2],[2,0],[0,2]],"player":[1,1],"heavy":[[2,0]],"blocks":1,
+        "powerups":[[2,1]],"bombs":[[1,3]],"portals":[[[0,1],[2,2]]],"ice":[[1,0]]}"#;
+
+    const CLASSIC: &str = r#"{"rows":3,"cols":4,"removed":[[0,3]],"circles":[[0,0],[1,1],[2,3]],
+        "crosses":[[1,2],[2,0]],"player":[1,1]}"#;
+
+    /// `file` mirrored or rotated by symmetry `t`.
+    fn transformed(file: &PuzzleFile, t: usize) -> PuzzleFile {
+        let (rows, cols) = (file.shape.rows, file.shape.cols);
+        let map = |v: &[(usize, usize)]| -> Vec<(usize, usize)> {
+            v.iter().map(|&p| transform(t, rows, cols, p)).collect()
+        };
+        let mut out = file.clone();
+        if t & 4 != 0 {
+            (out.shape.rows, out.shape.cols) = (cols, rows);
+        }
+        out.shape.removed = map(&file.shape.removed);
+        out.shape.ice = map(&file.shape.ice);
+        out.shape.heavy = map(&file.shape.heavy);
+        out.shape.powerups = map(&file.shape.powerups);
+        out.shape.bombs = map(&file.shape.bombs);
+        out.shape.portals = file
+            .shape
+            .portals
+            .iter()
+            .map(|&(a, b)| (transform(t, rows, cols, a), transform(t, rows, cols, b)))
+            .collect();
+        out.circles = map(&file.circles);
+        out.crosses = map(&file.crosses);
+        out.player = file.player.map(|p| transform(t, rows, cols, p));
+        out
+    }
+
+    #[test]
+    fn symmetries_share_the_canonical_hash() {
+        for json in [CLASSIC, VARIANTS] {
+            let file = PuzzleFile::parse(json).unwrap();
+            file.check(&file.board().unwrap()).unwrap();
+            for t in 0..SYMMETRIES {
+                let other = transformed(&file, t);
+                other.check(&other.board().unwrap()).unwrap();
+                assert_eq!(
+                    other.canonical_hash(),
+                    file.canonical_hash(),
+                    "symmetry {}",
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn classic_puzzles_keep_their_key() {
+        let file = PuzzleFile::parse(CLASSIC).unwrap();
+        let key = file.canonical_key();
+        // the format of the first canonical keys: size, holes, player, other
+        // circles and crosses
+        assert_eq!(key, "3x4|[(0, 0)]|(1, 2)|[(0, 3), (2, 0)]|[(1, 1), (2, 3)]");
+        for suffix in ["|heavy", "|blocks", "|powerups", "|bombs", "|Rules"] {
+            assert!(!key.contains(suffix), "{} in {}", suffix, key);
+        }
+        let key = PuzzleFile::parse(VARIANTS).unwrap().canonical_key();
+        for suffix in [
+            "|ice",
+            "|portals",
+            "|heavy",
+            "|blocks",
+            "|powerups",
+            "|bombs",
+        ] {
+            assert!(key.contains(suffix), "no {} in {}", suffix, key);
+        }
+    }
+}
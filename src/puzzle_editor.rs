@@ -1,27 +1,70 @@
-use crate::CrosstermBackend;
 use crate::Error;
-use crate::Terminal;
+use crate::board::{Board, PortalPair};
 use crate::browser;
-use crate::io::Stdout;
-use crate::puzzle_editor::event::Event;
-use crossterm::event;
-use crossterm::event::KeyCode;
+use crate::generator::verify_puzzle;
+use crate::input;
+use crate::puzzle::PuzzleFile;
+use crate::rules::{MAX_CIRCLES, MIN_CIRCLES};
+use crossterm::event::{Event, KeyCode};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Alignment, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Span, Spans};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use std::fs::{File, create_dir_all};
-use std::io::Write;
+use std::io::{Stdout, Write};
 use std::path::PathBuf;
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Number of separate regions holes and walls split the board into, when
+/// there is more than one.
+fn split_regions(
+    dims: (usize, usize),
+    removed: &[(usize, usize)],
+    walls: &[(usize, usize)],
+) -> Option<usize> {
+    let missing: Vec<(usize, usize)> = removed.iter().chain(walls).copied().collect();
+    let regions = Board::from_dims(dims.0, dims.1, &missing)
+        .ok()?
+        .components()
+        .len();
+    (regions > 1).then_some(regions)
+}
+
+/// The checks every puzzle file must pass (see `verify_puzzle`), on the
+/// editor's JSON before it is saved.
+fn verify(json: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let puzzle = PuzzleFile::parse(json)?;
+    let board = puzzle.board()?;
+    puzzle.check(&board)?;
+    let report = verify_puzzle(
+        &board,
+        &puzzle.circles,
+        &puzzle.crosses,
+        puzzle.player_idx(),
+    );
+    match report.problem() {
+        Some(problem) => Err(problem.into()),
+        None => Ok(()),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn puzzle_to_json(
     rows: usize,
     cols: usize,
     circles: &[(usize, usize)],
     crosses: &[(usize, usize)],
+    blocks: &[(usize, usize)],
     removed: &[(usize, usize)],
+    walls: &[(usize, usize)],
+    ice: &[(usize, usize)],
+    heavy: &[(usize, usize)],
+    powerups: &[(usize, usize)],
+    bombs: &[(usize, usize)],
+    portals: &[PortalPair],
     player: Option<(usize, usize)>,
     created_at: u64,
 ) -> String {
@@ -30,8 +73,10 @@ fn puzzle_to_json(
         .iter()
         .map(|&(r, c)| serde_json::json!([r, c]))
         .collect();
+    // blocks are listed after the crosses and counted (see `BoardShape::blocks`)
     let crosses_json: Vec<serde_json::Value> = crosses
         .iter()
+        .chain(blocks)
         .map(|&(r, c)| serde_json::json!([r, c]))
         .collect();
     let removed_json: Vec<serde_json::Value> = removed
@@ -44,7 +89,7 @@ fn puzzle_to_json(
         serde_json::Value::Null
     };
 
-    let obj = serde_json::json!({
+    let mut obj = serde_json::json!({
         "rows": rows,
         "cols": cols,
         "created_at": created_at,
@@ -53,6 +98,46 @@ fn puzzle_to_json(
         "removed": removed_json,
         "player": player_json
     });
+    // only written when used, so plain puzzles look as they always did
+    if !walls.is_empty() {
+        obj["walls"] = walls
+            .iter()
+            .map(|&(r, c)| serde_json::json!([r, c]))
+            .collect();
+    }
+    if !ice.is_empty() {
+        obj["ice"] = ice
+            .iter()
+            .map(|&(r, c)| serde_json::json!([r, c]))
+            .collect();
+    }
+    if !blocks.is_empty() {
+        obj["blocks"] = serde_json::json!(blocks.len());
+    }
+    if !heavy.is_empty() {
+        obj["heavy"] = heavy
+            .iter()
+            .map(|&(r, c)| serde_json::json!([r, c]))
+            .collect();
+    }
+    if !powerups.is_empty() {
+        obj["powerups"] = powerups
+            .iter()
+            .map(|&(r, c)| serde_json::json!([r, c]))
+            .collect();
+    }
+    if !bombs.is_empty() {
+        obj["bombs"] = bombs
+            .iter()
+            .map(|&(r, c)| serde_json::json!([r, c]))
+            .collect();
+    }
+    if !portals.is_empty() {
+        obj["portals"] = portals
+            .iter()
+            .map(|&(a, b)| serde_json::json!([[a.0, a.1], [b.0, b.1]]))
+            .collect();
+    }
 
     serde_json::to_string(&obj).unwrap_or_default()
 }
@@ -75,6 +160,17 @@ pub fn show_create_placeholder(
     let mut circles: Vec<(usize, usize)> = Vec::new();
     let mut crosses: Vec<(usize, usize)> = Vec::new();
     let mut removed: Vec<(usize, usize)> = Vec::new();
+    let mut ice: Vec<(usize, usize)> = Vec::new();
+    let mut powerups: Vec<(usize, usize)> = Vec::new();
+    let mut bombs: Vec<(usize, usize)> = Vec::new();
+    let mut walls: Vec<(usize, usize)> = Vec::new();
+    // neutral blocks, kept apart from the crosses until the puzzle is saved
+    let mut blocks: Vec<(usize, usize)> = Vec::new();
+    // crosses that can't be moved; always a subset of `crosses`
+    let mut heavy: Vec<(usize, usize)> = Vec::new();
+    let mut portals: Vec<PortalPair> = Vec::new();
+    // first end of a portal pair still waiting for its partner
+    let mut portal_start: Option<(usize, usize)> = None;
     // track a single player O (optional)
     let mut player: Option<(usize, usize)> = None;
     let mut error_msg: Option<String> = None;
@@ -96,7 +192,15 @@ pub fn show_create_placeholder(
                 &cursor,
                 &circles,
                 &crosses,
+                &blocks,
                 &removed,
+                &walls,
+                &ice,
+                &heavy,
+                &powerups,
+                &bombs,
+                &portals,
+                portal_start,
                 player,
             ));
             lines.push(Spans::from(Span::raw("")));
@@ -113,6 +217,21 @@ pub fn show_create_placeholder(
                 " Backspace on empty cell to delete. ",
             )));
             lines.push(Spans::from(Span::raw(" Space on empty cell to add. ")));
+            lines.push(Spans::from(Span::raw(
+                " Press I to toggle ice under the cursor. ",
+            )));
+            lines.push(Spans::from(Span::raw(" Press # to toggle a wall. ")));
+            lines.push(Spans::from(Span::raw(
+                " Press H on a cross to make it heavy. ",
+            )));
+            lines.push(Spans::from(Span::raw(
+                " Press B to place a neutral block. ",
+            )));
+            lines.push(Spans::from(Span::raw(" Press P to toggle a power-up. ")));
+            lines.push(Spans::from(Span::raw(" Press * to toggle a bomb. ")));
+            lines.push(Spans::from(Span::raw(
+                " Press T on two cells to link portals. ",
+            )));
             lines.push(Spans::from(Span::raw(" Press R to restore all cells. ")));
             lines.push(Spans::from(Span::raw(" Press Enter to save puzzle. ")));
             lines.push(Spans::from(Span::raw("Press q or Esc to return.")));
@@ -188,9 +307,10 @@ pub fn show_create_placeholder(
             }
         })?;
 
-        if event::poll(Duration::from_millis(150))?
-            && let Event::Key(key) = event::read()?
-        {
+        for event in input::drain(Duration::from_millis(150))? {
+            let Event::Key(key) = event else {
+                continue;
+            };
             if error_msg.is_some() {
                 // clear error popup on any key press
                 error_msg = None;
@@ -204,6 +324,42 @@ pub fn show_create_placeholder(
                     | KeyCode::Char('O')
                     | KeyCode::Char('x')
                     | KeyCode::Char('X')
+                    | KeyCode::Backspace
+                        if cursor.first().is_some_and(|p| walls.contains(p)) =>
+                    {
+                        // walls hold no pieces; Backspace turns one back into floor
+                        if key.code == KeyCode::Backspace {
+                            walls.retain(|p| Some(p) != cursor.first());
+                        }
+                    }
+                    KeyCode::Backspace if cursor.first().is_some_and(|p| blocks.contains(p)) => {
+                        blocks.retain(|p| Some(p) != cursor.first());
+                    }
+                    KeyCode::Char('#') => {
+                        // Toggle a wall under the cursor, clearing whatever was there
+                        if let Some(&pos) = cursor.first() {
+                            if let Some(idx) = walls.iter().position(|&p| p == pos) {
+                                walls.remove(idx);
+                            } else {
+                                walls.push(pos);
+                                circles.retain(|&p| p != pos);
+                                crosses.retain(|&p| p != pos);
+                                blocks.retain(|&p| p != pos);
+                                removed.retain(|&p| p != pos);
+                                ice.retain(|&p| p != pos);
+                                powerups.retain(|&p| p != pos);
+                                bombs.retain(|&p| p != pos);
+                                portals.retain(|&(a, b)| a != pos && b != pos);
+                                if player == Some(pos) {
+                                    player = None;
+                                }
+                            }
+                        }
+                    }
+                    KeyCode::Char('o')
+                    | KeyCode::Char('O')
+                    | KeyCode::Char('x')
+                    | KeyCode::Char('X')
                     | KeyCode::Backspace => edit_cell(
                         key.code,
                         &cursor,
@@ -212,6 +368,90 @@ pub fn show_create_placeholder(
                         &mut removed,
                         &mut player,
                     ),
+                    KeyCode::Char('b') | KeyCode::Char('B') => {
+                        // Place a neutral block, replacing the piece under the cursor
+                        if let Some(&pos) = cursor.first()
+                            && !removed.contains(&pos)
+                            && !blocks.contains(&pos)
+                        {
+                            circles.retain(|&p| p != pos);
+                            crosses.retain(|&p| p != pos);
+                            if player == Some(pos) {
+                                player = None;
+                            }
+                            blocks.push(pos);
+                        }
+                    }
+                    KeyCode::Char('p') | KeyCode::Char('P') => {
+                        // Toggle a power-up tile on the present cell under the cursor
+                        if let Some(&pos) = cursor.first()
+                            && !removed.contains(&pos)
+                            && !walls.contains(&pos)
+                        {
+                            if let Some(idx) = powerups.iter().position(|&p| p == pos) {
+                                powerups.remove(idx);
+                            } else {
+                                powerups.push(pos);
+                            }
+                        }
+                    }
+                    KeyCode::Char('*') => {
+                        // Toggle a bomb on the present cell under the cursor
+                        if let Some(&pos) = cursor.first()
+                            && !removed.contains(&pos)
+                            && !walls.contains(&pos)
+                        {
+                            if let Some(idx) = bombs.iter().position(|&p| p == pos) {
+                                bombs.remove(idx);
+                            } else {
+                                bombs.push(pos);
+                            }
+                        }
+                    }
+                    KeyCode::Char('h') | KeyCode::Char('H') => {
+                        // Toggle whether the cross under the cursor can be moved
+                        if let Some(&pos) = cursor.first()
+                            && crosses.contains(&pos)
+                        {
+                            if let Some(idx) = heavy.iter().position(|&p| p == pos) {
+                                heavy.remove(idx);
+                            } else {
+                                heavy.push(pos);
+                            }
+                        }
+                    }
+                    KeyCode::Char('i') | KeyCode::Char('I') => {
+                        // Toggle ice on the present cell under the cursor
+                        if let Some(&pos) = cursor.first()
+                            && !removed.contains(&pos)
+                            && !walls.contains(&pos)
+                        {
+                            if let Some(idx) = ice.iter().position(|&p| p == pos) {
+                                ice.remove(idx);
+                            } else {
+                                ice.push(pos);
+                            }
+                        }
+                    }
+                    KeyCode::Char('t') | KeyCode::Char('T') => {
+                        // Mark one end of a portal pair, or unlink the pair under the cursor
+                        if let Some(&pos) = cursor.first()
+                            && !removed.contains(&pos)
+                            && !walls.contains(&pos)
+                        {
+                            if let Some(idx) =
+                                portals.iter().position(|&(a, b)| a == pos || b == pos)
+                            {
+                                portals.remove(idx);
+                            } else if portal_start == Some(pos) {
+                                portal_start = None;
+                            } else if let Some(start) = portal_start.take() {
+                                portals.push((start, pos));
+                            } else {
+                                portal_start = Some(pos);
+                            }
+                        }
+                    }
                     KeyCode::Char('+') | KeyCode::Char('=') => {
                         // Increase matrix size (append to bottom/right)
                         increase_preview(&mut preview);
@@ -225,6 +465,16 @@ pub fn show_create_placeholder(
                             &mut removed,
                             &mut player,
                         );
+                        ice.retain(|&(r, c)| r < preview.0 && c < preview.1);
+                        blocks.retain(|&(r, c)| r < preview.0 && c < preview.1);
+                        powerups.retain(|&(r, c)| r < preview.0 && c < preview.1);
+                        bombs.retain(|&(r, c)| r < preview.0 && c < preview.1);
+                        walls.retain(|&(r, c)| r < preview.0 && c < preview.1);
+                        portals.retain(|&(a, b)| {
+                            a.0 < preview.0 && a.1 < preview.1 && b.0 < preview.0 && b.1 < preview.1
+                        });
+                        portal_start =
+                            portal_start.filter(|&(r, c)| r < preview.0 && c < preview.1);
                         // Ensure cursor remains within bounds
                         if let Some(pos) = cursor.get_mut(0) {
                             pos.0 = std::cmp::min(pos.0, preview.0.saturating_sub(1));
@@ -241,21 +491,37 @@ pub fn show_create_placeholder(
                     }
                     KeyCode::Enter => {
                         // Validate circle count before saving
-                        if circles.len() != 3 {
+                        if !(MIN_CIRCLES..=MAX_CIRCLES).contains(&circles.len()) {
                             error_msg = Some(format!(
-                                "Puzzle must contain exactly 3 circles; found {}.",
+                                "Puzzle must contain {} to {} circles; found {}.",
+                                MIN_CIRCLES,
+                                MAX_CIRCLES,
                                 circles.len()
                             ));
+                        } else if let Some(regions) = split_regions(preview, &removed, &walls) {
+                            error_msg = Some(format!(
+                                "Holes and walls split the board into {} parts; connect them first.",
+                                regions
+                            ));
                         } else {
                             // Serialize and save puzzle as JSON
                             let now = SystemTime::now()
                                 .duration_since(UNIX_EPOCH)
                                 .unwrap_or_default()
                                 .as_secs();
+                            // ice on cells deleted later is dropped
+                            ice.retain(|p| !removed.contains(p));
+                            powerups.retain(|p| !removed.contains(p));
+                            bombs.retain(|p| !removed.contains(p));
+                            portals.retain(|(a, b)| !removed.contains(a) && !removed.contains(b));
+                            // walls are missing cells as far as play goes
+                            let missing: Vec<(usize, usize)> =
+                                removed.iter().chain(walls.iter()).copied().collect();
                             let json = puzzle_to_json(
-                                preview.0, preview.1, &circles, &crosses, &removed, player, now,
+                                preview.0, preview.1, &circles, &crosses, &blocks, &missing,
+                                &walls, &ice, &heavy, &powerups, &bombs, &portals, player, now,
                             );
-                            match save_puzzle_to_file(&json, now) {
+                            match verify(&json).and_then(|()| save_puzzle_to_file(&json, now)) {
                                 Ok(path) => {
                                     success_msg =
                                         Some(format!("Saved puzzle to {}", path.display()));
@@ -273,6 +539,9 @@ pub fn show_create_placeholder(
                     }
                     code => move_cursor(&mut cursor, code, preview.0, preview.1),
                 }
+                // a cross that was deleted or replaced takes its weight with it
+                heavy.retain(|p| crosses.contains(p));
+                blocks.retain(|p| !circles.contains(p) && !crosses.contains(p));
             }
         }
     }
@@ -305,9 +574,9 @@ fn edit_cell(
                 *player = Some(pos);
                 return;
             }
-            // add circle if missing, but enforce a maximum of 3
+            // add circle if missing, but enforce the maximum
             if !circles.contains(&pos) {
-                if circles.len() >= 3 {
+                if circles.len() >= MAX_CIRCLES {
                     // limit reached; do not add another circle
                     return;
                 }
@@ -376,14 +645,25 @@ fn move_cursor(cursor: &mut Vec<(usize, usize)>, key: KeyCode, rows: usize, cols
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_matrix(
     size: &[(usize, usize)],
     cursor: &[(usize, usize)],
     circles: &[(usize, usize)],
     crosses: &[(usize, usize)],
+    blocks: &[(usize, usize)],
     removed: &[(usize, usize)],
+    walls: &[(usize, usize)],
+    ice: &[(usize, usize)],
+    heavy: &[(usize, usize)],
+    powerups: &[(usize, usize)],
+    bombs: &[(usize, usize)],
+    portals: &[PortalPair],
+    portal_start: Option<(usize, usize)>,
     player: Option<(usize, usize)>,
 ) -> Vec<Spans<'static>> {
+    // blocks fill a cell like crosses do; only their glyph differs
+    let crosses: Vec<(usize, usize)> = crosses.iter().chain(blocks).copied().collect();
     let mut output: Vec<Spans<'static>> = Vec::new();
 
     for (rows, cols) in size.iter().copied() {
@@ -424,7 +704,9 @@ fn create_matrix(
             let mut circle_here = vec![false; cols];
             let mut cross_here = vec![false; cols];
             let mut removed_here = vec![false; cols];
+            let mut ice_here = vec![false; cols];
             for col in 0..cols {
+                ice_here[col] = ice.contains(&(row, col));
                 circle_here[col] = circles.iter().any(|&(r, c)| r == row && c == col);
                 cross_here[col] = crosses.iter().any(|&(r, c)| r == row && c == col);
                 removed_here[col] = removed.iter().any(|&(r, c)| r == row && c == col);
@@ -433,8 +715,29 @@ fn create_matrix(
             // Content line: draw cells and separators with conditional highlighting
             let mut content_spans: Vec<Span> = Vec::new();
             for col in 0..cols {
+                // portal pairs are lettered A, B, ...; a half-made pair shows '*'
+                let portal_label = if portal_start == Some((row, col)) {
+                    Some("*".to_string())
+                } else {
+                    portals
+                        .iter()
+                        .position(|&(a, b)| a == (row, col) || b == (row, col))
+                        .map(|i| ((b'A' + (i % 26) as u8) as char).to_string())
+                };
+
+                // portals and ice cells get a tinted background, walls are solid grey
+                let bg = if walls.contains(&(row, col)) {
+                    Style::default().bg(Color::DarkGray)
+                } else if portal_label.is_some() && !removed_here[col] {
+                    Style::default().bg(Color::Magenta)
+                } else if ice_here[col] && !removed_here[col] {
+                    Style::default().bg(Color::Blue)
+                } else {
+                    Style::default()
+                };
+
                 // left padding
-                content_spans.push(Span::raw(" "));
+                content_spans.push(Span::styled(" ", bg));
 
                 // cell contents: circle, cross, cursor (only if empty and cell present), or empty/removed
                 if circle_here[col] {
@@ -442,35 +745,40 @@ fn create_matrix(
                     if is_player {
                         content_spans.push(Span::styled(
                             "o".to_string(),
-                            Style::default()
-                                .fg(Color::Yellow)
-                                .add_modifier(Modifier::BOLD),
+                            bg.fg(Color::Yellow).add_modifier(Modifier::BOLD),
                         ));
                     } else {
-                        content_spans.push(Span::styled(
-                            "o".to_string(),
-                            Style::default().fg(Color::LightBlue),
-                        ));
+                        content_spans.push(Span::styled("o".to_string(), bg.fg(Color::LightBlue)));
                     }
+                } else if blocks.contains(&(row, col)) {
+                    content_spans.push(Span::styled("■".to_string(), bg.fg(Color::Gray)));
                 } else if cross_here[col] {
-                    content_spans.push(Span::styled(
-                        "x".to_string(),
-                        Style::default().fg(Color::Red),
-                    ));
+                    let glyph = if heavy.contains(&(row, col)) {
+                        "X"
+                    } else {
+                        "x"
+                    };
+                    content_spans.push(Span::styled(glyph.to_string(), bg.fg(Color::Red)));
                 } else if cursor.contains(&(row, col)) && !removed_here[col] {
                     content_spans.push(Span::styled(
                         "●",
-                        Style::default()
-                            .fg(Color::Yellow)
-                            .add_modifier(Modifier::BOLD),
+                        bg.fg(Color::Yellow).add_modifier(Modifier::BOLD),
                     ));
+                } else if let Some(label) = portal_label.filter(|_| !removed_here[col]) {
+                    content_spans.push(Span::styled(label, bg.fg(Color::White)));
+                } else if powerups.contains(&(row, col)) && !removed_here[col] {
+                    content_spans.push(Span::styled("+", bg.fg(Color::LightGreen)));
+                } else if bombs.contains(&(row, col)) && !removed_here[col] {
+                    content_spans.push(Span::styled("*", bg.fg(Color::LightRed)));
+                } else if ice_here[col] && !removed_here[col] {
+                    content_spans.push(Span::styled("~", bg.fg(Color::LightCyan)));
                 } else {
-                    // empty or removed cell; display blank content
-                    content_spans.push(Span::raw(" "));
+                    // empty, removed or wall cell; display blank content
+                    content_spans.push(Span::styled(" ", bg));
                 }
 
                 // right padding
-                content_spans.push(Span::raw(" "));
+                content_spans.push(Span::styled(" ", bg));
 
                 // separator between cells (vertical).
                 if col + 1 < cols {
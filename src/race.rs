@@ -0,0 +1,216 @@
+// `tic-tac-go race`: two players race to solve the same puzzle over TCP.
+//
+// The host generates a seeded puzzle and sends it as one line,
+// `puzzle <json>`, where the JSON is a `GameState`. Sending the puzzle itself
+// rather than just the seed means both sides play the identical puzzle even if
+// their difficulty settings differ. After that each side reports its progress
+// as lines of `moves N`, `won N` or `lost N`.
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+
+use rand::{Rng, thread_rng};
+
+use crate::generate::parse_difficulty;
+use crate::generator::{Difficulty, generate_from_seed};
+use crate::puzzle::PuzzleFile;
+use crate::state::GameState;
+
+const DEFAULT_PORT: u16 = 7878;
+// Seeds tried before the host gives up on generating a puzzle
+const SEED_ATTEMPTS: u64 = 20;
+
+/// What the other player last reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progress {
+    Moves(usize),
+    Won(usize),
+    Lost(usize),
+    /// The connection closed
+    Left,
+}
+
+impl Progress {
+    fn encode(self) -> String {
+        match self {
+            Progress::Moves(n) => format!("moves {}\n", n),
+            Progress::Won(n) => format!("won {}\n", n),
+            Progress::Lost(n) => format!("lost {}\n", n),
+            Progress::Left => "left\n".to_string(),
+        }
+    }
+
+    fn decode(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line == "left" {
+            return Some(Progress::Left);
+        }
+        let (kind, n) = line.split_once(' ')?;
+        let n = n.parse().ok()?;
+        match kind {
+            "moves" => Some(Progress::Moves(n)),
+            "won" => Some(Progress::Won(n)),
+            "lost" => Some(Progress::Lost(n)),
+            _ => None,
+        }
+    }
+
+    /// Status line describing the opponent.
+    pub fn describe(self) -> String {
+        match self {
+            Progress::Moves(n) => format!("opponent: {} moves", n),
+            Progress::Won(n) => format!("opponent solved it in {} moves", n),
+            Progress::Lost(n) => format!("opponent lost after {} moves", n),
+            Progress::Left => "opponent left".to_string(),
+        }
+    }
+}
+
+/// Connection to the other player. Incoming lines are read on a background
+/// thread, so polling never blocks the game loop.
+pub struct Link {
+    stream: TcpStream,
+    incoming: mpsc::Receiver<Progress>,
+}
+
+impl Link {
+    fn new(stream: TcpStream, reader: BufReader<TcpStream>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else { break };
+                if let Some(p) = Progress::decode(&line)
+                    && tx.send(p).is_err()
+                {
+                    return;
+                }
+            }
+            let _ = tx.send(Progress::Left);
+        });
+        Link {
+            stream,
+            incoming: rx,
+        }
+    }
+
+    /// Report our own progress; a dropped connection shows up as `Left` on
+    /// the receiving side, so errors are ignored here.
+    pub fn send(&mut self, progress: Progress) {
+        let _ = self.stream.write_all(progress.encode().as_bytes());
+    }
+
+    /// The newest report from the opponent since the last call, if any.
+    pub fn poll(&self) -> Option<Progress> {
+        self.incoming.try_iter().last()
+    }
+}
+
+fn print_usage() {
+    eprintln!("usage: tic-tac-go race host [--port N] [--difficulty LEVEL] [--seed S]");
+    eprintln!("       tic-tac-go race join HOST[:PORT]");
+    eprintln!();
+    eprintln!(
+        "The host waits for one opponent (port {} by default) and",
+        DEFAULT_PORT
+    );
+    eprintln!("sends it a freshly generated puzzle; both sides then race to");
+    eprintln!("solve it while seeing each other's move count.");
+}
+
+fn host(args: &[String]) -> Result<(Link, GameState), Box<dyn Error>> {
+    let mut port = DEFAULT_PORT;
+    let mut difficulty = Difficulty::Medium;
+    let mut seed: u64 = thread_rng().r#gen();
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--port" | "-p" => {
+                port = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--port expects a number")?;
+            }
+            "--difficulty" | "-d" => {
+                difficulty = it
+                    .next()
+                    .and_then(|v| parse_difficulty(v))
+                    .ok_or("--difficulty expects easy, medium, hard, expert or insane")?;
+            }
+            "--seed" => {
+                seed = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--seed expects a number")?;
+            }
+            other => return Err(format!("unknown option {}", other).into()),
+        }
+    }
+
+    let rules = crate::config::get().rules;
+    let puzzle = (0..SEED_ATTEMPTS)
+        .map(|i| generate_from_seed(seed.wrapping_add(i), difficulty, rules))
+        .find(|p| !p.circles.is_empty())
+        .ok_or("could not generate a puzzle")?;
+    let board = &puzzle.board;
+    let circles: Vec<(usize, usize)> = puzzle.circles.iter().map(|&f| board.from_flat(f)).collect();
+    let crosses: Vec<(usize, usize)> = puzzle.crosses.iter().map(|&f| board.from_flat(f)).collect();
+    let file = PuzzleFile::from_parts(board, &circles, &crosses, puzzle.player_idx, None);
+    let state = GameState::from_puzzle(&file)?;
+
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    eprintln!(
+        "waiting for an opponent on port {} (seed {})...",
+        port, puzzle.seed
+    );
+    let (mut stream, addr) = listener.accept()?;
+    eprintln!("{} joined", addr);
+    stream.write_all(format!("puzzle {}\n", serde_json::to_string(&state)?).as_bytes())?;
+    let reader = BufReader::new(stream.try_clone()?);
+    Ok((Link::new(stream, reader), state))
+}
+
+fn join(args: &[String]) -> Result<(Link, GameState), Box<dyn Error>> {
+    let addr = args.first().ok_or("join expects HOST[:PORT]")?;
+    let addr = if addr.contains(':') {
+        addr.clone()
+    } else {
+        format!("{}:{}", addr, DEFAULT_PORT)
+    };
+    let stream = TcpStream::connect(&addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let json = line
+        .strip_prefix("puzzle ")
+        .ok_or("the host did not send a puzzle")?;
+    let state = GameState::parse(json)?;
+    Ok((Link::new(stream, reader), state))
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (mut link, state) = match args.first().map(String::as_str) {
+        Some("host") => host(&args[1..])?,
+        Some("join") => join(&args[1..])?,
+        Some("-h") | Some("--help") => {
+            print_usage();
+            return Ok(());
+        }
+        _ => {
+            print_usage();
+            return Err("race expects host or join".into());
+        }
+    };
+    let _ = crate::logging::init();
+    let (mut terminal, _guard) = crate::term::enter()?;
+    crate::game::run_puzzle(
+        &mut terminal,
+        state.board,
+        state.circles,
+        state.crosses,
+        state.player_idx,
+        Some(&mut link),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+// `tic-tac-go rate`: print the difficulty score of puzzle files, using the same
+// model the generator uses to pick puzzles for each difficulty.
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::puzzle::PuzzleFile;
+use crate::rating::{Rating, rate};
+
+const DEFAULT_MAX_NODES: usize = 500_000;
+
+fn print_usage() {
+    eprintln!("usage: tic-tac-go rate FILE... [--json] [--max-nodes N]");
+    eprintln!();
+    eprintln!("Scores each puzzle from its optimal solution: moves, pushes and");
+    eprintln!("positions one move away from a loss. A directory rates every");
+    eprintln!("*.json file in it.");
+}
+
+fn expand(path: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(path)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn rate_file(path: &Path, max_nodes: usize) -> Result<Rating, Box<dyn Error>> {
+    let puzzle = PuzzleFile::load(path)?;
+    let board = puzzle.board()?;
+    puzzle.check(&board)?;
+    rate(
+        &board,
+        &puzzle.circles,
+        &puzzle.crosses,
+        puzzle.player_idx(),
+        max_nodes,
+    )
+    .ok_or_else(|| format!("no solution within {} states", max_nodes).into())
+}
+
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut as_json = false;
+    let mut max_nodes = DEFAULT_MAX_NODES;
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--json" => as_json = true,
+            "--max-nodes" => {
+                max_nodes = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--max-nodes expects a number")?;
+            }
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(());
+            }
+            other if other.starts_with('-') => {
+                print_usage();
+                return Err(format!("unknown option {}", other).into());
+            }
+            other => paths.extend(expand(Path::new(other))?),
+        }
+    }
+    if paths.is_empty() {
+        print_usage();
+        return Err("no puzzle files given".into());
+    }
+
+    let mut results: Vec<Value> = Vec::new();
+    for path in &paths {
+        let file = path.display().to_string();
+        match rate_file(path, max_nodes) {
+            Ok(r) => {
+                if !as_json {
+                    println!(
+                        "{}: score {:.1} ({:?}) - {} moves, {} pushes, {} near losses, {} circles moved, {} cross moves, {} pieces touched",
+                        file,
+                        r.score(),
+                        r.difficulty(),
+                        r.moves,
+                        r.pushes,
+                        r.near_losses,
+                        r.circles_moved,
+                        r.cross_moves,
+                        r.pieces_touched
+                    );
+                }
+                results.push(json!({
+                    "file": file,
+                    "score": r.score(),
+                    "difficulty": format!("{:?}", r.difficulty()),
+                    "moves": r.moves,
+                    "pushes": r.pushes,
+                    "near_losses": r.near_losses,
+                    "circles_moved": r.circles_moved,
+                    "cross_moves": r.cross_moves,
+                    "pieces_touched": r.pieces_touched,
+                }));
+            }
+            Err(e) => {
+                if !as_json {
+                    println!("{}: {}", file, e);
+                }
+                results.push(json!({ "file": file, "error": e.to_string() }));
+            }
+        }
+    }
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    }
+    Ok(())
+}
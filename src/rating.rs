@@ -0,0 +1,128 @@
+// Difficulty scoring for puzzles.
+//
+// Solution length alone is a poor difficulty measure: a long walk across an
+// empty board is easy, while a short solution that threads pushes past crosses
+// on the edge of a losing line is not. The score combines three things read
+// off one optimal solution:
+//   moves        - length of the optimal solution
+//   pushes       - how many of those moves push (or pull) a piece
+//   near losses  - positions along the solution where some available move
+//                  would complete a line of crosses
+// Along with it come a few counts that say how much of the board the solution
+// involves, which the generator uses to turn down puzzles that are little
+// more than a walk.
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::generator::Difficulty;
+use crate::movement::legal_moves;
+use crate::rules::check_lose_flat;
+use crate::solver;
+
+const PUSH_WEIGHT: f64 = 1.0;
+const NEAR_LOSS_WEIGHT: f64 = 2.0;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Rating {
+    pub moves: usize,
+    pub pushes: usize,
+    pub near_losses: usize,
+    /// Circles other than the player's that the solution moves
+    pub circles_moved: usize,
+    /// Moves of the solution that move a cross
+    pub cross_moves: usize,
+    /// Distinct pieces besides the player that the solution moves
+    pub pieces_touched: usize,
+}
+
+impl Rating {
+    pub fn score(&self) -> f64 {
+        self.moves as f64
+            + PUSH_WEIGHT * self.pushes as f64
+            + NEAR_LOSS_WEIGHT * self.near_losses as f64
+    }
+
+    /// Highest tier whose score range starts at or below this rating (Easy
+    /// for anything below).
+    pub fn difficulty(&self) -> Difficulty {
+        let score = self.score();
+        Difficulty::ALL
+            .into_iter()
+            .rev()
+            .find(|&d| score >= score_range(d).0)
+            .unwrap_or(Difficulty::Easy)
+    }
+
+    /// Whether the solution is little more than a walk: no other circle
+    /// moves, or a single push of a single piece does it.
+    pub fn is_walk(&self) -> bool {
+        self.circles_moved == 0 || (self.pushes <= 1 && self.pieces_touched <= 1)
+    }
+}
+
+/// Scores the generator accepts for a difficulty, as `[min, max)`.
+pub fn score_range(difficulty: Difficulty) -> (f64, f64) {
+    let params = difficulty.params();
+    (params.min_score, params.max_score.unwrap_or(f64::INFINITY))
+}
+
+fn losing_move_available(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+) -> bool {
+    legal_moves(circles, crosses, player_idx, 0, board)
+        .iter()
+        .any(|(_, m)| {
+            let crosses_flat: Vec<usize> = m
+                .crosses
+                .iter()
+                .map(|&(r, c)| board.to_flat(r, c))
+                .collect();
+            check_lose_flat(&crosses_flat, board)
+        })
+}
+
+/// Rate a position by replaying an optimal solution. Returns `None` when the
+/// solver finds no solution within `max_nodes` states.
+pub fn rate(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    max_nodes: usize,
+) -> Option<Rating> {
+    let steps = solver::solve(board, circles, crosses, player_idx, max_nodes)?;
+    let mut circles = circles.to_vec();
+    let mut crosses = crosses.to_vec();
+    let mut near_losses = 0;
+    let mut moved = vec![false; circles.len()];
+    let mut crosses_moved = vec![false; crosses.len()];
+    let mut cross_moves = 0;
+    for step in &steps {
+        if losing_move_available(board, &circles, &crosses, player_idx) {
+            near_losses += 1;
+        }
+        let (circles_before, crosses_before) = (circles.clone(), crosses.clone());
+        step.apply(&mut circles, &mut crosses, player_idx, board);
+        for (i, (a, b)) in circles_before.iter().zip(&circles).enumerate() {
+            moved[i] |= i != player_idx && a != b;
+        }
+        for (i, (a, b)) in crosses_before.iter().zip(&crosses).enumerate() {
+            crosses_moved[i] |= a != b;
+        }
+        if crosses_before != crosses {
+            cross_moves += 1;
+        }
+    }
+    let circles_moved = moved.iter().filter(|&&m| m).count();
+    Some(Rating {
+        moves: steps.len(),
+        pushes: steps.iter().filter(|s| s.push.is_some()).count(),
+        near_losses,
+        circles_moved,
+        cross_moves,
+        pieces_touched: circles_moved + crosses_moved.iter().filter(|&&m| m).count(),
+    })
+}
@@ -1,140 +1,533 @@
 // Game rules and search helpers
+use crate::ai::Strength;
 use crate::board::Board;
-use std::collections::{HashMap, HashSet};
+use rand::RngCore;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
-pub fn is_win_flat(positions: &[usize], board: &Board) -> bool {
-    if positions.len() < 3 {
-        return false;
+/// Puzzles have at least three circles (enough for one line) and at most
+/// five; any three of them in a line win.
+pub const MIN_CIRCLES: usize = 3;
+pub const MAX_CIRCLES: usize = 5;
+
+/// Optional rule variants. They travel with the `Board`, so everything that
+/// evaluates a position (game, generator, solver) agrees on them, and puzzle
+/// files record them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    /// Three in a row diagonally also counts, for wins and losses alike
+    pub diagonals: bool,
+    /// Pushing into a row of adjacent pieces shifts the whole row by one
+    pub chain_push: bool,
+    /// The player may also pull the piece behind it while stepping away
+    pub pull: bool,
+    /// After every player move a cross steps toward a line (see `ai`). The
+    /// solver and generator ignore it and plan as if crosses stood still.
+    pub adversarial: bool,
+    /// How far ahead the adversarial crosses look
+    pub ai_strength: Strength,
+    /// Running out of moves loses; the budget comes from the puzzle's par
+    /// (see `move_budget`)
+    pub move_limit: bool,
+    /// Toroidal board: moving or pushing off one edge comes back in on the
+    /// opposite edge, and lines may run across the seam (see `Board::wrap`)
+    pub wrap: bool,
+    /// A cross pushed into a hole falls in and is out of play for good;
+    /// circles are still stopped by holes. A fallen cross keeps its place in
+    /// the crosses, on the hole's cell, where no check counts it (see
+    /// `in_play`).
+    pub sinkholes: bool,
+    /// Every move steps all circles at once, not just the player's (see
+    /// `unison`)
+    pub unison: bool,
+    /// How the game is won and lost (see `RuleSet`); diagonal lines count in
+    /// every variant when `diagonals` is on
+    pub variant: Variant,
+}
+
+/// The rule sets a game can be played with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// Three circles in a line win, three crosses in a line lose
+    #[default]
+    Classic,
+    /// The same with lines of four
+    FourInARow,
+    /// Circles win by covering every goal cell of the board; three crosses
+    /// in a line still lose
+    GoalCells,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 3] = [Variant::Classic, Variant::FourInARow, Variant::GoalCells];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Variant::Classic => "classic",
+            Variant::FourInARow => "four in a row",
+            Variant::GoalCells => "goal cells",
+        }
     }
-    let mut by_row: HashMap<usize, Vec<usize>> = HashMap::new();
-    let mut by_col: HashMap<usize, Vec<usize>> = HashMap::new();
-    for &p in positions {
-        let (r, c) = board.from_flat(p);
-        by_row.entry(r).or_default().push(c);
-        by_col.entry(c).or_default().push(r);
+
+    /// The variant after this one, for cycling through them in the menu.
+    pub fn next(self) -> Variant {
+        let i = Variant::ALL.iter().position(|&v| v == self).unwrap_or(0);
+        Variant::ALL[(i + 1) % Variant::ALL.len()]
     }
-    for (_r, mut cols_vec) in by_row.into_iter() {
-        if cols_vec.len() < 3 {
-            continue;
+
+    /// Parse a variant as written in config files, e.g. `four_in_a_row`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "classic" => Some(Variant::Classic),
+            "four_in_a_row" => Some(Variant::FourInARow),
+            "goal_cells" => Some(Variant::GoalCells),
+            _ => None,
         }
-        cols_vec.sort_unstable();
-        for i in 0..cols_vec.len().saturating_sub(2) {
-            if cols_vec[i + 1] == cols_vec[i] + 1 && cols_vec[i + 2] == cols_vec[i + 1] + 1 {
-                return true;
-            }
+    }
+}
+
+/// Moves allowed under the move-limit rule for a puzzle whose optimal
+/// solution takes `par` moves: half as many again, and at least five spare.
+pub fn move_budget(par: usize) -> usize {
+    par + (par / 2).max(5)
+}
+
+impl Rules {
+    pub fn is_classic(&self) -> bool {
+        *self == Rules::default()
+    }
+
+    /// Names of the variants these rules add to the classic game, as listed
+    /// next to the difficulty.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        if self.variant != Variant::Classic {
+            names.push(self.variant.label().to_string());
+        }
+        let flags = [
+            (self.diagonals, "diagonals"),
+            (self.chain_push, "chain push"),
+            (self.pull, "pulls"),
+            (self.wrap, "wrap-around"),
+            (self.sinkholes, "sinkholes"),
+            (self.unison, "unison"),
+        ];
+        names.extend(flags.iter().filter(|f| f.0).map(|f| f.1.to_string()));
+        if self.adversarial {
+            names.push(format!("{} crosses", self.ai_strength.label()));
         }
+        names
+    }
+
+    /// Kinds of move available in every direction: the usual walk or push
+    /// (`false`), plus a pull (`true`) when the pull rule is on.
+    pub fn pull_choices(&self) -> &'static [bool] {
+        if self.pull { &[false, true] } else { &[false] }
     }
-    for (_c, mut rows_vec) in by_col.into_iter() {
-        if rows_vec.len() < 3 {
-            continue;
+
+    /// The rule set deciding wins and losses under these rules.
+    pub fn rule_set(&self) -> &'static dyn RuleSet {
+        match self.variant {
+            Variant::Classic => &CLASSIC,
+            Variant::FourInARow => &FOUR_IN_A_ROW,
+            Variant::GoalCells => &GOAL_CELLS,
         }
-        rows_vec.sort_unstable();
-        for i in 0..rows_vec.len().saturating_sub(2) {
-            if rows_vec[i + 1] == rows_vec[i] + 1 && rows_vec[i + 2] == rows_vec[i + 1] + 1 {
-                return true;
-            }
+    }
+
+    /// Directions a line can run in: right and down, plus both diagonals
+    /// when enabled.
+    pub fn line_dirs(&self) -> &'static [(isize, isize)] {
+        if self.diagonals {
+            &[(0, 1), (1, 0), (1, 1), (1, -1)]
+        } else {
+            &[(0, 1), (1, 0)]
         }
     }
-    false
 }
 
-pub fn check_lose_flat(crosses: &[usize], board: &Board) -> bool {
-    if crosses.len() < 3 {
-        return false;
+/// Win, loss and deadlock checks of one variant. Positions are flat indices
+/// of `board`, whose rules picked the rule set (see `Rules::rule_set`).
+pub trait RuleSet: Sync {
+    /// Whether the circles at `circles` have won.
+    fn is_win(&self, circles: &[usize], board: &Board) -> bool;
+
+    /// Whether the crosses at `crosses` have lost the game.
+    fn is_loss(&self, crosses: &[usize], board: &Board) -> bool {
+        has_line(crosses, board, 3)
     }
-    let mut by_row: HashMap<usize, Vec<usize>> = HashMap::new();
-    let mut by_col: HashMap<usize, Vec<usize>> = HashMap::new();
-    for &p in crosses {
-        let (r, c) = board.from_flat(p);
-        by_row.entry(r).or_default().push(c);
-        by_col.entry(c).or_default().push(r);
+
+    /// Whether some cross is stuck for good (see `check_cross_deadlock`).
+    fn is_deadlocked(&self, crosses: &[usize], board: &Board) -> bool {
+        cross_trapped(crosses, board, 3)
     }
-    for (_r, mut cols_vec) in by_row.into_iter() {
-        if cols_vec.len() < 3 {
-            continue;
-        }
-        cols_vec.sort_unstable();
-        for i in 0..cols_vec.len().saturating_sub(2) {
-            if cols_vec[i + 1] == cols_vec[i] + 1 && cols_vec[i + 2] == cols_vec[i + 1] + 1 {
-                return true;
-            }
+
+    /// Groups of cells that win once each holds a circle: what the generator
+    /// starts from and the solver's lower bound aims for.
+    fn targets(&self, board: &Board) -> Vec<Vec<(usize, usize)>>;
+
+    /// Groups of cells that lose once each holds a cross.
+    fn losing_lines(&self, board: &Board) -> Vec<Vec<(usize, usize)>> {
+        lines(board, 3)
+    }
+
+    /// Circles to highlight on a won board.
+    fn winning_cells(&self, circles: &[(usize, usize)], board: &Board) -> Vec<(usize, usize)>;
+
+    /// Crosses to highlight on a lost board.
+    fn losing_cells(&self, crosses: &[(usize, usize)], board: &Board) -> Vec<(usize, usize)> {
+        line_cells(crosses, board, 3)
+    }
+
+    /// Mark whatever the variant needs on a freshly generated board, before
+    /// any piece is placed.
+    fn prepare(&self, _board: &mut Board, _rng: &mut dyn RngCore) {}
+}
+
+/// Lines of `length` pieces win for circles and lose for crosses.
+pub struct Lines {
+    pub length: usize,
+}
+
+/// Circles must cover the board's goal cells; crosses lose in lines of three.
+pub struct GoalCells;
+
+pub const CLASSIC: Lines = Lines { length: 3 };
+pub const FOUR_IN_A_ROW: Lines = Lines { length: 4 };
+pub const GOAL_CELLS: GoalCells = GoalCells;
+
+impl RuleSet for Lines {
+    fn is_win(&self, circles: &[usize], board: &Board) -> bool {
+        has_line(circles, board, self.length)
+    }
+
+    fn is_loss(&self, crosses: &[usize], board: &Board) -> bool {
+        has_line(crosses, board, self.length)
+    }
+
+    fn is_deadlocked(&self, crosses: &[usize], board: &Board) -> bool {
+        cross_trapped(crosses, board, self.length)
+    }
+
+    fn targets(&self, board: &Board) -> Vec<Vec<(usize, usize)>> {
+        lines(board, self.length)
+    }
+
+    fn losing_lines(&self, board: &Board) -> Vec<Vec<(usize, usize)>> {
+        lines(board, self.length)
+    }
+
+    fn winning_cells(&self, circles: &[(usize, usize)], board: &Board) -> Vec<(usize, usize)> {
+        line_cells(circles, board, self.length)
+    }
+
+    fn losing_cells(&self, crosses: &[(usize, usize)], board: &Board) -> Vec<(usize, usize)> {
+        line_cells(crosses, board, self.length)
+    }
+}
+
+impl RuleSet for GoalCells {
+    fn is_win(&self, circles: &[usize], board: &Board) -> bool {
+        let goals = board.goals.iter().filter(|&&g| g).count();
+        goals > 0 && circles.iter().filter(|&&p| board.goals[p]).count() == goals
+    }
+
+    fn targets(&self, board: &Board) -> Vec<Vec<(usize, usize)>> {
+        let goals = board.goal_cells();
+        if goals.is_empty() {
+            Vec::new()
+        } else {
+            vec![goals]
         }
     }
-    for (_c, mut rows_vec) in by_col.into_iter() {
-        if rows_vec.len() < 3 {
-            continue;
+
+    fn winning_cells(&self, circles: &[(usize, usize)], board: &Board) -> Vec<(usize, usize)> {
+        let goals = board.goal_cells();
+        circles
+            .iter()
+            .copied()
+            .filter(|p| goals.contains(p))
+            .collect()
+    }
+
+    /// A random line of three becomes the goal, unless the board has goals.
+    fn prepare(&self, board: &mut Board, rng: &mut dyn RngCore) {
+        if board.goals.contains(&true) {
+            return;
         }
-        rows_vec.sort_unstable();
-        for i in 0..rows_vec.len().saturating_sub(2) {
-            if rows_vec[i + 1] == rows_vec[i] + 1 && rows_vec[i + 2] == rows_vec[i + 1] + 1 {
-                return true;
+        if let Some(line) = lines(board, 3).choose(rng) {
+            board.set_goals(line);
+        }
+    }
+}
+
+/// The cells of the line of `length` starting at (r, c) along (dr, dc), or
+/// `None` when it would visit a cell twice (a short ring of a torus).
+fn line_from(
+    (r, c): (isize, isize),
+    (dr, dc): (isize, isize),
+    length: usize,
+    board: &Board,
+) -> Option<Vec<(isize, isize)>> {
+    let cells: Vec<(isize, isize)> = (0..length as isize)
+        .map(|k| board.wrap(r + k * dr, c + k * dc))
+        .collect();
+    if cells[1..].contains(&(r, c)) {
+        None
+    } else {
+        Some(cells)
+    }
+}
+
+/// Every line of `length` consecutive present cells along any direction the
+/// board's rules allow, including those that run across the seam of a
+/// toroidal board.
+pub fn lines(board: &Board, length: usize) -> Vec<Vec<(usize, usize)>> {
+    let mut found: Vec<Vec<(usize, usize)>> = Vec::new();
+    for &dir in board.rules.line_dirs() {
+        for r in 0..board.rows {
+            for c in 0..board.row_widths[r] {
+                let Some(cells) = line_from((r as isize, c as isize), dir, length, board) else {
+                    continue;
+                };
+                if cells.iter().all(|&(r, c)| board.has_cell(r, c)) {
+                    found.push(
+                        cells
+                            .iter()
+                            .map(|&(r, c)| (r as usize, c as usize))
+                            .collect(),
+                    );
+                }
             }
         }
     }
-    false
+    found
 }
 
-/// Cheap conservative deadlock checks for crosses:
-/// - detect any 2x2 filled block of crosses (unsolvable in general)
-/// - detect crosses in convex board corners (no adjacent cells in two orthogonal dirs)
-/// - detect crosses blocked on two orthogonal sides by other crosses/walls (conservative)
-pub fn check_cross_deadlock(crosses: &[usize], board: &Board) -> bool {
-    if crosses.is_empty() {
+/// Whether the piece at flat index `p` is still in play, i.e. has not fallen
+/// into a hole under the sinkhole rule.
+pub fn in_play(p: usize, board: &Board) -> bool {
+    board.cells[p]
+}
+
+/// Whether `length` of `positions` form a line under the board's rules.
+fn has_line(positions: &[usize], board: &Board, length: usize) -> bool {
+    if positions.len() < length {
         return false;
     }
-    let mut set: HashSet<(usize, usize)> = HashSet::new();
-    for &p in crosses {
-        let (r, c) = board.from_flat(p);
-        set.insert((r, c));
-    }
-
-    // 2x2 block check
-    for &(r, c) in set.iter() {
-        if c + 1 < board.row_widths[r]
-            && r + 1 < board.rows
-            && c < board.row_widths[r + 1]
-            && c + 1 < board.row_widths[r + 1]
-            && board.is_cell_present(r, c)
-            && board.is_cell_present(r, c + 1)
-            && board.is_cell_present(r + 1, c)
-            && board.is_cell_present(r + 1, c + 1)
-            && set.contains(&(r, c + 1))
-            && set.contains(&(r + 1, c))
-            && set.contains(&(r + 1, c + 1))
-        {
-            return true;
+    let set: HashSet<(isize, isize)> = positions
+        .iter()
+        .filter(|&&p| in_play(p, board))
+        .map(|&p| {
+            let (r, c) = board.from_flat(p);
+            (r as isize, c as isize)
+        })
+        .collect();
+    set.iter().any(|&(r, c)| {
+        board.rules.line_dirs().iter().any(|&(dr, dc)| {
+            // on a torus a ring shorter than the line can't hold it
+            (1..length as isize).all(|k| {
+                let cell = board.wrap(r + k * dr, c + k * dc);
+                cell != (r, c) && set.contains(&cell)
+            })
+        })
+    })
+}
+
+/// Cells of `positions` that belong to a line of `length`, e.g. to highlight
+/// the winning or losing line.
+fn line_cells(positions: &[(usize, usize)], board: &Board, length: usize) -> Vec<(usize, usize)> {
+    let at = |(r, c): (isize, isize)| {
+        board.has_cell(r, c) && positions.contains(&(r as usize, c as usize))
+    };
+    let mut cells = Vec::new();
+    for &(r, c) in positions {
+        for &dir in board.rules.line_dirs() {
+            if let Some(line) = line_from((r as isize, c as isize), dir, length, board)
+                && line.iter().all(|&cell| at(cell))
+            {
+                cells.extend(line.iter().map(|&(r, c)| (r as usize, c as usize)));
+            }
         }
     }
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+}
 
-    // corner / blocked-by-crosses checks (conservative)
-    for &(r, c) in set.iter() {
-        let up_missing = r == 0 || c >= board.row_widths[r - 1] || !board.is_cell_present(r - 1, c);
-        let down_missing = r + 1 >= board.rows || c >= board.row_widths[r + 1] || !board.is_cell_present(r + 1, c);
-        let left_missing = c == 0 || !board.is_cell_present(r, c - 1);
-        let right_missing = c + 1 >= board.row_widths[r] || !board.is_cell_present(r, c + 1);
+pub fn is_win_flat(positions: &[usize], board: &Board) -> bool {
+    board.rules.rule_set().is_win(positions, board)
+}
 
-        if (up_missing && left_missing)
-            || (up_missing && right_missing)
-            || (down_missing && left_missing)
-            || (down_missing && right_missing)
-        {
-            return true;
+pub fn check_lose_flat(crosses: &[usize], board: &Board) -> bool {
+    let crosses = board.line_crosses(crosses);
+    board.rules.rule_set().is_loss(crosses, board)
+}
+
+// Deadlock detection for crosses, in the spirit of Sokoban freeze deadlocks.
+// Only the player pushes, one piece at a time, so a cross can move along an
+// axis only when both neighbouring cells on that axis exist and neither holds
+// a cross that is stuck for good. With chain push a row of pieces moves
+// together, and with pulls a cross can be dragged away from its neighbours, so
+// under those variants only holes and edges can hold a cross. Under the
+// sinkhole rule a cross next to a hole can still be dropped into it, so only
+// edges and walls hold it, and a cross is never trapped in a short run.
+
+const AXES: [(isize, isize); 2] = [(0, 1), (1, 0)];
+
+/// Whether the board's shape lets a piece at `pos` ever move along the axis
+/// (dr, dc): pushing needs a cell on both sides, pulling two cells on one side.
+fn axis_open(pos: (isize, isize), dr: isize, dc: isize, board: &Board) -> bool {
+    let has = |k: isize| {
+        let (r, c) = board.wrap(pos.0 + k * dr, pos.1 + k * dc);
+        board.has_cell(r, c)
+    };
+    (has(-1) && has(1)) || (board.rules.pull && ((has(1) && has(2)) || (has(-1) && has(-2))))
+}
+
+/// `axis_open` for a cross, which under the sinkhole rule can also be pushed
+/// from a cell on one side into a hole on the other.
+fn cross_axis_open(pos: (isize, isize), dr: isize, dc: isize, board: &Board) -> bool {
+    let at = |k: isize| board.wrap(pos.0 + k * dr, pos.1 + k * dc);
+    let has = |k: isize| {
+        let (r, c) = at(k);
+        board.has_cell(r, c)
+    };
+    let hole = |k: isize| {
+        let (r, c) = at(k);
+        board.is_hole(r, c)
+    };
+    axis_open(pos, dr, dc, board)
+        || (board.rules.sinkholes && ((has(-1) && hole(1)) || (has(1) && hole(-1))))
+}
+
+/// Whether the board's shape alone keeps the piece at `pos` from ever moving.
+pub fn is_immovable(pos: (usize, usize), board: &Board) -> bool {
+    let pos = (pos.0 as isize, pos.1 as isize);
+    !AXES.iter().any(|&(dr, dc)| axis_open(pos, dr, dc, board))
+}
+
+/// Whether the cross at `pos` can never be pushed again. `stuck` holds the
+/// crosses already assumed frozen further up the recursion; treating them as
+/// walls is what lets pairs and blocks of crosses lock each other.
+fn cross_frozen(
+    pos: (isize, isize),
+    set: &HashSet<(isize, isize)>,
+    board: &Board,
+    stuck: &mut Vec<(isize, isize)>,
+) -> bool {
+    if board.is_heavy(pos.0 as usize, pos.1 as usize) {
+        return true;
+    }
+    if board.beside_bomb(pos.0 as usize, pos.1 as usize) {
+        return false;
+    }
+    stuck.push(pos);
+    let mut frozen = true;
+    for &(dr, dc) in &AXES {
+        let sides = [
+            board.wrap(pos.0 - dr, pos.1 - dc),
+            board.wrap(pos.0 + dr, pos.1 + dc),
+        ];
+        let blocked = if board.rules.chain_push || board.rules.pull || board.rules.sinkholes {
+            !cross_axis_open(pos, dr, dc, board)
+        } else {
+            sides.iter().any(|&(r, c)| {
+                !board.has_cell(r, c)
+                    || stuck.contains(&(r, c))
+                    || (set.contains(&(r, c)) && cross_frozen((r, c), set, board, stuck))
+            })
+        };
+        if !blocked {
+            frozen = false;
+            break;
         }
+    }
+    stuck.pop();
+    frozen
+}
 
-        // consider neighboring crosses as blockers (only crosses count as blockers here)
-        let up_blocked = up_missing || set.contains(&(r.saturating_sub(1), c));
-        let down_blocked = down_missing || set.contains(&(r + 1, c));
-        let left_blocked = left_missing || set.contains(&(r, c.saturating_sub(1)));
-        let right_blocked = right_missing || set.contains(&(r, c + 1));
-
-        if (up_blocked && left_blocked)
-            || (up_blocked && right_blocked)
-            || (down_blocked && left_blocked)
-            || (down_blocked && right_blocked)
-        {
-            return true;
+/// Number of present cells in the unbroken line through `pos` along (dr, dc).
+/// On a toroidal board that line may be a whole ring of cells.
+fn run_length(pos: (isize, isize), dr: isize, dc: isize, board: &Board) -> usize {
+    let mut len = 1;
+    for sign in [-1, 1] {
+        let (mut r, mut c) = board.wrap(pos.0 + sign * dr, pos.1 + sign * dc);
+        while board.has_cell(r, c) {
+            if (r, c) == pos {
+                return len;
+            }
+            len += 1;
+            (r, c) = board.wrap(r + sign * dr, c + sign * dc);
         }
     }
+    len
+}
+
+/// Flags, parallel to `crosses`, for crosses that can never be moved again:
+/// heavy, stuck in a corner, against holes or edges on both axes, or locked
+/// against other frozen crosses (pairs along an edge, 2x2 blocks, longer
+/// chains). Crosses that fell into a hole are not frozen, just gone, and
+/// crosses beside a bomb are not frozen while it can still clear them.
+pub fn frozen_crosses(crosses: &[usize], board: &Board) -> Vec<bool> {
+    let position = |p: usize| {
+        let (r, c) = board.from_flat(p);
+        (r as isize, c as isize)
+    };
+    let set: HashSet<(isize, isize)> = crosses
+        .iter()
+        .filter(|&&p| in_play(p, board))
+        .map(|&p| position(p))
+        .collect();
+    let mut stuck = Vec::new();
+    crosses
+        .iter()
+        .map(|&p| in_play(p, board) && cross_frozen(position(p), &set, board, &mut stuck))
+        .collect()
+}
+
+/// Sokoban-style deadlock check for crosses under the board's rule set. A
+/// neutral block stuck for good costs nothing, so blocks are left out, and
+/// on a board with power-ups any cross may still be removed.
+pub fn check_cross_deadlock(crosses: &[usize], board: &Board) -> bool {
+    if board.powerups.contains(&true) {
+        return false;
+    }
+    let crosses = board.line_crosses(crosses);
+    board.rules.rule_set().is_deadlocked(crosses, board)
+}
 
-    false
+/// Whether some cross other than a heavy one, which is meant to stay put, is
+/// stuck for good:
+/// - it is frozen (see `frozen_crosses`)
+/// - it sits against holes or edges on one axis and the other axis is a run
+///   of at most `length` cells, so it can never leave that potential lose line
+fn cross_trapped(crosses: &[usize], board: &Board, length: usize) -> bool {
+    // a cross beside a bomb may still be blown away
+    let light = |p: usize| {
+        let (r, c) = board.from_flat(p);
+        !board.heavy[p] && !board.beside_bomb(r, c)
+    };
+    let frozen = frozen_crosses(crosses, board);
+    if crosses.iter().zip(frozen).any(|(&p, f)| f && light(p)) {
+        return true;
+    }
+    if board.rules.sinkholes {
+        return false;
+    }
+    crosses
+        .iter()
+        .filter(|&&p| in_play(p, board) && light(p))
+        .any(|&p| {
+            let (r, c) = board.from_flat(p);
+            let pos = (r as isize, c as isize);
+            let movable: Vec<(isize, isize)> = AXES
+                .iter()
+                .copied()
+                .filter(|&(dr, dc)| axis_open(pos, dr, dc, board))
+                .collect();
+            matches!(movable[..], [(dr, dc)] if run_length(pos, dr, dc, board) <= length)
+        })
 }
@@ -0,0 +1,127 @@
+// Scores for solved puzzles, and the running totals kept across sessions.
+//
+// A solved puzzle is worth a base amount for its difficulty, scaled down by
+// moves spent beyond par, by time beyond a par-based target and by every move
+// taken back. Lost puzzles score nothing. The totals live in
+// `tic-tac-go-stats.json` in the working directory, next to the log;
+// `tic-tac-go stats` prints them. The file also remembers the last day whose
+// daily result was sent, so it is sent only once.
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::generator::{Difficulty, Setup};
+
+pub const STATS_FILE: &str = "tic-tac-go-stats.json";
+
+// Seconds per par move a solve may take before the time factor drops
+const SECONDS_PER_MOVE: f64 = 8.0;
+// Target time for puzzles without a known par
+const UNKNOWN_PAR_SECONDS: f64 = 120.0;
+// Floor of the time factor, so a slow solve still counts for something
+const MIN_TIME_FACTOR: f64 = 0.5;
+// Each undo keeps this share of the score
+const UNDO_FACTOR: f64 = 0.9;
+
+/// Points for solving a puzzle of `setup` at par, in time and without undos.
+pub fn base(setup: Setup) -> f64 {
+    match setup {
+        Setup::Tier(Difficulty::Easy) | Setup::Preset(_) => 100.0,
+        Setup::Tier(Difficulty::Medium) | Setup::Custom(_) => 250.0,
+        Setup::Tier(Difficulty::Hard) => 500.0,
+        Setup::Tier(Difficulty::Expert) => 750.0,
+        Setup::Tier(Difficulty::Insane) => 1000.0,
+    }
+}
+
+/// Score of a solve in `moves` moves and `elapsed` time with `undos` moves
+/// taken back.
+pub fn score(
+    setup: Setup,
+    par: Option<usize>,
+    moves: usize,
+    elapsed: Duration,
+    undos: usize,
+) -> u32 {
+    let efficiency = par.map_or(1.0, |p| (p as f64 / moves.max(1) as f64).min(1.0));
+    let target = par.map_or(UNKNOWN_PAR_SECONDS, |p| p as f64 * SECONDS_PER_MOVE);
+    let time = (target / elapsed.as_secs_f64().max(1.0)).clamp(MIN_TIME_FACTOR, 1.0);
+    let assists = UNDO_FACTOR.powi(undos as i32);
+    (base(setup) * efficiency * time * assists).round() as u32
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub played: u32,
+    pub won: u32,
+    pub total_score: u64,
+    pub best_score: u32,
+    /// The last day whose daily puzzle result was sent
+    pub daily_sent: Option<u64>,
+}
+
+impl Stats {
+    /// The saved totals; a missing or unreadable file starts from zero.
+    pub fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(STATS_FILE) else {
+            return Stats::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("{}: {}; starting new stats", STATS_FILE, e);
+            Stats::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(Path::new(STATS_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a finished puzzle, solved with `score` or lost (`None`), to the
+    /// saved totals and return them.
+    pub fn record(score: Option<u32>) -> Self {
+        let mut stats = Stats::load();
+        stats.played += 1;
+        if let Some(s) = score {
+            stats.won += 1;
+            stats.total_score += u64::from(s);
+            stats.best_score = stats.best_score.max(s);
+        }
+        if let Err(e) = stats.save() {
+            log::warn!("could not save {}: {}", STATS_FILE, e);
+        }
+        stats
+    }
+}
+
+/// Whether the result for the daily puzzle of `day` is the first one to
+/// send; if so, it is noted as sent.
+pub fn first_daily_result(day: u64) -> bool {
+    let mut stats = Stats::load();
+    if stats.daily_sent == Some(day) {
+        return false;
+    }
+    stats.daily_sent = Some(day);
+    if let Err(e) = stats.save() {
+        log::warn!("could not save {}: {}", STATS_FILE, e);
+    }
+    true
+}
+
+/// `tic-tac-go stats`: print the saved totals.
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if let Some(arg) = args.first() {
+        eprintln!("usage: tic-tac-go stats");
+        return Err(format!("unexpected argument {}", arg).into());
+    }
+    let stats = Stats::load();
+    println!("puzzles played: {}", stats.played);
+    println!("puzzles solved: {}", stats.won);
+    println!("total score:    {}", stats.total_score);
+    println!("best score:     {}", stats.best_score);
+    Ok(())
+}
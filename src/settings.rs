@@ -0,0 +1,284 @@
+// The Settings screen: the config options that make sense to change while
+// playing, each changed in place with ←/→ and written to the config file
+// straight away (see `config::set`).
+use std::error::Error;
+
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::app::{self, AppEvent, Screen, Step};
+use crate::config::{self, Config};
+use crate::game::{GameExit, GridStyle, PlayerStyle};
+use crate::generator::{Difficulty, Undos};
+use crate::layout;
+use crate::sound;
+use crate::term::Tui;
+
+// Rows of the box besides the settings: borders, title, blank lines, message
+// and keys
+const CHROME_ROWS: u16 = 8;
+
+// Choices the held-key settings cycle through
+const REPEAT_DELAYS: [u64; 5] = [100, 150, 250, 400, 600];
+const REPEAT_RATES: [u32; 6] = [6, 8, 12, 16, 20, 30];
+// `None` is the difficulty's built-in allowance
+const UNDO_CHOICES: [Option<Undos>; 6] = [
+    None,
+    Some(Undos::Unlimited),
+    Some(Undos::Limit(0)),
+    Some(Undos::Limit(1)),
+    Some(Undos::Limit(3)),
+    Some(Undos::Limit(5)),
+];
+
+#[derive(Clone, Copy)]
+enum Setting {
+    Sound,
+    Animations,
+    ScreenReader,
+    PlayerStyle,
+    GridStyle,
+    RepeatDelay,
+    RepeatRate,
+    WarnLosingMoves,
+    Assist,
+    OptimalDistance,
+    SoftLockWarning,
+    LargeBoards,
+    LiveGeneration,
+    Undos(Difficulty),
+}
+
+const SETTINGS: [Setting; 18] = [
+    Setting::Sound,
+    Setting::Animations,
+    Setting::ScreenReader,
+    Setting::PlayerStyle,
+    Setting::GridStyle,
+    Setting::RepeatDelay,
+    Setting::RepeatRate,
+    Setting::WarnLosingMoves,
+    Setting::Assist,
+    Setting::OptimalDistance,
+    Setting::SoftLockWarning,
+    Setting::LargeBoards,
+    Setting::LiveGeneration,
+    Setting::Undos(Difficulty::Easy),
+    Setting::Undos(Difficulty::Medium),
+    Setting::Undos(Difficulty::Hard),
+    Setting::Undos(Difficulty::Expert),
+    Setting::Undos(Difficulty::Insane),
+];
+
+/// The choice `step` places after `current` in `choices`, wrapping around.
+/// A value that is not among them (set in the file by hand) goes to the first.
+fn cycle<T: Copy + PartialEq>(choices: &[T], current: T, step: isize) -> T {
+    match choices.iter().position(|&c| c == current) {
+        Some(i) => choices[(i as isize + step).rem_euclid(choices.len() as isize) as usize],
+        None => choices[0],
+    }
+}
+
+fn on_off(on: bool) -> String {
+    if on { "on" } else { "off" }.to_string()
+}
+
+fn undos_text(undos: Undos) -> String {
+    match undos {
+        Undos::Unlimited => "unlimited".to_string(),
+        Undos::Limit(n) => n.to_string(),
+    }
+}
+
+impl Setting {
+    fn label(self) -> String {
+        match self {
+            Setting::Sound => "Sound".to_string(),
+            Setting::Animations => "Animations".to_string(),
+            Setting::ScreenReader => "Screen reader line".to_string(),
+            Setting::PlayerStyle => "Player".to_string(),
+            Setting::GridStyle => "Grid".to_string(),
+            Setting::RepeatDelay => "Held key: first repeat".to_string(),
+            Setting::RepeatRate => "Held key: repeat rate".to_string(),
+            Setting::WarnLosingMoves => "Warn before losing moves".to_string(),
+            Setting::Assist => "Refuse losing moves on Easy".to_string(),
+            Setting::OptimalDistance => "Optimal moves left".to_string(),
+            Setting::SoftLockWarning => "Warn when unwinnable".to_string(),
+            Setting::LargeBoards => "Large custom boards".to_string(),
+            Setting::LiveGeneration => "Live generation".to_string(),
+            Setting::Undos(d) => format!("Undos on {:?}", d),
+        }
+    }
+
+    fn value(self, config: &Config) -> String {
+        match self {
+            Setting::Sound => on_off(config.sound),
+            Setting::Animations => on_off(!config.reduce_motion),
+            Setting::ScreenReader => on_off(config.screen_reader),
+            Setting::PlayerStyle => match config.player_style {
+                PlayerStyle::Bold => "bold o",
+                PlayerStyle::At => "@",
+                PlayerStyle::Blink => "blinking o",
+                PlayerStyle::Inverse => "inverse o",
+            }
+            .to_string(),
+            Setting::GridStyle => match config.grid_style {
+                GridStyle::Minimal => "minimal",
+                GridStyle::Classic => "classic boxes",
+            }
+            .to_string(),
+            Setting::RepeatDelay => format!("{} ms", config.key_repeat.delay_ms),
+            Setting::RepeatRate => format!("{} per second", config.key_repeat.per_second),
+            Setting::WarnLosingMoves => on_off(config.warn_losing_moves),
+            Setting::Assist => on_off(config.assist),
+            Setting::OptimalDistance => on_off(config.optimal_distance),
+            Setting::SoftLockWarning => on_off(config.soft_lock_warning),
+            Setting::LargeBoards => on_off(config.large_boards),
+            Setting::LiveGeneration => on_off(config.live_generation),
+            Setting::Undos(d) => match config.overrides(d).undos {
+                Some(undos) => undos_text(undos),
+                None => format!("default ({})", undos_text(d.default_params().undos)),
+            },
+        }
+    }
+
+    /// Move the setting `step` choices on; on/off settings just flip.
+    fn change(self, config: &mut Config, step: isize) {
+        match self {
+            Setting::Sound => config.sound = !config.sound,
+            Setting::Animations => config.reduce_motion = !config.reduce_motion,
+            Setting::ScreenReader => config.screen_reader = !config.screen_reader,
+            Setting::PlayerStyle => {
+                config.player_style = cycle(&PlayerStyle::ALL, config.player_style, step)
+            }
+            Setting::GridStyle => {
+                config.grid_style = cycle(&GridStyle::ALL, config.grid_style, step)
+            }
+            Setting::RepeatDelay => {
+                let repeat = &mut config.key_repeat;
+                repeat.delay_ms = cycle(&REPEAT_DELAYS, repeat.delay_ms, step);
+            }
+            Setting::RepeatRate => {
+                let repeat = &mut config.key_repeat;
+                repeat.per_second = cycle(&REPEAT_RATES, repeat.per_second, step);
+            }
+            Setting::WarnLosingMoves => config.warn_losing_moves = !config.warn_losing_moves,
+            Setting::Assist => config.assist = !config.assist,
+            Setting::OptimalDistance => config.optimal_distance = !config.optimal_distance,
+            Setting::SoftLockWarning => config.soft_lock_warning = !config.soft_lock_warning,
+            Setting::LargeBoards => config.large_boards = !config.large_boards,
+            Setting::LiveGeneration => config.live_generation = !config.live_generation,
+            Setting::Undos(d) => {
+                let overrides = config.overrides_mut(d);
+                overrides.undos = cycle(&UNDO_CHOICES, overrides.undos, step);
+            }
+        }
+    }
+}
+
+struct SettingsScreen {
+    selection: usize,
+    // where the last change went, or why it could not be saved
+    message: Option<String>,
+}
+
+impl SettingsScreen {
+    fn change(&mut self, step: isize) {
+        let setting = SETTINGS[self.selection];
+        let result = config::set(|config| setting.change(config, step));
+        let config = config::get();
+        self.message = Some(match result {
+            Ok(()) => format!("{}: {}", setting.label(), setting.value(config)),
+            Err(e) => format!("could not save the config: {}", e),
+        });
+        // sound is switched on and off for the session in `sound`
+        sound::set_enabled(config.sound);
+    }
+}
+
+impl Screen for SettingsScreen {
+    type Output = ();
+
+    fn update(&mut self, event: AppEvent) -> Step<()> {
+        let AppEvent::KeyPressed(key) = event else {
+            return Step::Idle;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Step::Done(()),
+            KeyCode::Up | KeyCode::Char('w') => self.selection = self.selection.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('s') => {
+                self.selection = (self.selection + 1).min(SETTINGS.len() - 1)
+            }
+            KeyCode::Left | KeyCode::Char('a') => self.change(-1),
+            KeyCode::Right | KeyCode::Char('d') | KeyCode::Enter | KeyCode::Char(' ') => {
+                self.change(1)
+            }
+            _ => return Step::Idle,
+        }
+        Step::Redraw
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let overlay_w = std::cmp::min(60, size.width.saturating_sub(4));
+        let overlay_h = SETTINGS.len() as u16 + CHROME_ROWS;
+        let area = layout::centered(size, overlay_w, overlay_h);
+        let config = config::get();
+
+        let mut lines: Vec<Spans> = vec![
+            Spans::from(Span::styled(
+                " Settings ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::raw("")),
+        ];
+        for (i, setting) in SETTINGS.iter().enumerate() {
+            let text = format!("{:<26}{:>20}", setting.label(), setting.value(config));
+            if i == self.selection {
+                lines.push(Spans::from(Span::styled(
+                    format!("> {} <", text),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+            } else {
+                lines.push(Spans::from(Span::raw(format!("  {}  ", text))));
+            }
+        }
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::styled(
+            self.message.clone().unwrap_or_default(),
+            Style::default().fg(Color::DarkGray),
+        )));
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw(
+            "↑/↓ select   ←/→ change   Esc: back",
+        )));
+
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// Show the Settings screen until the player goes back to the menu.
+pub fn show_settings(terminal: &mut Tui) -> Result<GameExit, Box<dyn Error>> {
+    let mut screen = SettingsScreen {
+        selection: 0,
+        message: None,
+    };
+    app::run(terminal, &mut screen)?;
+    Ok(GameExit::Menu)
+}
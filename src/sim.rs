@@ -0,0 +1,504 @@
+// Headless play: boards drawn in ASCII, moves as text and the resulting
+// status, so the engine can be driven and checked without a terminal.
+//
+// Boards have one line per row:
+//   .    empty cell             @    the player (a circle)
+//   o    circle                 x    cross
+//   #    wall                   ~    ice
+//   X    heavy cross, which can't be pushed or pulled
+//   b    neutral block, pushed like a cross but never part of a line
+//   +    power-up tile
+//   *    bomb, which clears the crosses next to it when a piece is pushed on
+//   _    goal cell (goal-cell rules; a piece on a goal hides it)
+//   A-H  portal; the two cells with the same letter are a pair
+// Anything else (e.g. a space) is a hole, and short rows end in holes.
+//
+// Moves use the solver's notation (see `Step::notation`): u/d/l/r, upper case
+// for a push, a `p` prefix for a pull and an `x` prefix to spend a power-up on
+// the cross that way. Case only documents the intent and is not checked.
+//
+// `tic-tac-go sim FILE...` runs case files for regression checks. A case file
+// starts with optional `rules` (e.g. `rules pull wrap`), `moves` and `expect`
+// (playing, won, lost or stalemate) lines and `//` comments; the board
+// follows.
+//
+// `tic-tac-go --script` plays a move string from a file or stdin on a puzzle
+// file, an ASCII board or a generated puzzle, and prints where it ended.
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+
+use crate::ai;
+use crate::board::{Board, PortalPair};
+use crate::generate::parse_difficulty;
+use crate::generator::{self, Difficulty};
+use crate::movement::{PowerUps, legal_moves, remove_cross};
+use crate::puzzle::PuzzleFile;
+use crate::rules::{Rules, Variant, check_lose_flat, is_win_flat};
+use crate::state::GameState;
+
+const PORTAL_LETTERS: &str = "ABCDEFGH";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+    /// No move changes the board, a draw the game counts as unsolved
+    Stalemate,
+}
+
+impl Status {
+    pub fn label(self) -> &'static str {
+        match self {
+            Status::Playing => "playing",
+            Status::Won => "won",
+            Status::Lost => "lost",
+            Status::Stalemate => "stalemate",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        [
+            Status::Playing,
+            Status::Won,
+            Status::Lost,
+            Status::Stalemate,
+        ]
+        .into_iter()
+        .find(|st| st.label() == s)
+    }
+}
+
+/// What a move does in its direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Walk, pushing what is in the way
+    Step,
+    Pull,
+    /// Spend a power-up on the cross next to the player
+    Remove,
+}
+
+/// One move: direction and action.
+pub type Move = (isize, isize, Action);
+
+/// Build a game from an ASCII board (see the format above), checked like a
+/// puzzle file.
+pub fn parse_board(text: &str, rules: Rules) -> Result<GameState, Box<dyn Error>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let rows = lines.len();
+    let cols = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut removed = Vec::new();
+    let mut walls = Vec::new();
+    let mut ice = Vec::new();
+    let mut goals = Vec::new();
+    let mut heavy = Vec::new();
+    let mut blocks = Vec::new();
+    let mut powerups = Vec::new();
+    let mut bombs = Vec::new();
+    let mut portal_ends: Vec<Vec<(usize, usize)>> = vec![Vec::new(); PORTAL_LETTERS.len()];
+    let mut circles = Vec::new();
+    let mut crosses = Vec::new();
+    let mut player = None;
+    for (r, line) in lines.iter().enumerate() {
+        let mut chars = line.chars();
+        for c in 0..cols {
+            match chars.next().unwrap_or(' ') {
+                '.' => {}
+                '@' => {
+                    if player.is_some() {
+                        return Err("more than one player (@) on the board".into());
+                    }
+                    player = Some(circles.len());
+                    circles.push((r, c));
+                }
+                'o' => circles.push((r, c)),
+                'x' => crosses.push((r, c)),
+                'X' => {
+                    crosses.push((r, c));
+                    heavy.push((r, c));
+                }
+                'b' => blocks.push((r, c)),
+                '+' => powerups.push((r, c)),
+                '*' => bombs.push((r, c)),
+                '#' => walls.push((r, c)),
+                '~' => ice.push((r, c)),
+                '_' => goals.push((r, c)),
+                ch => match PORTAL_LETTERS.find(ch) {
+                    Some(i) => portal_ends[i].push((r, c)),
+                    None => removed.push((r, c)),
+                },
+            }
+        }
+    }
+    let mut portals: Vec<PortalPair> = Vec::new();
+    for (ends, letter) in portal_ends.iter().zip(PORTAL_LETTERS.chars()) {
+        match ends[..] {
+            [] => {}
+            [a, b] => portals.push((a, b)),
+            _ => return Err(format!("portal {} needs exactly two cells", letter).into()),
+        }
+    }
+    let mut board = Board::from_dims(rows, cols, &removed)?;
+    board.set_walls(&walls);
+    board.set_ice(&ice);
+    board.set_portals(&portals);
+    board.set_goals(&goals);
+    board.set_heavy(&heavy);
+    board.set_powerups(&powerups);
+    board.set_bombs(&bombs);
+    // blocks go after the crosses (see `Board::blocks`)
+    board.blocks = blocks.len();
+    crosses.extend(blocks);
+    board.rules = rules;
+    let player_idx = player.ok_or("no player (@) on the board")?;
+    let state = GameState {
+        powerups: PowerUps::start(&board, circles[player_idx]),
+        board,
+        circles,
+        crosses,
+        player_idx,
+        moves: 0,
+    };
+    state.check()?;
+    Ok(state)
+}
+
+/// The board of `state` in the same ASCII format.
+pub fn to_ascii(state: &GameState) -> String {
+    let board = &state.board;
+    let mut letters = vec![None; board.total_cells];
+    for ((a, b), letter) in board.portal_pairs().into_iter().zip(PORTAL_LETTERS.chars()) {
+        letters[board.to_flat(a.0, a.1)] = Some(letter);
+        letters[board.to_flat(b.0, b.1)] = Some(letter);
+    }
+    let mut out = String::new();
+    for r in 0..board.rows {
+        let mut line = String::new();
+        for c in 0..board.row_widths[r] {
+            let ch = if let Some(i) = state.circles.iter().position(|&p| p == (r, c)) {
+                if i == state.player_idx { '@' } else { 'o' }
+            } else if let Some(i) = state.crosses.iter().position(|&p| p == (r, c))
+                && board.is_cell_present(r, c)
+            {
+                if board.is_block(i, state.crosses.len()) {
+                    'b'
+                } else if board.is_heavy(r, c) {
+                    'X'
+                } else {
+                    'x'
+                }
+            } else if board.is_wall(r, c) {
+                '#'
+            } else if !board.is_cell_present(r, c) {
+                ' '
+            } else if let Some(letter) = letters[board.to_flat(r, c)] {
+                letter
+            } else if board.is_ice(r as isize, c as isize) {
+                '~'
+            } else if board.is_goal(r, c) {
+                '_'
+            } else if board.powerups[board.to_flat(r, c)] && !state.powerups.is_taken(board, (r, c))
+            {
+                '+'
+            } else if board.is_bomb(r, c) {
+                '*'
+            } else {
+                '.'
+            };
+            line.push(ch);
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Parse a move string; whitespace is ignored.
+pub fn parse_moves(text: &str) -> Result<Vec<Move>, String> {
+    let mut moves = Vec::new();
+    let mut action = Action::Step;
+    for ch in text.chars().filter(|c| !c.is_whitespace()) {
+        let (dr, dc) = match ch.to_ascii_lowercase() {
+            'p' if action == Action::Step => {
+                action = Action::Pull;
+                continue;
+            }
+            'x' if action == Action::Step => {
+                action = Action::Remove;
+                continue;
+            }
+            'u' => (-1, 0),
+            'd' => (1, 0),
+            'l' => (0, -1),
+            'r' => (0, 1),
+            _ => return Err(format!("unknown move '{}'", ch)),
+        };
+        moves.push((dr, dc, action));
+        action = Action::Step;
+    }
+    if action != Action::Step {
+        return Err("pull or removal without a direction at the end".to_string());
+    }
+    Ok(moves)
+}
+
+/// Whether the position is won, lost or still open. A board where both
+/// circles and crosses are aligned counts as won.
+pub fn status(state: &GameState) -> Status {
+    let board = &state.board;
+    let flat = |pieces: &[(usize, usize)]| -> Vec<usize> {
+        pieces.iter().map(|&(r, c)| board.to_flat(r, c)).collect()
+    };
+    if is_win_flat(&flat(&state.circles), board) {
+        Status::Won
+    } else if check_lose_flat(&flat(&state.crosses), board) {
+        Status::Lost
+    } else if legal_moves(
+        &state.circles,
+        &state.crosses,
+        state.player_idx,
+        state.powerups.held(),
+        board,
+    )
+    .is_empty()
+    {
+        Status::Stalemate
+    } else {
+        Status::Playing
+    }
+}
+
+/// Play `moves` the way the game does: a move that changes nothing is not
+/// counted, adversarial crosses answer every counted move, and moves after
+/// the game ended are ignored.
+pub fn play(state: &mut GameState, moves: &[Move]) -> Status {
+    for &mv in moves {
+        if status(state) != Status::Playing {
+            break;
+        }
+        step(state, mv);
+    }
+    status(state)
+}
+
+/// Make one move of `play`. If it counted, returns the crosses as the move
+/// left them, before any adversarial reply. A pull without the pull rule and
+/// a removal without a power-up in hand do not count.
+pub fn step(state: &mut GameState, (dr, dc, action): Move) -> Option<Vec<(usize, usize)>> {
+    if action == Action::Pull && !state.board.rules.pull {
+        return None;
+    }
+    let before = (state.circles.clone(), state.crosses.clone());
+    if action == Action::Remove {
+        let held = state.powerups.held() > 0;
+        if !held
+            || !remove_cross(
+                &state.circles,
+                &mut state.crosses,
+                state.player_idx,
+                dr,
+                dc,
+                &state.board,
+            )
+        {
+            return None;
+        }
+        state.powerups.used += 1;
+    } else {
+        crate::movement::apply_move(
+            &mut state.circles,
+            &mut state.crosses,
+            state.player_idx,
+            dr,
+            dc,
+            action == Action::Pull,
+            &state.board,
+        );
+    }
+    if (&state.circles, &state.crosses) == (&before.0, &before.1) {
+        return None;
+    }
+    state.moves += 1;
+    let player = state.circles[state.player_idx];
+    state.powerups.pick_up(&state.board, player);
+    let moved = state.crosses.clone();
+    if state.board.rules.adversarial {
+        ai::respond(
+            &state.circles,
+            &mut state.crosses,
+            state.player_idx,
+            &state.board,
+        );
+    }
+    Some(moved)
+}
+
+fn parse_rules(words: &str) -> Result<Rules, String> {
+    let mut rules = Rules::default();
+    for word in words.split_whitespace() {
+        match word {
+            "diagonals" => rules.diagonals = true,
+            "chain_push" => rules.chain_push = true,
+            "pull" => rules.pull = true,
+            "adversarial" => rules.adversarial = true,
+            "wrap" => rules.wrap = true,
+            "sinkholes" => rules.sinkholes = true,
+            "unison" => rules.unison = true,
+            other => match Variant::parse(other) {
+                Some(variant) => rules.variant = variant,
+                None => return Err(format!("unknown rule '{}'", other)),
+            },
+        }
+    }
+    Ok(rules)
+}
+
+/// Run one case file; `Ok(None)` when it passed, otherwise what went wrong.
+fn run_case(text: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let mut rules = Rules::default();
+    let mut moves = Vec::new();
+    let mut expect = None;
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.peek() {
+        let (key, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "" if rest.is_empty() => {}
+            k if k.starts_with("//") => {}
+            "rules" => rules = parse_rules(rest)?,
+            "moves" => moves = parse_moves(rest)?,
+            "expect" => {
+                expect = Some(
+                    Status::parse(rest.trim())
+                        .ok_or("expect takes playing, won, lost or stalemate")?,
+                )
+            }
+            _ => break,
+        }
+        lines.next();
+    }
+    let board: Vec<&str> = lines.collect();
+    let mut state = parse_board(&board.join("\n"), rules)?;
+    let got = play(&mut state, &moves);
+    Ok(match expect {
+        Some(want) if want != got => Some(format!(
+            "expected {}, got {} after {} moves\n{}",
+            want.label(),
+            got.label(),
+            state.moves,
+            to_ascii(&state)
+        )),
+        _ => None,
+    })
+}
+
+/// Entry point of `tic-tac-go sim`; returns whether every case passed.
+pub fn run(args: &[String]) -> Result<bool, Box<dyn Error>> {
+    let help = args.iter().any(|a| a == "-h" || a == "--help");
+    if args.is_empty() || help {
+        eprintln!("usage: tic-tac-go sim FILE...");
+        eprintln!();
+        eprintln!("Plays each case file headlessly and checks its expected status.");
+        return Ok(help);
+    }
+    let mut failed = 0;
+    for path in args {
+        let result = fs::read_to_string(path)
+            .map_err(|e| e.into())
+            .and_then(|text| run_case(&text));
+        match result {
+            Ok(None) => println!("{}  ok", path),
+            Ok(Some(why)) => {
+                failed += 1;
+                println!("{}  FAILED  {}", path, why);
+            }
+            Err(e) => {
+                failed += 1;
+                println!("{}  error  {}", path, e);
+            }
+        }
+    }
+    println!("{} cases, {} failed", args.len(), failed);
+    Ok(failed == 0)
+}
+
+fn print_script_usage() {
+    eprintln!(
+        "usage: tic-tac-go --script [MOVES] (--puzzle FILE | --board FILE | --seed S [--difficulty LEVEL])"
+    );
+    eprintln!();
+    eprintln!("Reads moves from MOVES, or stdin when it is missing or -, plays them on");
+    eprintln!("the puzzle and prints the final board and status. --board takes an");
+    eprintln!("ASCII board as used by `sim`; --seed generates the puzzle the game shows");
+    eprintln!("for that seed and difficulty (default medium) under the configured rules.");
+}
+
+/// Entry point of `tic-tac-go --script`.
+pub fn script(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut moves_path: Option<&str> = None;
+    let mut puzzle: Option<&str> = None;
+    let mut board: Option<&str> = None;
+    let mut seed: Option<u64> = None;
+    let mut difficulty = Difficulty::Medium;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--puzzle" => puzzle = Some(it.next().ok_or("--puzzle expects a file")?),
+            "--board" => board = Some(it.next().ok_or("--board expects a file")?),
+            "--seed" => {
+                seed = Some(
+                    it.next()
+                        .and_then(|v| v.parse().ok())
+                        .ok_or("--seed expects a number")?,
+                );
+            }
+            "--difficulty" | "-d" => {
+                difficulty = it
+                    .next()
+                    .and_then(|v| parse_difficulty(v))
+                    .ok_or("--difficulty expects easy, medium, hard, expert or insane")?;
+            }
+            "-h" | "--help" => {
+                print_script_usage();
+                return Ok(());
+            }
+            other if other.starts_with('-') && other != "-" => {
+                print_script_usage();
+                return Err(format!("unknown option: {}", other).into());
+            }
+            other => moves_path = Some(other),
+        }
+    }
+
+    let mut state = match (puzzle, board, seed) {
+        (Some(path), None, None) => GameState::from_puzzle(&PuzzleFile::load(path.as_ref())?)?,
+        (None, Some(path), None) => parse_board(&fs::read_to_string(path)?, Rules::default())?,
+        (None, None, Some(seed)) => {
+            let rules = crate::config::get().rules;
+            let p = generator::generate_from_seed(seed, difficulty, rules);
+            if p.circles.is_empty() {
+                return Err(format!("seed {} gives no puzzle", seed).into());
+            }
+            GameState::from_generated(p)
+        }
+        _ => {
+            print_script_usage();
+            return Err("pick exactly one of --puzzle, --board and --seed".into());
+        }
+    };
+
+    let text = match moves_path {
+        Some(path) if path != "-" => fs::read_to_string(path)?,
+        _ => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        }
+    };
+    let status = play(&mut state, &parse_moves(&text)?);
+    print!("{}", to_ascii(&state));
+    println!("{} after {} moves", status.label(), state.moves);
+    Ok(())
+}
@@ -1,16 +1,374 @@
-use crate::{board::Board, rules::is_win_flat};
+// Optimal solver for tic-tac-go positions.
+//
+// Uses IDA* (iterative deepening A*): repeated depth-first searches bounded by
+// f = g + h, where h is the admissible lower bound from `heuristics`. Memory
+// stays proportional to the solution depth plus a bounded transposition table,
+// so optimal lengths can be found for Hard puzzles where a breadth-first search
+// would have to keep every visited state around.
+//
+// The search records the moves it is currently exploring, so the result is the
+// full move sequence; callers that only need the optimal length take `len()`.
+use std::collections::HashMap;
 
-enum Step {
-    Left,
-    Right,
-    Top,
-    Bottom,
+use crate::board::Board;
+use crate::heuristics::LowerBound;
+use crate::movement::{PowerUps, Push, apply_move, legal_moves, remove_cross};
+use crate::rules::{check_lose_flat, is_win_flat};
+
+// Cap on remembered states per iteration; the table is simply cleared when it
+// fills up, which only costs some re-expansion.
+const MAX_TABLE_ENTRIES: usize = 1 << 20;
+
+/// One player move: the direction taken, whether it was a pull or spent a
+/// power-up on the cross that way, and what, if anything, it moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub dr: isize,
+    pub dc: isize,
+    pub pull: bool,
+    pub remove: bool,
+    pub push: Option<Push>,
+}
+
+impl Step {
+    /// Sokoban-style notation: u/d/l/r for a plain move, upper case for a push,
+    /// a `p` prefix for a pull (`pU` steps up dragging the piece below) and an
+    /// `x` prefix for a power-up's removal (`xR` removes the cross to the
+    /// right).
+    pub fn notation(&self) -> String {
+        let c = match (self.dr, self.dc) {
+            (-1, 0) => 'u',
+            (1, 0) => 'd',
+            (0, -1) => 'l',
+            _ => 'r',
+        };
+        if self.remove {
+            return format!("x{}", c.to_ascii_uppercase());
+        }
+        match (self.pull, self.push.is_some()) {
+            (true, _) => format!("p{}", c.to_ascii_uppercase()),
+            (false, true) => c.to_ascii_uppercase().to_string(),
+            (false, false) => c.to_string(),
+        }
+    }
+
+    /// Play this move on a position.
+    pub fn apply(
+        &self,
+        circles: &mut [(usize, usize)],
+        crosses: &mut Vec<(usize, usize)>,
+        player_idx: usize,
+        board: &Board,
+    ) {
+        if self.remove {
+            remove_cross(circles, crosses, player_idx, self.dr, self.dc, board);
+        } else {
+            apply_move(
+                circles, crosses, player_idx, self.dr, self.dc, self.pull, board,
+            );
+        }
+    }
+}
+
+enum Search {
+    Found,
+    // smallest f value that exceeded the bound (usize::MAX if none did)
+    NotFound(usize),
+    Aborted,
+}
+
+struct Ida<'a> {
+    board: &'a Board,
+    bound: LowerBound<'a>,
+    player_idx: usize,
+    max_nodes: usize,
+    nodes: usize,
+    // moves from the root to the node being expanded
+    path: Vec<Step>,
+    // state key -> smallest depth it was reached at during this iteration
+    table: HashMap<Vec<usize>, usize>,
+    // whether the board has power-up tiles, which the key then covers
+    powerups: bool,
+}
+
+impl Ida<'_> {
+    /// Player first, then the remaining circles, the crosses and the neutral
+    /// blocks, each sorted, and the power-ups on boards that have any.
+    fn key(
+        &self,
+        circles: &[(usize, usize)],
+        crosses: &[(usize, usize)],
+        powerups: PowerUps,
+    ) -> Vec<usize> {
+        let (pr, pc) = circles[self.player_idx];
+        let mut others: Vec<usize> = circles
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != self.player_idx)
+            .map(|(_, &(r, c))| self.board.to_flat(r, c))
+            .collect();
+        others.sort_unstable();
+        let sorted = |v: &[(usize, usize)]| {
+            let mut flat: Vec<usize> = v.iter().map(|&(r, c)| self.board.to_flat(r, c)).collect();
+            flat.sort_unstable();
+            flat
+        };
+        let scoring = self.board.line_crosses(crosses);
+        let mut key = vec![self.board.to_flat(pr, pc)];
+        key.extend(others);
+        key.push(usize::MAX);
+        key.extend(sorted(scoring));
+        if scoring.len() < crosses.len() {
+            key.push(usize::MAX);
+            key.extend(sorted(&crosses[scoring.len()..]));
+        }
+        if self.powerups {
+            key.extend([usize::MAX, powerups.taken as usize, powerups.used]);
+        }
+        key
+    }
+
+    fn search(
+        &mut self,
+        circles: &[(usize, usize)],
+        crosses: &[(usize, usize)],
+        powerups: PowerUps,
+        g: usize,
+        bound: usize,
+    ) -> Search {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return Search::Aborted;
+        }
+
+        let h = match self.bound.estimate(circles, crosses, self.player_idx) {
+            Some(h) => h,
+            None => return Search::NotFound(usize::MAX),
+        };
+        let f = g + h;
+        if f > bound {
+            return Search::NotFound(f);
+        }
+        let circles_flat: Vec<usize> = circles
+            .iter()
+            .map(|&(r, c)| self.board.to_flat(r, c))
+            .collect();
+        if is_win_flat(&circles_flat, self.board) {
+            return Search::Found;
+        }
+
+        let key = self.key(circles, crosses, powerups);
+        if let Some(&seen) = self.table.get(&key)
+            && seen <= g
+        {
+            return Search::NotFound(usize::MAX);
+        }
+        if self.table.len() >= MAX_TABLE_ENTRIES {
+            self.table.clear();
+        }
+        self.table.insert(key, g);
+
+        let mut next_bound = usize::MAX;
+        let moves = legal_moves(
+            circles,
+            crosses,
+            self.player_idx,
+            powerups.held(),
+            self.board,
+        );
+        for (dir, m) in moves {
+            let (new_circles, new_crosses) = (m.circles, m.crosses);
+            let mut new_powerups = powerups;
+            new_powerups.used += m.remove as usize;
+            new_powerups.pick_up(self.board, new_circles[self.player_idx]);
+            let crosses_flat: Vec<usize> = new_crosses
+                .iter()
+                .map(|&(r, c)| self.board.to_flat(r, c))
+                .collect();
+            if check_lose_flat(&crosses_flat, self.board) {
+                continue;
+            }
+            let (dr, dc) = dir.delta();
+            self.path.push(Step {
+                dr,
+                dc,
+                pull: m.pull,
+                remove: m.remove,
+                push: m.push,
+            });
+            match self.search(&new_circles, &new_crosses, new_powerups, g + 1, bound) {
+                Search::Found => return Search::Found,
+                Search::Aborted => return Search::Aborted,
+                Search::NotFound(t) => next_bound = next_bound.min(t),
+            }
+            self.path.pop();
+        }
+        Search::NotFound(next_bound)
+    }
+}
+
+/// What a search within a node budget found out about a position.
+#[derive(Clone, Debug)]
+pub enum Outcome {
+    /// A shortest solution
+    Solved(Vec<Step>),
+    /// There is none: the circles can't be lined up from here
+    Unsolvable,
+    /// The budget ran out first
+    GaveUp,
 }
 
-pub fn solve_puzzle(puzzle: Board) {
-    let mut steps: Vec<Step> = Vec::new();
-    while !is_win_flat(&[42], &puzzle) {
-        // &[42] is a placeholder
-        steps.push(Step::Left);
+/// Shortest sequence of player moves that aligns any three circles, found with
+/// IDA*. Returns `None` when the position is unsolvable or the search expanded
+/// more than `max_nodes` states (counted across all iterations).
+pub fn solve(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    max_nodes: usize,
+) -> Option<Vec<Step>> {
+    let powerups = PowerUps::start(board, circles[player_idx]);
+    match search(board, circles, crosses, player_idx, powerups, max_nodes) {
+        Outcome::Solved(path) => Some(path),
+        Outcome::Unsolvable | Outcome::GaveUp => None,
+    }
+}
+
+/// `solve`, telling an unsolvable position from a search that gave up, from
+/// a position where the player has `powerups`.
+pub fn search(
+    board: &Board,
+    circles: &[(usize, usize)],
+    crosses: &[(usize, usize)],
+    player_idx: usize,
+    powerups: PowerUps,
+    max_nodes: usize,
+) -> Outcome {
+    if circles.len() < 3 || player_idx >= circles.len() {
+        return Outcome::Unsolvable;
+    }
+    let crosses_flat: Vec<usize> = crosses.iter().map(|&(r, c)| board.to_flat(r, c)).collect();
+    if check_lose_flat(&crosses_flat, board) {
+        return Outcome::Unsolvable;
+    }
+
+    let mut ida = Ida {
+        board,
+        bound: LowerBound::new(board),
+        player_idx,
+        max_nodes,
+        nodes: 0,
+        path: Vec::new(),
+        table: HashMap::new(),
+        powerups: board.powerups.contains(&true),
+    };
+    let mut bound = ida
+        .bound
+        .estimate(circles, crosses, player_idx)
+        .unwrap_or(usize::MAX);
+    while bound != usize::MAX {
+        ida.table.clear();
+        match ida.search(circles, crosses, powerups, 0, bound) {
+            Search::Found => {
+                log::debug!("solved in {} moves, {} nodes", ida.path.len(), ida.nodes);
+                return Outcome::Solved(ida.path);
+            }
+            Search::Aborted => {
+                log::debug!("gave up after {} nodes", ida.nodes);
+                return Outcome::GaveUp;
+            }
+            Search::NotFound(t) => bound = t,
+        }
+    }
+    log::debug!("unsolvable, {} nodes", ida.nodes);
+    Outcome::Unsolvable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+    use crate::sim::parse_board;
+    use crate::state::GameState;
+
+    fn board(text: &str) -> GameState {
+        parse_board(text, Rules::default()).unwrap()
+    }
+
+    fn solve_state(state: &GameState, max_nodes: usize) -> Option<Vec<Step>> {
+        solve(
+            &state.board,
+            &state.circles,
+            &state.crosses,
+            state.player_idx,
+            max_nodes,
+        )
+    }
+
+    #[test]
+    fn finds_the_optimal_length() {
+        let state = board("o..\no..\n.@.");
+        assert_eq!(solve_state(&state, 1000).map(|s| s.len()), Some(1));
+        // the cross is in the way until the power-up removes it
+        let state = board("o...\no...\nx...\n.+@.");
+        let steps = solve_state(&state, 100_000).unwrap();
+        let notation: Vec<String> = steps.iter().map(Step::notation).collect();
+        assert_eq!(notation, ["l", "u", "xL", "l"]);
+    }
+
+    #[test]
+    fn boxed_in_player_is_unsolvable() {
+        let state = board("@X.o\nX...\n...o");
+        let outcome = search(
+            &state.board,
+            &state.circles,
+            &state.crosses,
+            state.player_idx,
+            state.powerups,
+            1000,
+        );
+        assert!(matches!(outcome, Outcome::Unsolvable));
+    }
+
+    #[test]
+    fn gives_up_within_a_tiny_budget() {
+        let state = board("o...\no...\nx...\n.+@.");
+        let outcome = search(
+            &state.board,
+            &state.circles,
+            &state.crosses,
+            state.player_idx,
+            state.powerups,
+            2,
+        );
+        assert!(matches!(outcome, Outcome::GaveUp));
+    }
+
+    #[test]
+    fn lower_bound_never_exceeds_the_moves_left() {
+        for text in [
+            "o..\no..\n.@.",
+            "..o.\n@ox.\n..o.\n....",
+            "o....\n.x...\n..@o.\n...x.\n....o",
+            ".o..\n..x.\n@o.o\n....",
+        ] {
+            let state = board(text);
+            let steps = solve_state(&state, 100_000).unwrap();
+            let bound = LowerBound::new(&state.board);
+            let (mut circles, mut crosses) = (state.circles.clone(), state.crosses.clone());
+            for (i, step) in steps.iter().enumerate() {
+                let left = steps.len() - i;
+                let h = bound.estimate(&circles, &crosses, state.player_idx);
+                assert!(
+                    h.is_some_and(|h| h <= left),
+                    "{}: {:?} with {} left",
+                    text,
+                    h,
+                    left
+                );
+                step.apply(&mut circles, &mut crosses, state.player_idx, &state.board);
+            }
+        }
     }
 }
@@ -0,0 +1,44 @@
+// Optional sound cues, played with the terminal bell.
+//
+// Cues are off unless the config sets `"sound": true`; the difficulty menu
+// can toggle them for the rest of the session. The bell is the only backend,
+// so every cue sounds the same, but callers still say which event happened so
+// that a louder or richer backend only has to change `play`.
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Game events that can make a sound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    Push,
+    Blocked,
+    Win,
+    Loss,
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn set_enabled(on: bool) {
+    ENABLED.store(on, Ordering::Relaxed);
+}
+
+/// Play `cue` if sound is on. Errors writing the bell are ignored; a missing
+/// beep is not worth interrupting the game for.
+pub fn play(cue: Cue) {
+    if !enabled() {
+        return;
+    }
+    let rings = match cue {
+        Cue::Push | Cue::Blocked => 1,
+        Cue::Win | Cue::Loss => 2,
+    };
+    let mut out = io::stdout();
+    for _ in 0..rings {
+        let _ = out.write_all(b"\x07");
+    }
+    let _ = out.flush();
+}
@@ -0,0 +1,88 @@
+// A game in progress, in the same schema as puzzle files
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::generator::GeneratedPuzzle;
+use crate::movement::PowerUps;
+use crate::puzzle::PuzzleFile;
+
+/// Board, pieces, power-ups and move count of a game. The board serializes as a
+/// `BoardShape` and pieces as (row, col) pairs, exactly as in a `PuzzleFile`,
+/// so saves and network messages read like puzzle files with a nested board.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameState {
+    pub board: Board,
+    pub circles: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub crosses: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub player_idx: usize,
+    #[serde(default)]
+    pub moves: usize,
+    #[serde(default)]
+    pub powerups: PowerUps,
+}
+
+impl GameState {
+    /// Starting position of a puzzle file, checked the same way `validate`
+    /// checks it.
+    pub fn from_puzzle(file: &PuzzleFile) -> Result<Self, Box<dyn Error>> {
+        let board = file.board()?;
+        file.check(&board)?;
+        let player_idx = file.player_idx();
+        Ok(GameState {
+            powerups: PowerUps::start(&board, file.circles[player_idx]),
+            board,
+            circles: file.circles.clone(),
+            crosses: file.crosses.clone(),
+            player_idx,
+            moves: 0,
+        })
+    }
+
+    /// Starting position of a generated puzzle.
+    pub fn from_generated(puzzle: GeneratedPuzzle) -> Self {
+        let board = puzzle.board;
+        let to_pos = |v: &[usize]| -> Vec<(usize, usize)> {
+            v.iter().map(|&f| board.from_flat(f)).collect()
+        };
+        let circles = to_pos(&puzzle.circles);
+        GameState {
+            powerups: PowerUps::start(&board, circles[puzzle.player_idx]),
+            circles,
+            crosses: to_pos(&puzzle.crosses),
+            player_idx: puzzle.player_idx,
+            board,
+            moves: 0,
+        }
+    }
+
+    pub fn parse(json: &str) -> Result<Self, Box<dyn Error>> {
+        let state: GameState = serde_json::from_str(json)?;
+        state.check()?;
+        Ok(state)
+    }
+
+    /// Puzzle file for the current position, e.g. to save it from the game.
+    pub fn to_puzzle(&self) -> PuzzleFile {
+        PuzzleFile::from_parts(
+            &self.board,
+            &self.circles,
+            &self.crosses,
+            self.player_idx,
+            None,
+        )
+    }
+
+    /// The structural checks of `PuzzleFile::check`, plus a valid board and
+    /// player.
+    pub fn check(&self) -> Result<(), String> {
+        self.board.validate().map_err(|e| e.to_string())?;
+        if self.player_idx >= self.circles.len() {
+            return Err(format!("player index {} has no circle", self.player_idx));
+        }
+        self.to_puzzle().check(&self.board)
+    }
+}
@@ -0,0 +1,90 @@
+// Terminal setup for the interactive screens.
+//
+// `enter` switches to raw mode and the alternate screen and returns a guard
+// that switches back when dropped, so every way out of a screen restores the
+// terminal: normal returns, `?` errors and panics alike. A panic also restores
+// it from the panic hook, before the message is printed; otherwise the message
+// would land on the alternate screen and vanish with it.
+//
+// Terminals are asked to report focus changes, which pause the game clock,
+// and those with the kitty keyboard protocol to report key releases as well,
+// for held keys (see `input`).
+use std::error::Error;
+use std::io::{self, Stdout};
+use std::panic;
+use std::sync::Once;
+
+use crossterm::cursor::Show;
+use crossterm::event::{
+    DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+    supports_keyboard_enhancement,
+};
+use ratatui::Terminal;
+use ratatui::backend::CrosstermBackend;
+
+use crate::input;
+
+static PANIC_HOOK: Once = Once::new();
+
+/// The terminal every screen draws on.
+pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+
+/// Restores the terminal when dropped.
+pub struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Leave raw mode and the alternate screen and show the cursor again. Errors
+/// are ignored: this runs on the way out and there is nothing left to do.
+fn restore() {
+    if input::releases() {
+        input::set_releases(false);
+        let _ = execute!(io::stdout(), PopKeyboardEnhancementFlags);
+    }
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange,
+        Show
+    );
+}
+
+/// Set the terminal up for a full-screen UI.
+pub fn enter() -> Result<(Tui, Guard), Box<dyn Error>> {
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore();
+            previous(info);
+        }));
+    });
+    enable_raw_mode()?;
+    // from here on the guard undoes whatever part of the setup succeeded
+    let guard = Guard;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
+    if supports_keyboard_enhancement().unwrap_or(false) {
+        execute!(
+            io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+        )?;
+        input::set_releases(true);
+    }
+    let terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    Ok((terminal, guard))
+}
@@ -0,0 +1,74 @@
+// Fixed-rate frame clock for the interactive screens.
+//
+// Each pass of a screen's loop draws and then calls `Ticker::events`, which
+// returns at the next tick with every terminal event that arrived in between
+// (see `input`).
+// Loops therefore keep a steady pace whether or not keys are pressed, so
+// animations, toasts and timers advance on their own, and a burst of key
+// presses is handled in one go instead of one per frame.
+//
+// A frame is only drawn when something on screen may have changed: the loop
+// (see `app::run`) keeps a dirty flag, sets it for every event (key presses
+// and resizes alike) and for anything that changes on its own, and draws
+// through `draw_if_dirty`. Redrawing an unchanged screen makes some terminals
+// flicker.
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::event::Event;
+use ratatui::backend::Backend;
+use ratatui::{Frame, Terminal};
+
+use crate::input;
+
+/// Screens redraw at about 60 frames per second.
+pub const FRAME: Duration = Duration::from_micros(16_667);
+
+pub struct Ticker {
+    period: Duration,
+    next: Instant,
+}
+
+impl Ticker {
+    pub fn new(period: Duration) -> Self {
+        Ticker {
+            period,
+            next: Instant::now() + period,
+        }
+    }
+
+    /// Wait for the next tick and return the events received until then.
+    /// A tick missed because drawing or input handling ran long is skipped
+    /// rather than made up with a burst of redraws.
+    pub fn events(&mut self) -> io::Result<Vec<Event>> {
+        let mut events = Vec::new();
+        loop {
+            let now = Instant::now();
+            if now >= self.next {
+                self.next = (self.next + self.period).max(now);
+                return Ok(events);
+            }
+            if let Some(event) = input::next(self.next - now)? {
+                events.push(event);
+            }
+        }
+    }
+}
+
+impl Default for Ticker {
+    fn default() -> Self {
+        Ticker::new(FRAME)
+    }
+}
+
+/// Draw a frame with `draw` if `dirty` is set, and clear it.
+pub fn draw_if_dirty<B, F>(terminal: &mut Terminal<B>, dirty: &mut bool, draw: F) -> io::Result<()>
+where
+    B: Backend,
+    F: FnOnce(&mut Frame<B>),
+{
+    if std::mem::take(dirty) {
+        terminal.draw(draw)?;
+    }
+    Ok(())
+}
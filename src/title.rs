@@ -0,0 +1,158 @@
+// The title screen shown once at startup, before the main menu: the logo
+// sweeping in from the left, the version and a hint of the day. Any key goes
+// on to the menu, whether the animation has finished or not.
+use std::time::{Duration, Instant};
+
+use ratatui::Frame;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use crate::app::{AppEvent, Screen, Step};
+use crate::layout;
+use crate::leaderboard;
+
+const LOGO: [&str; 4] = [
+    r" _____  ___   ___      _____    _     ___       ___   ___  ",
+    r"|_   _||_ _| / __|    |_   _|  /_\   / __|     / __| / _ \ ",
+    r"  | |   | | | (__  __   | |   / _ \ | (__  __ | (_ || (_) |",
+    r"  |_|  |___| \___|      |_|  /_/ \_\ \___|     \___| \___/ ",
+];
+// Columns where "TAC" and "GO" start, for colouring the words like the
+// pieces: crosses red, circles green, the player yellow
+const TAC_COLUMN: usize = 22;
+const GO_COLUMN: usize = 46;
+// Rows besides the logo: blank lines, version, hint and the key prompt
+const TEXT_ROWS: u16 = 7;
+
+// How long the logo takes to sweep in, and how fast the prompt blinks after
+const REVEAL: Duration = Duration::from_millis(700);
+const BLINK: Duration = Duration::from_millis(600);
+
+const HINTS: [&str; 8] = [
+    "Hold Shift with a direction to dash until something stops you.",
+    "Type a number before a move to repeat it: 3d is three steps right.",
+    "u takes a move back, as often as the difficulty allows.",
+    "c copies the seed, so a friend can play the same puzzle.",
+    "A new daily puzzle is waiting every day at midnight UTC.",
+    "The difficulty menu remembers your last choice and rules.",
+    "Without pulls, a cross pushed into a corner is there to stay.",
+    "Two crosses in a row only need a third to beat you.",
+];
+
+/// Finishes on the first key press.
+pub struct TitleScreen {
+    started: Instant,
+    // what the last frame showed, so ticks only redraw when it changes
+    shown: (usize, bool),
+}
+
+impl TitleScreen {
+    pub fn new() -> Self {
+        TitleScreen {
+            started: Instant::now(),
+            shown: (0, false),
+        }
+    }
+
+    /// Columns of the logo revealed so far and whether the prompt is lit.
+    fn view(&self) -> (usize, bool) {
+        let width = LOGO[0].len();
+        let elapsed = self.started.elapsed();
+        if crate::config::get().reduce_motion {
+            return (width, true);
+        }
+        if elapsed < REVEAL {
+            let revealed = width as u128 * elapsed.as_millis() / REVEAL.as_millis();
+            return (revealed as usize, false);
+        }
+        let blinks = (elapsed - REVEAL).as_millis() / BLINK.as_millis();
+        (width, blinks.is_multiple_of(2))
+    }
+}
+
+/// Today's hint; they take turns, one a day.
+fn hint() -> &'static str {
+    HINTS[(leaderboard::today() % HINTS.len() as u64) as usize]
+}
+
+fn logo_line(line: &str, revealed: usize) -> Spans<'static> {
+    let word = |from: usize, to: usize, color: Color| {
+        let text: String = (from..to)
+            .zip(line[from..to].chars())
+            .map(|(i, ch)| if i < revealed { ch } else { ' ' })
+            .collect();
+        Span::styled(
+            text,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )
+    };
+    Spans::from(vec![
+        word(0, TAC_COLUMN, Color::Red),
+        word(TAC_COLUMN, GO_COLUMN, Color::Green),
+        word(GO_COLUMN, line.len(), Color::Yellow),
+    ])
+}
+
+impl Screen for TitleScreen {
+    type Output = ();
+
+    fn update(&mut self, event: AppEvent) -> Step<()> {
+        match event {
+            AppEvent::KeyPressed(_) => Step::Done(()),
+            AppEvent::Tick if self.view() != self.shown => {
+                self.shown = self.view();
+                Step::Redraw
+            }
+            _ => Step::Idle,
+        }
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let (revealed, prompt) = self.view();
+        let size = f.size();
+        let logo_w = LOGO[0].len() as u16;
+        let hint = hint();
+        let overlay_w = logo_w.max(hint.len() as u16 + 6);
+        let overlay_h = LOGO.len() as u16 + TEXT_ROWS;
+        let area = layout::centered(size, overlay_w, overlay_h);
+
+        let mut lines: Vec<Spans> = Vec::new();
+        if size.width >= logo_w {
+            lines.extend(LOGO.iter().map(|line| logo_line(line, revealed)));
+        } else {
+            // too narrow for the logo: the name alone, on the logo's middle row
+            lines.push(Spans::from(Span::raw("")));
+            lines.push(Spans::from(Span::styled(
+                "tic-tac-go",
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            lines.extend([Spans::from(Span::raw("")), Spans::from(Span::raw(""))]);
+        }
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::styled(
+            format!("v{}", env!("CARGO_PKG_VERSION")),
+            Style::default().fg(Color::DarkGray),
+        )));
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw(format!("Hint: {}", hint))));
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw("")));
+        lines.push(Spans::from(Span::raw(if prompt {
+            "Press any key"
+        } else {
+            ""
+        })));
+
+        let para = Paragraph::new(lines).alignment(Alignment::Center);
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
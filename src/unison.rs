@@ -0,0 +1,75 @@
+// The unison rule: a move steps every circle at once, 2048-style, instead of
+// the player's circle alone.
+//
+// Circles move front first along the direction, each exactly as the player
+// would (`attempt_move_runtime`), so pushes are resolved in that order: a
+// circle that is blocked also blocks every circle queued up behind it, and a
+// cross between two circles is pushed by the one behind once the one in front
+// has moved on. Pulls are still made by the player's circle alone.
+//
+// Every circle moves at most one cell per move, which is what the solver's
+// lower bound relies on under this rule (see `heuristics`). `reverse` undoes
+// a move for the generator's scramble, but not exactly on every board (ice,
+// portals, circles that stood still), so a scrambled position is not solvable
+// by construction here; it is kept only once the solver has solved it, as
+// every generated puzzle is.
+use std::cmp::Reverse;
+
+use crate::board::Board;
+use crate::movement::attempt_move_runtime;
+
+/// Indices of the circles, the one farthest along (dr, dc) first.
+fn front_first(circles: &[(usize, usize)], dr: isize, dc: isize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..circles.len()).collect();
+    order.sort_by_key(|&i| {
+        let (r, c) = circles[i];
+        Reverse(r as isize * dr + c as isize * dc)
+    });
+    order
+}
+
+/// Step every circle by (dr, dc), each pushing whatever is in its way.
+pub fn step_all(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) {
+    for i in front_first(circles, dr, dc) {
+        attempt_move_runtime(circles, crosses, i, dr, dc, board);
+    }
+}
+
+/// A position that `step_all` in the opposite direction leads back to this
+/// one from: every circle with a free cell ahead steps by (dr, dc), pulling
+/// along the cross right behind it.
+pub fn reverse(
+    circles: &mut [(usize, usize)],
+    crosses: &mut [(usize, usize)],
+    dr: isize,
+    dc: isize,
+    board: &Board,
+) {
+    for i in front_first(circles, dr, dc) {
+        let (r, c) = circles[i];
+        let (nr, nc) = board.wrap(r as isize + dr, c as isize + dc);
+        if !board.has_cell(nr, nc) {
+            continue;
+        }
+        let ahead = (nr as usize, nc as usize);
+        if circles.contains(&ahead) || crosses.contains(&ahead) {
+            continue;
+        }
+        let (br, bc) = board.wrap(r as isize - dr, c as isize - dc);
+        if board.has_cell(br, bc)
+            && !board.is_heavy(br as usize, bc as usize)
+            && let Some(j) = crosses
+                .iter()
+                .position(|&p| p == (br as usize, bc as usize))
+        {
+            crosses[j] = (r, c);
+        }
+        circles[i] = ahead;
+    }
+}
@@ -0,0 +1,212 @@
+// `tic-tac-go validate`: sanity checks for a directory of puzzle files.
+//
+// Every *.json file is classified as one of:
+//   ok          - parses, is structurally sound and has a solution
+//   malformed   - not valid JSON, pieces off the board / overlapping, or a
+//                 board failing `Board::validate` (e.g. split into regions),
+//                 or circles that have already won
+//   lost        - the crosses are already lined up in the start position
+//   unsolvable  - the solver found no win within its node budget
+//   duplicate   - same as a puzzle in an earlier file, possibly mirrored or
+//                 rotated (compared by `PuzzleFile::canonical_hash`)
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use crate::generator::verify_puzzle_within;
+use crate::puzzle::PuzzleFile;
+
+const DEFAULT_MAX_NODES: usize = 500_000;
+
+struct Report {
+    file: String,
+    status: &'static str,
+    detail: Value,
+}
+
+fn puzzle_paths(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.eq_ignore_ascii_case("json"))
+                    .unwrap_or(false)
+        })
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn check_file(
+    path: &Path,
+    seen: &mut HashMap<u64, String>,
+    max_nodes: usize,
+) -> (&'static str, Value) {
+    let file = path.display().to_string();
+    let puzzle = match PuzzleFile::load(path) {
+        Ok(p) => p,
+        Err(e) => return ("malformed", json!({ "error": e.to_string() })),
+    };
+    let board = match puzzle.board() {
+        Ok(b) => b,
+        Err(e) => return ("malformed", json!({ "error": e.to_string() })),
+    };
+    if let Err(e) = puzzle.check(&board) {
+        return ("malformed", json!({ "error": e }));
+    }
+
+    let hash = puzzle.canonical_hash();
+    if let Some(original) = seen.get(&hash) {
+        return ("duplicate", json!({ "duplicate_of": original }));
+    }
+    seen.insert(hash, file);
+
+    let report = verify_puzzle_within(
+        &board,
+        &puzzle.circles,
+        &puzzle.crosses,
+        puzzle.player_idx(),
+        max_nodes,
+    );
+    if report.lost {
+        return ("lost", json!({ "error": report.problem() }));
+    }
+    if report.won {
+        return ("malformed", json!({ "error": report.problem() }));
+    }
+
+    match &report.solution {
+        Some(steps) => {
+            let solution: String = steps.iter().map(|s| s.notation()).collect();
+            (
+                "ok",
+                json!({
+                    "min_moves": report.optimal_moves(),
+                    "solution": solution,
+                    "deadlocked": report.deadlocked,
+                    "hash": format!("{:016x}", hash),
+                }),
+            )
+        }
+        None => (
+            "unsolvable",
+            json!({ "error": format!("no solution within {} states", max_nodes) }),
+        ),
+    }
+}
+
+fn print_usage() {
+    eprintln!("usage: tic-tac-go validate [DIR] [--json] [--max-nodes N]");
+    eprintln!();
+    eprintln!("Checks every puzzle file in DIR (default: puzzles) and reports");
+    eprintln!("malformed, lost, unsolvable and duplicate puzzles.");
+}
+
+/// Entry point for the `validate` subcommand. Returns `Ok(true)` when every
+/// puzzle in the directory passed.
+pub fn run(args: &[String]) -> Result<bool, Box<dyn Error>> {
+    let mut dir = PathBuf::from("puzzles");
+    let mut as_json = false;
+    let mut max_nodes = DEFAULT_MAX_NODES;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--json" => as_json = true,
+            "--max-nodes" => {
+                max_nodes = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--max-nodes expects a number")?;
+            }
+            "-h" | "--help" => {
+                print_usage();
+                return Ok(true);
+            }
+            other if other.starts_with('-') => {
+                print_usage();
+                return Err(format!("unknown option {}", other).into());
+            }
+            other => dir = PathBuf::from(other),
+        }
+    }
+
+    let mut seen: HashMap<u64, String> = HashMap::new();
+    let mut reports: Vec<Report> = Vec::new();
+    for path in puzzle_paths(&dir)? {
+        let (status, detail) = check_file(&path, &mut seen, max_nodes);
+        reports.push(Report {
+            file: path.display().to_string(),
+            status,
+            detail,
+        });
+    }
+
+    let count = |status: &str| reports.iter().filter(|r| r.status == status).count();
+    let all_ok = reports.iter().all(|r| r.status == "ok");
+
+    if as_json {
+        let puzzles: Vec<Value> = reports
+            .iter()
+            .map(|r| {
+                let mut obj = json!({ "file": r.file, "status": r.status });
+                if let (Some(o), Some(d)) = (obj.as_object_mut(), r.detail.as_object()) {
+                    o.extend(d.clone());
+                }
+                obj
+            })
+            .collect();
+        let out = json!({
+            "directory": dir.display().to_string(),
+            "summary": {
+                "total": reports.len(),
+                "ok": count("ok"),
+                "malformed": count("malformed"),
+                "lost": count("lost"),
+                "unsolvable": count("unsolvable"),
+                "duplicate": count("duplicate"),
+            },
+            "puzzles": puzzles,
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+    } else {
+        let width = reports.iter().map(|r| r.file.len()).max().unwrap_or(0);
+        for r in &reports {
+            let detail = if let Some(n) = r.detail.get("min_moves") {
+                format!("min moves {}", n)
+            } else if let Some(orig) = r.detail.get("duplicate_of").and_then(|v| v.as_str()) {
+                format!("same as {}", orig)
+            } else {
+                r.detail
+                    .get("error")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string()
+            };
+            println!(
+                "{:<width$}  {:<10}  {}",
+                r.file,
+                r.status,
+                detail,
+                width = width
+            );
+        }
+        println!(
+            "{} puzzles: {} ok, {} malformed, {} lost, {} unsolvable, {} duplicate",
+            reports.len(),
+            count("ok"),
+            count("malformed"),
+            count("lost"),
+            count("unsolvable"),
+            count("duplicate")
+        );
+    }
+
+    Ok(all_ok)
+}
@@ -0,0 +1,242 @@
+// The weekly challenge: one puzzle file per week, downloaded from a URL a
+// community agrees on, so everyone plays the same level.
+//
+// With `weekly_url` set in the config the main menu gets a "Weekly
+// challenge" entry. The URL serves a puzzle file in the usual JSON format and
+// is expected to change what it serves once a week (weeks start on Monday,
+// UTC). The first download of a week is kept in `tic-tac-go-weekly.json` in
+// the working directory and played from there for the rest of the week. When
+// the server can't be reached the cached puzzle, even an older one, can still
+// be played.
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::backend::Backend;
+use ratatui::layout::Alignment;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use reqwest::blocking::Client;
+use reqwest::header::{ACCEPT, USER_AGENT};
+use serde::{Deserialize, Serialize};
+
+use crate::app::{self, AppEvent, Screen, Step};
+use crate::game::{self, GameExit};
+use crate::generator::verify_puzzle;
+use crate::journal;
+use crate::layout;
+use crate::leaderboard;
+use crate::puzzle::PuzzleFile;
+use crate::term::Tui;
+
+pub const CACHE_FILE: &str = "tic-tac-go-weekly.json";
+
+// The download gives up after this long and falls back to the cache
+const TIMEOUT: Duration = Duration::from_secs(10);
+// 1970-01-01 was a Thursday; shifting by this many days starts weeks on Monday
+const MONDAY_OFFSET: u64 = 3;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Cache {
+    /// Week the puzzle was downloaded in (see `this_week`)
+    week: u64,
+    puzzle: PuzzleFile,
+}
+
+/// Whether the config names a weekly challenge URL.
+pub fn enabled() -> bool {
+    !crate::config::get().weekly_url.trim().is_empty()
+}
+
+/// Weeks since the Monday before the epoch.
+fn this_week() -> u64 {
+    (leaderboard::today() + MONDAY_OFFSET) / 7
+}
+
+/// The date of the Monday `week` starts on.
+fn week_start(week: u64) -> String {
+    journal::date((week * 7).saturating_sub(MONDAY_OFFSET) * 86_400)
+}
+
+fn load_cache() -> Option<Cache> {
+    let contents = fs::read_to_string(CACHE_FILE).ok()?;
+    serde_json::from_str(&contents)
+        .map_err(|e| log::warn!("{}: {}; ignoring it", CACHE_FILE, e))
+        .ok()
+}
+
+fn save_cache(cache: &Cache) -> Result<(), Box<dyn Error>> {
+    fs::write(Path::new(CACHE_FILE), serde_json::to_string(cache)?)?;
+    Ok(())
+}
+
+fn download(url: &str) -> Result<PuzzleFile, Box<dyn Error>> {
+    let client = Client::builder().timeout(TIMEOUT).build()?;
+    let resp = client
+        .get(url)
+        .header(ACCEPT, "application/json")
+        .header(USER_AGENT, "tic-tac-go")
+        .send()?;
+    let status = resp.status();
+    if !status.is_success() {
+        return Err(format!("server answered {}", status).into());
+    }
+    let puzzle = PuzzleFile::parse(&resp.text()?)?;
+    // reject a broken puzzle now rather than after it replaced the cache
+    let board = puzzle.board()?;
+    puzzle.check(&board)?;
+    let report = verify_puzzle(
+        &board,
+        &puzzle.circles,
+        &puzzle.crosses,
+        puzzle.player_idx(),
+    );
+    if let Some(problem) = report.problem() {
+        return Err(format!("the puzzle can't be played: {}", problem).into());
+    }
+    Ok(puzzle)
+}
+
+/// Waits for the download, then offers the cached puzzle if it failed.
+/// Finishes with the puzzle to play, or `None` to go back to the menu.
+struct FetchScreen {
+    rx: mpsc::Receiver<Result<PuzzleFile, String>>,
+    cache: Option<Cache>,
+    error: Option<String>,
+}
+
+impl Screen for FetchScreen {
+    type Output = Option<PuzzleFile>;
+
+    fn update(&mut self, event: AppEvent) -> Step<Option<PuzzleFile>> {
+        match event {
+            AppEvent::Tick if self.error.is_none() => match self.rx.try_recv() {
+                Ok(Ok(puzzle)) => Step::Done(Some(puzzle)),
+                Ok(Err(e)) => {
+                    log::warn!("weekly challenge download failed: {}", e);
+                    self.error = Some(e);
+                    Step::Redraw
+                }
+                Err(_) => Step::Idle,
+            },
+            AppEvent::KeyPressed(key) => match key.code {
+                KeyCode::Enter if self.error.is_some() => {
+                    Step::Done(self.cache.take().map(|c| c.puzzle))
+                }
+                KeyCode::Esc | KeyCode::Char('q') => Step::Done(None),
+                _ => Step::Idle,
+            },
+            _ => Step::Idle,
+        }
+    }
+
+    fn draw<B: Backend>(&self, f: &mut Frame<B>) {
+        let size = f.size();
+        let overlay_w = std::cmp::min(64, size.width.saturating_sub(4));
+        // room for a few lines of error message
+        let area = layout::centered(size, overlay_w, 12);
+        let mut lines = vec![
+            Spans::from(Span::styled(
+                " Weekly challenge ",
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(Span::raw("")),
+        ];
+        match (&self.error, &self.cache) {
+            (None, _) => {
+                lines.push(Spans::from(Span::raw("Fetching this week's puzzle...")));
+                lines.push(Spans::from(Span::raw("")));
+                lines.push(Spans::from(Span::raw("Esc: back")));
+            }
+            (Some(e), cache) => {
+                lines.push(Spans::from(Span::styled(
+                    format!("Download failed: {}", e),
+                    Style::default().fg(Color::Red),
+                )));
+                lines.push(Spans::from(Span::raw("")));
+                lines.push(Spans::from(Span::raw(match cache {
+                    Some(c) => format!(
+                        "Enter: play the saved puzzle from the week of {}",
+                        week_start(c.week)
+                    ),
+                    None => "No saved puzzle to play instead.".to_string(),
+                })));
+                lines.push(Spans::from(Span::raw("Esc: back")));
+            }
+        }
+        let para = Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("tic-tac-go"));
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::Black)),
+            area,
+        );
+        f.render_widget(para, area);
+    }
+}
+
+/// This week's puzzle: from the cache when it was downloaded this week,
+/// otherwise fetched, with the cache as the fallback. `None` when the player
+/// backed out.
+fn puzzle(terminal: &mut Tui) -> Result<Option<PuzzleFile>, Box<dyn Error>> {
+    let week = this_week();
+    let cache = load_cache();
+    if let Some(c) = cache.as_ref().filter(|c| c.week == week) {
+        return Ok(Some(c.puzzle.clone()));
+    }
+    let url = crate::config::get().weekly_url.clone();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(download(&url).map_err(|e| e.to_string()));
+    });
+    let mut screen = FetchScreen {
+        rx,
+        cache,
+        error: None,
+    };
+    let fetched = app::run(terminal, &mut screen)?;
+    // only a fresh download is cached; a cached puzzle comes back as it was
+    if screen.error.is_none()
+        && let Some(puzzle) = &fetched
+    {
+        let cache = Cache {
+            week,
+            puzzle: puzzle.clone(),
+        };
+        if let Err(e) = save_cache(&cache) {
+            log::warn!("could not save {}: {}", CACHE_FILE, e);
+        }
+    }
+    Ok(fetched)
+}
+
+/// Play the weekly challenge.
+pub fn play(terminal: &mut Tui) -> Result<GameExit, Box<dyn Error>> {
+    let Some(puzzle) = puzzle(terminal)? else {
+        return Ok(GameExit::Menu);
+    };
+    let board = puzzle.board()?;
+    let player_idx = puzzle.player_idx();
+    log::info!(
+        "weekly challenge for the week of {}",
+        week_start(this_week())
+    );
+    let (exit, _) = game::run_puzzle(
+        terminal,
+        board,
+        puzzle.circles,
+        puzzle.crosses,
+        player_idx,
+        None,
+    )?;
+    Ok(exit)
+}
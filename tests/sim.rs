@@ -0,0 +1,125 @@
+// Regression cases for the rule variants, played headlessly through
+// `tic-tac-go sim` (see src/sim.rs for the case file format).
+use std::fs;
+use std::process::Command;
+
+/// Run `case` through `tic-tac-go sim` and fail with its report unless the
+/// expected status came out. `name` keeps the scratch directories apart.
+fn check(name: &str, case: &str) {
+    let dir = std::env::temp_dir().join(format!("tic-tac-go-sim-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("case.txt");
+    fs::write(&path, case).unwrap();
+    // run in the scratch directory, which takes the log and keeps any config
+    // or stats of the checkout out of it
+    let output = Command::new(env!("CARGO_BIN_EXE_tic-tac-go"))
+        .arg("sim")
+        .arg(&path)
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+    assert!(
+        output.status.success(),
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn won_lost_and_playing() {
+    check("won", "moves l\nexpect won\no..\no..\n.@.\n");
+    check("lost", "moves L\nexpect lost\nx..o\nx..o\n.x@.\n");
+    check("playing", "moves u\nexpect playing\no..\no..\n.@.\n");
+    // moves after the end are ignored
+    check("after-end", "moves lr\nexpect won\no..\no..\n.@.\n");
+}
+
+#[test]
+fn stalemate() {
+    check("stalemate", "expect stalemate\n@X.o\nX...\n...o\n");
+    check("not-stalemate", "expect playing\n@x.o\nX...\n...o\n");
+}
+
+#[test]
+fn ice() {
+    // the circle slides over both ice cells up to the wall
+    check("ice", "moves R\nexpect won\n@o~~#\n...o.\n...o.\n");
+    check("no-ice", "moves R\nexpect playing\n@o..#\n...o.\n...o.\n");
+}
+
+#[test]
+fn portals() {
+    check("portal", "moves R\nexpect won\n@oA.\n...o\n...A\n...o\n");
+}
+
+#[test]
+fn wrap() {
+    check("wrap", "rules wrap\nmoves l\nexpect won\n..o\n@..\n..o\n");
+    check("no-wrap", "moves l\nexpect playing\n..o\n@..\n..o\n");
+}
+
+#[test]
+fn chain_push() {
+    check(
+        "chain",
+        "rules chain_push\nmoves R\nexpect won\n..o.\n@ox.\n..o.\n",
+    );
+    check("no-chain", "moves R\nexpect playing\n..o.\n@ox.\n..o.\n");
+}
+
+#[test]
+fn pull() {
+    check(
+        "pull",
+        "rules pull\nmoves pL\nexpect won\n....\no.@o\n....\n",
+    );
+    check("no-pull", "moves pL\nexpect playing\n....\no.@o\n....\n");
+}
+
+#[test]
+fn unison() {
+    check("unison", "rules unison\nmoves d\nexpect won\n@o.\n..o\n");
+    check("no-unison", "moves d\nexpect playing\n@o.\n..o\n");
+}
+
+#[test]
+fn sinkholes() {
+    check(
+        "sinkhole",
+        "rules sinkholes\nmoves R\nexpect won\n.o.\n@x\n.o.\n",
+    );
+    check("no-sinkhole", "moves R\nexpect playing\n.o.\n@x\n.o.\n");
+}
+
+#[test]
+fn heavy_crosses() {
+    check("heavy-still", "moves R\nexpect playing\n.o.\n@X.\n.o.\n");
+    check("heavy-line", "moves L\nexpect lost\nX..o\nX..o\n.x@.\n");
+}
+
+#[test]
+fn blocks() {
+    check("blocks", "moves L\nexpect playing\nb..o\nb..o\n.b@.\n");
+}
+
+#[test]
+fn bombs() {
+    // the blast clears the crosses either side before they count
+    check("bomb", "moves U\nexpect playing\nx*x.\n.x..\no@.o\n");
+}
+
+#[test]
+fn powerups() {
+    let board = "o...\no...\nx...\n.+@.\n";
+    check("powerup", &format!("moves l u xL l\nexpect won\n{}", board));
+    // a removal without a power-up in hand does not count
+    check(
+        "no-powerup",
+        &format!(
+            "moves xU l l U\nexpect playing\n{}",
+            board.replace('+', ".")
+        ),
+    );
+}